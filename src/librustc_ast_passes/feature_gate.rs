@@ -244,6 +244,8 @@ impl<'a> Visitor<'a> for PostExpansionVisitor<'a> {
                 gate_doc!(
                     include => external_doc
                     cfg => doc_cfg
+                    auto_cfg => doc_cfg
+                    cfg_hide => doc_cfg
                     masked => doc_masked
                     alias => doc_alias
                     keyword => doc_keyword
@@ -219,6 +219,28 @@ pub fn expand_test_or_bench(
                                     field("ignore", cx.expr_bool(sp, should_ignore(&item))),
                                     // allow_fail: true | false
                                     field("allow_fail", cx.expr_bool(sp, should_fail(&item))),
+                                    // timeout: None | Some(secs)
+                                    field(
+                                        "timeout",
+                                        match test_timeout(cx, &item) {
+                                            Some(secs) => cx.expr_some(sp, cx.expr_u64(sp, secs)),
+                                            None => cx.expr_path(cx.path_global(
+                                                sp,
+                                                cx.std_path(&[sym::option, sym::Option, sym::None]),
+                                            )),
+                                        },
+                                    ),
+                                    // retries: None | Some(n)
+                                    field(
+                                        "retries",
+                                        match test_retries(cx, &item) {
+                                            Some(n) => cx.expr_some(sp, cx.expr_usize(sp, n)),
+                                            None => cx.expr_path(cx.path_global(
+                                                sp,
+                                                cx.std_path(&[sym::option, sym::Option, sym::None]),
+                                            )),
+                                        },
+                                    ),
                                     // should_panic: ...
                                     field(
                                         "should_panic",
@@ -310,6 +332,50 @@ fn should_fail(i: &ast::Item) -> bool {
     attr::contains_name(&i.attrs, sym::allow_fail)
 }
 
+fn test_timeout(cx: &ExtCtxt<'_>, i: &ast::Item) -> Option<u64> {
+    let attr = attr::find_by_name(&i.attrs, sym::test_timeout)?;
+    // Handle #[test_timeout(60)]
+    let secs = match attr.meta_item_list().as_deref() {
+        Some([nested]) => match nested.literal().map(|lit| &lit.kind) {
+            Some(ast::LitKind::Int(secs, _)) if *secs <= u64::MAX as u128 => Some(*secs as u64),
+            _ => None,
+        },
+        _ => None,
+    };
+    // A test would time out right away, so `--test-timeout 0` is rejected as well.
+    let msg = match secs {
+        Some(0) => "argument for `test_timeout` must not be 0",
+        Some(secs) => return Some(secs),
+        None => "argument must be a number of seconds",
+    };
+    cx.parse_sess
+        .span_diagnostic
+        .struct_span_err(attr.span, msg)
+        .help("use `#[test_timeout(60)]` to fail the test after one minute")
+        .emit();
+    None
+}
+
+fn test_retries(cx: &ExtCtxt<'_>, i: &ast::Item) -> Option<usize> {
+    let attr = attr::find_by_name(&i.attrs, sym::test_retries)?;
+    // Handle #[test_retries(3)]
+    let retries = match attr.meta_item_list().as_deref() {
+        Some([nested]) => match nested.literal().map(|lit| &lit.kind) {
+            Some(ast::LitKind::Int(n, _)) if *n <= usize::MAX as u128 => Some(*n as usize),
+            _ => None,
+        },
+        _ => None,
+    };
+    if retries.is_none() {
+        cx.parse_sess
+            .span_diagnostic
+            .struct_span_err(attr.span, "argument must be a number of retries")
+            .help("use `#[test_retries(2)]` to run the test up to three times")
+            .emit();
+    }
+    retries
+}
+
 fn should_panic(cx: &ExtCtxt<'_>, i: &ast::Item) -> ShouldPanic {
     match attr::find_by_name(&i.attrs, sym::should_panic) {
         Some(attr) => {
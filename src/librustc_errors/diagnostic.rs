@@ -5,6 +5,7 @@ use crate::Level;
 use crate::Substitution;
 use crate::SubstitutionPart;
 use crate::SuggestionStyle;
+use crate::TranslatableMessage;
 use rustc_span::{MultiSpan, Span, DUMMY_SP};
 use std::fmt;
 
@@ -22,6 +23,16 @@ pub struct Diagnostic {
     /// as a sort key to sort a buffer of diagnostics.  By default, it is the primary span of
     /// `span` if there is one.  Otherwise, it is `DUMMY_SP`.
     pub sort_span: Span,
+
+    /// The identifier and the arguments of the message, if it can be translated.
+    pub translation: Option<TranslatableMessage>,
+    /// The labels of `span` that can be translated.
+    pub label_translations: Vec<(Span, TranslatableMessage)>,
+
+    /// The primary span of the error this diagnostic is a consequence of, if any. Unless
+    /// `-Z verbose-diagnostics` is given, an error that is the consequence of an error that was
+    /// emitted is only counted, and not shown.
+    pub caused_by: Option<Span>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, RustcEncodable, RustcDecodable)]
@@ -37,6 +48,8 @@ pub struct SubDiagnostic {
     pub message: Vec<(String, Style)>,
     pub span: MultiSpan,
     pub render_span: Option<MultiSpan>,
+    /// The identifier and the arguments of the message, if it can be translated.
+    pub translation: Option<TranslatableMessage>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -100,6 +113,9 @@ impl Diagnostic {
             children: vec![],
             suggestions: vec![],
             sort_span: DUMMY_SP,
+            translation: None,
+            label_translations: vec![],
+            caused_by: None,
         }
     }
 
@@ -140,6 +156,13 @@ impl Diagnostic {
         self
     }
 
+    /// Adds a label that can be translated. See `span_label`.
+    pub fn span_label_translatable(&mut self, span: Span, label: TranslatableMessage) -> &mut Self {
+        self.span.push_span_label(span, label.english());
+        self.label_translations.push((span, label));
+        self
+    }
+
     pub fn replace_span_with(&mut self, after: Span) -> &mut Self {
         let before = self.span.clone();
         self.set_span(after);
@@ -234,6 +257,11 @@ impl Diagnostic {
         self
     }
 
+    pub fn note_translatable(&mut self, msg: TranslatableMessage) -> &mut Self {
+        self.sub_translatable(Level::Note, msg, MultiSpan::new());
+        self
+    }
+
     pub fn highlighted_note(&mut self, msg: Vec<(String, Style)>) -> &mut Self {
         self.sub_with_highlights(Level::Note, msg, MultiSpan::new(), None);
         self
@@ -245,6 +273,15 @@ impl Diagnostic {
         self
     }
 
+    pub fn span_note_translatable<S: Into<MultiSpan>>(
+        &mut self,
+        sp: S,
+        msg: TranslatableMessage,
+    ) -> &mut Self {
+        self.sub_translatable(Level::Note, msg, sp.into());
+        self
+    }
+
     pub fn warn(&mut self, msg: &str) -> &mut Self {
         self.sub(Level::Warning, msg, MultiSpan::new(), None);
         self
@@ -261,6 +298,11 @@ impl Diagnostic {
         self
     }
 
+    pub fn help_translatable(&mut self, msg: TranslatableMessage) -> &mut Self {
+        self.sub_translatable(Level::Help, msg, MultiSpan::new());
+        self
+    }
+
     /// Prints the span with some help above it.
     pub fn span_help<S: Into<MultiSpan>>(&mut self, sp: S, msg: &str) -> &mut Self {
         self.sub(Level::Help, msg, sp.into(), None);
@@ -480,6 +522,13 @@ impl Diagnostic {
         self
     }
 
+    /// Marks the diagnostic as a consequence of the error whose primary span is `root`, e.g. an
+    /// unresolved name, which it is grouped with.
+    pub fn caused_by(&mut self, root: Span) -> &mut Self {
+        self.caused_by = Some(root);
+        self
+    }
+
     pub fn clear_code(&mut self) -> &mut Self {
         self.code = None;
         self
@@ -494,6 +543,13 @@ impl Diagnostic {
         self
     }
 
+    /// Sets the primary message to a message that can be translated.
+    pub fn set_translatable_message(&mut self, msg: TranslatableMessage) -> &mut Self {
+        self.message = vec![(msg.english(), Style::NoStyle)];
+        self.translation = Some(msg);
+        self
+    }
+
     pub fn message(&self) -> String {
         self.message.iter().map(|i| i.0.as_str()).collect::<String>()
     }
@@ -506,6 +562,7 @@ impl Diagnostic {
     /// message".
     pub fn copy_details_not_message(&mut self, from: &Diagnostic) {
         self.span = from.span.clone();
+        self.label_translations = from.label_translations.clone();
         self.code = from.code.clone();
         self.children.extend(from.children.iter().cloned())
     }
@@ -524,6 +581,20 @@ impl Diagnostic {
             message: vec![(message.to_owned(), Style::NoStyle)],
             span,
             render_span,
+            translation: None,
+        };
+        self.children.push(sub);
+    }
+
+    /// Convenience function for internal use, clients should use one of the
+    /// public methods above.
+    fn sub_translatable(&mut self, level: Level, msg: TranslatableMessage, span: MultiSpan) {
+        let sub = SubDiagnostic {
+            level,
+            message: vec![(msg.english(), Style::NoStyle)],
+            span,
+            render_span: None,
+            translation: Some(msg),
         };
         self.children.push(sub);
     }
@@ -537,7 +608,7 @@ impl Diagnostic {
         span: MultiSpan,
         render_span: Option<MultiSpan>,
     ) {
-        let sub = SubDiagnostic { level, message, span, render_span };
+        let sub = SubDiagnostic { level, message, span, render_span, translation: None };
         self.children.push(sub);
     }
 }
@@ -1,5 +1,5 @@
 use crate::{Applicability, Handler, Level, StashKey};
-use crate::{Diagnostic, DiagnosticId, DiagnosticStyledString};
+use crate::{Diagnostic, DiagnosticId, DiagnosticStyledString, TranslatableMessage};
 
 use log::debug;
 use rustc_span::{MultiSpan, Span};
@@ -194,6 +194,12 @@ impl<'a> DiagnosticBuilder<'a> {
         self
     }
 
+    forward!(pub fn span_label_translatable(
+        &mut self,
+        span: Span,
+        label: TranslatableMessage,
+    ) -> &mut Self);
+
     /// Labels all the given spans with the provided label.
     /// See `span_label` for more information.
     pub fn span_labels(
@@ -238,9 +244,16 @@ impl<'a> DiagnosticBuilder<'a> {
         sp: S,
         msg: &str,
     ) -> &mut Self);
+    forward!(pub fn note_translatable(&mut self, msg: TranslatableMessage) -> &mut Self);
+    forward!(pub fn span_note_translatable<S: Into<MultiSpan>>(
+        &mut self,
+        sp: S,
+        msg: TranslatableMessage,
+    ) -> &mut Self);
     forward!(pub fn warn(&mut self, msg: &str) -> &mut Self);
     forward!(pub fn span_warn<S: Into<MultiSpan>>(&mut self, sp: S, msg: &str) -> &mut Self);
     forward!(pub fn help(&mut self, msg: &str) -> &mut Self);
+    forward!(pub fn help_translatable(&mut self, msg: TranslatableMessage) -> &mut Self);
     forward!(pub fn span_help<S: Into<MultiSpan>>(
         &mut self,
         sp: S,
@@ -359,6 +372,8 @@ impl<'a> DiagnosticBuilder<'a> {
 
     forward!(pub fn set_span<S: Into<MultiSpan>>(&mut self, sp: S) -> &mut Self);
     forward!(pub fn code(&mut self, s: DiagnosticId) -> &mut Self);
+    forward!(pub fn caused_by(&mut self, root: Span) -> &mut Self);
+    forward!(pub fn set_translatable_message(&mut self, msg: TranslatableMessage) -> &mut Self);
 
     pub fn allow_suggestions(&mut self, allow: bool) -> &mut Self {
         self.0.allow_suggestions = allow;
@@ -429,6 +444,16 @@ macro_rules! struct_span_err {
     })
 }
 
+/// Like `struct_span_err!`, with a message that can be translated.
+#[macro_export]
+macro_rules! struct_span_err_translatable {
+    ($session:expr, $span:expr, $code:ident, $message:expr $(,)?) => {{
+        let mut err = $session.struct_span_err_with_code($span, "", $crate::error_code!($code));
+        err.set_translatable_message($message);
+        err
+    }};
+}
+
 #[macro_export]
 macro_rules! error_code {
     ($code:ident) => {{ $crate::DiagnosticId::Error(stringify!($code).to_owned()) }};
@@ -319,6 +319,7 @@ pub trait Emitter {
                     message: vec![(msg, Style::NoStyle)],
                     span: MultiSpan::new(),
                     render_span: None,
+                    translation: None,
                 });
             }
         }
@@ -0,0 +1,277 @@
+//! Application of the suggestions of the diagnostics to the source code.
+//!
+//! With `-Z apply-suggestions`, the `FixingEmitter` collects the suggestions of the diagnostics
+//! it passes to another emitter, and rewrites the source files with them once it is dropped, at
+//! the end of the compilation. Only the suggestions that are machine-applicable, or also maybe
+//! incorrect, and that don't offer alternatives are applied.
+//!
+//! The suggestions are applied in the order of the diagnostics. A suggestion whose edits overlap
+//! the edits of a suggestion applied before it is skipped, since the two can't be combined, but
+//! a suggestion made several times is applied once. The suggestions in the code of macros, those
+//! whose parts overlap each other, and those for files that changed on disk since they were read,
+//! are never applied. None are applied if the compilation is aborted.
+
+use crate::emitter::Emitter;
+use crate::{Applicability, CodeSuggestion, Diagnostic, Level};
+
+use rustc_data_structures::sync::Lrc;
+use rustc_span::source_map::SourceMap;
+use rustc_span::{FileName, SourceFile, Span};
+use std::fs;
+use std::mem;
+use std::ops::Range;
+use std::path::Path;
+use std::thread;
+
+#[cfg(test)]
+mod tests;
+
+/// A replacement of some of the text of a file.
+#[derive(Clone, Debug, PartialEq)]
+struct Edit {
+    /// The replaced bytes, in the file as it is on disk.
+    range: Range<usize>,
+    snippet: String,
+}
+
+impl Edit {
+    fn overlaps(&self, other: &Edit) -> bool {
+        // Two insertions at the same place could be made in either order.
+        self.range.start < other.range.end && other.range.start < self.range.end
+            || self.range == other.range
+    }
+}
+
+/// A suggestion to apply.
+struct Fix {
+    msg: String,
+    /// Where the suggestion is reported.
+    span: Span,
+    edits: Vec<(Lrc<SourceFile>, Edit)>,
+}
+
+/// The edits to make to a file, and the suggestions they come from.
+struct FileFixes {
+    sf: Lrc<SourceFile>,
+    edits: Vec<Edit>,
+    fixes: Vec<(String, Span)>,
+}
+
+/// An emitter that applies the suggestions of the diagnostics passed to another emitter, which
+/// prints them.
+pub struct FixingEmitter {
+    emitter: Box<dyn Emitter + Send>,
+    sm: Lrc<SourceMap>,
+    /// Whether the suggestions that may be incorrect are applied, and not only those that are
+    /// machine-applicable.
+    maybe_incorrect: bool,
+    fixes: Vec<Fix>,
+}
+
+impl FixingEmitter {
+    /// Creates an emitter applying the suggestions that are at least as applicable as
+    /// `applicability`, either `MachineApplicable` or `MaybeIncorrect`.
+    pub fn new(
+        emitter: Box<dyn Emitter + Send>,
+        source_map: Lrc<SourceMap>,
+        applicability: Applicability,
+    ) -> FixingEmitter {
+        FixingEmitter {
+            emitter,
+            sm: source_map,
+            maybe_incorrect: applicability == Applicability::MaybeIncorrect,
+            fixes: Vec::new(),
+        }
+    }
+
+    fn is_applied(&self, applicability: Applicability) -> bool {
+        match applicability {
+            Applicability::MachineApplicable => true,
+            Applicability::MaybeIncorrect => self.maybe_incorrect,
+            Applicability::HasPlaceholders | Applicability::Unspecified => false,
+        }
+    }
+
+    fn fix(&self, suggestion: &CodeSuggestion) -> Option<Fix> {
+        // The substitutions of a suggestion are alternatives, which only the user can choose
+        // from.
+        let parts = match &suggestion.substitutions[..] {
+            [substitution] if !substitution.parts.is_empty() => &substitution.parts,
+            _ => return None,
+        };
+        let mut edits = Vec::with_capacity(parts.len());
+        for part in parts {
+            if part.span.is_dummy() || part.span.from_expansion() {
+                return None;
+            }
+            let sf = self.sm.lookup_byte_offset(part.span.lo()).sf;
+            if !sf.contains(part.span.hi()) {
+                return None;
+            }
+            let start = sf.original_relative_byte_pos(part.span.lo()).0 as usize;
+            let end = sf.original_relative_byte_pos(part.span.hi()).0 as usize;
+            edits.push((sf, Edit { range: start..end, snippet: part.snippet.clone() }));
+        }
+        // The parts of a suggestion are all applied, so they can't overlap each other.
+        let overlaps = edits.iter().enumerate().any(|(i, (sf, edit))| {
+            edits[..i].iter().any(|(other_sf, other)| {
+                Lrc::ptr_eq(sf, other_sf) && other != edit && other.overlaps(edit)
+            })
+        });
+        if overlaps {
+            return None;
+        }
+        Some(Fix { msg: suggestion.msg.clone(), span: parts[0].span, edits })
+    }
+
+    /// Sorts the edits of the suggestions by file, and returns the suggestions that overlap
+    /// others.
+    fn resolve(&mut self) -> (Vec<FileFixes>, Vec<Fix>) {
+        let mut files: Vec<FileFixes> = Vec::new();
+        let mut skipped = Vec::new();
+        for fix in mem::take(&mut self.fixes) {
+            let overlaps = fix.edits.iter().any(|(sf, edit)| {
+                files
+                    .iter()
+                    .filter(|file| Lrc::ptr_eq(&file.sf, sf))
+                    .flat_map(|file| &file.edits)
+                    .any(|e| e != edit && e.overlaps(edit))
+            });
+            if overlaps {
+                skipped.push(fix);
+                continue;
+            }
+
+            let mut is_new = false;
+            for (sf, edit) in &fix.edits {
+                let file = file_fixes(&mut files, sf);
+                if !file.edits.contains(edit) {
+                    file.edits.push(edit.clone());
+                    is_new = true;
+                }
+            }
+            if is_new {
+                file_fixes(&mut files, &fix.edits[0].0).fixes.push((fix.msg, fix.span));
+            }
+        }
+        (files, skipped)
+    }
+
+    fn apply(&mut self) {
+        let (files, skipped) = self.resolve();
+        for fix in skipped {
+            let mut diag = Diagnostic::new(
+                Level::Warning,
+                "suggestion not applied, as it overlaps another suggestion",
+            );
+            diag.set_span(fix.span).note(&format!("the suggestion is: {}", fix.msg));
+            self.emitter.emit_diagnostic(&diag);
+        }
+
+        for mut file in files {
+            let name = file.sf.unmapped_path.as_ref().unwrap_or(&file.sf.name);
+            let diag = match name {
+                FileName::Real(path) => match rewrite(path, &file.sf, &mut file.edits) {
+                    Ok(()) => {
+                        let count = file.fixes.len();
+                        let mut diag = Diagnostic::new(
+                            Level::Note,
+                            &format!(
+                                "applied {} suggestion{} to `{}`",
+                                count,
+                                if count == 1 { "" } else { "s" },
+                                path.display()
+                            ),
+                        );
+                        for (msg, span) in file.fixes {
+                            diag.span_note(span, &msg);
+                        }
+                        diag
+                    }
+                    Err(e) => Diagnostic::new(
+                        Level::Warning,
+                        &format!("couldn't apply suggestions to `{}`: {}", path.display(), e),
+                    ),
+                },
+                _ => Diagnostic::new(
+                    Level::Warning,
+                    &format!("couldn't apply suggestions to `{}`, which isn't a file", name),
+                ),
+            };
+            self.emitter.emit_diagnostic(&diag);
+        }
+    }
+}
+
+fn file_fixes<'a>(files: &'a mut Vec<FileFixes>, sf: &Lrc<SourceFile>) -> &'a mut FileFixes {
+    match files.iter().position(|file| Lrc::ptr_eq(&file.sf, sf)) {
+        Some(i) => &mut files[i],
+        None => {
+            files.push(FileFixes { sf: sf.clone(), edits: Vec::new(), fixes: Vec::new() });
+            files.last_mut().unwrap()
+        }
+    }
+}
+
+fn rewrite(path: &Path, sf: &SourceFile, edits: &mut [Edit]) -> Result<(), String> {
+    let src = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    if !sf.src_hash.matches(&src) {
+        return Err("the file changed during the compilation".to_owned());
+    }
+    fs::write(path, apply_edits(&src, edits)).map_err(|e| e.to_string())
+}
+
+/// Applies edits that don't overlap to `src`.
+fn apply_edits(src: &str, edits: &mut [Edit]) -> String {
+    edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+    let mut fixed = String::with_capacity(src.len());
+    let mut pos = 0;
+    for edit in edits.iter() {
+        fixed.push_str(&src[pos..edit.range.start]);
+        fixed.push_str(&edit.snippet);
+        pos = edit.range.end;
+    }
+    fixed.push_str(&src[pos..]);
+    fixed
+}
+
+impl Emitter for FixingEmitter {
+    fn emit_diagnostic(&mut self, diag: &Diagnostic) {
+        for suggestion in &diag.suggestions {
+            if self.is_applied(suggestion.applicability) {
+                if let Some(fix) = self.fix(suggestion) {
+                    self.fixes.push(fix);
+                }
+            }
+        }
+        self.emitter.emit_diagnostic(diag);
+    }
+
+    fn emit_artifact_notification(&mut self, path: &Path, artifact_type: &str) {
+        self.emitter.emit_artifact_notification(path, artifact_type);
+    }
+
+    fn should_show_explain(&self) -> bool {
+        self.emitter.should_show_explain()
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        self.emitter.source_map()
+    }
+}
+
+impl Drop for FixingEmitter {
+    fn drop(&mut self) {
+        // The emitter is dropped while unwinding after a fatal error, or a bug of the compiler,
+        // when the suggestions may be incomplete.
+        if !thread::panicking() {
+            self.apply();
+        } else if !self.fixes.is_empty() {
+            let diag = Diagnostic::new(
+                Level::Note,
+                "no suggestions were applied, as the compilation was aborted",
+            );
+            self.emitter.emit_diagnostic(&diag);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use super::*;
+
+use rustc_span::source_map::FilePathMapping;
+use rustc_span::BytePos;
+
+struct Silent;
+
+impl Emitter for Silent {
+    fn emit_diagnostic(&mut self, _: &Diagnostic) {}
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        None
+    }
+}
+
+fn with_default_globals<R>(f: impl FnOnce() -> R) -> R {
+    let globals = rustc_span::Globals::new(rustc_span::edition::DEFAULT_EDITION);
+    rustc_span::GLOBALS.set(&globals, || rustc_span::GLOBALS.set(&globals, f))
+}
+
+fn edit(range: Range<usize>, snippet: &str) -> Edit {
+    Edit { range, snippet: snippet.to_owned() }
+}
+
+/// Emits warnings with suggestions replacing `lo..hi` by a snippet, with some applicability,
+/// about `code`, and returns the edits to the file and the messages of the skipped suggestions.
+fn resolve(
+    code: &str,
+    applicability: Applicability,
+    suggestions: &[(u32, u32, &str, Applicability)],
+) -> (Vec<Edit>, Vec<String>) {
+    with_default_globals(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("test.rs").to_owned().into(), code.to_owned());
+        let mut emitter = FixingEmitter::new(Box::new(Silent), sm, applicability);
+        for &(lo, hi, snippet, applicability) in suggestions {
+            let span = Span::with_root_ctxt(BytePos(lo), BytePos(hi));
+            let mut diag = Diagnostic::new(Level::Warning, "warning");
+            diag.span_suggestion(span, snippet, snippet.to_owned(), applicability);
+            emitter.emit_diagnostic(&diag);
+        }
+        let (files, skipped) = emitter.resolve();
+        let edits = files.into_iter().flat_map(|file| file.edits).collect();
+        (edits, skipped.into_iter().map(|fix| fix.msg).collect())
+    })
+}
+
+#[test]
+fn apply() {
+    let mut edits = vec![edit(8..9, "2"), edit(4..5, "_x"), edit(10..10, " // two")];
+    assert_eq!(apply_edits("let x = 1;", &mut edits), "let _x = 2; // two");
+}
+
+#[test]
+fn overlaps() {
+    assert!(edit(0..3, "a").overlaps(&edit(2..4, "b")));
+    assert!(edit(2..2, "a").overlaps(&edit(2..2, "b")));
+    assert!(!edit(0..2, "a").overlaps(&edit(2..4, "b")));
+    assert!(!edit(2..2, "a").overlaps(&edit(2..4, "b")));
+}
+
+#[test]
+fn applicability() {
+    let suggestions = [
+        (0, 1, "machine-applicable", Applicability::MachineApplicable),
+        (1, 2, "maybe-incorrect", Applicability::MaybeIncorrect),
+        (2, 3, "has-placeholders", Applicability::HasPlaceholders),
+        (3, 4, "unspecified", Applicability::Unspecified),
+    ];
+    let (edits, _) = resolve("abcd", Applicability::MachineApplicable, &suggestions);
+    assert_eq!(edits, vec![edit(0..1, "machine-applicable")]);
+    let (edits, _) = resolve("abcd", Applicability::MaybeIncorrect, &suggestions);
+    assert_eq!(edits, vec![edit(0..1, "machine-applicable"), edit(1..2, "maybe-incorrect")]);
+}
+
+#[test]
+fn overlapping_suggestions() {
+    let suggestions = [
+        (0, 2, "a", Applicability::MachineApplicable),
+        (1, 3, "b", Applicability::MachineApplicable),
+        (0, 2, "a", Applicability::MachineApplicable),
+        (3, 3, "c", Applicability::MachineApplicable),
+    ];
+    let (edits, skipped) = resolve("abcd", Applicability::MachineApplicable, &suggestions);
+    assert_eq!(edits, vec![edit(0..2, "a"), edit(3..3, "c")]);
+    assert_eq!(skipped, vec!["b".to_owned()]);
+}
+
+#[test]
+fn overlapping_parts() {
+    // A suggestion whose parts overlap each other can't be applied.
+    with_default_globals(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("test.rs").to_owned().into(), "abcd".to_owned());
+        let mut emitter =
+            FixingEmitter::new(Box::new(Silent), sm, Applicability::MachineApplicable);
+        let span = |lo, hi| Span::with_root_ctxt(BytePos(lo), BytePos(hi));
+        let mut diag = Diagnostic::new(Level::Warning, "warning");
+        diag.multipart_suggestion(
+            "overlapping",
+            vec![(span(0, 2), "a".to_owned()), (span(1, 3), "b".to_owned())],
+            Applicability::MachineApplicable,
+        );
+        diag.multipart_suggestion(
+            "disjoint",
+            vec![(span(0, 1), "a".to_owned()), (span(1, 3), "b".to_owned())],
+            Applicability::MachineApplicable,
+        );
+        emitter.emit_diagnostic(&diag);
+        let (files, skipped) = emitter.resolve();
+        let edits: Vec<_> = files.into_iter().flat_map(|file| file.edits).collect();
+        assert_eq!(edits, vec![edit(0..1, "a"), edit(1..3, "b")]);
+        assert!(skipped.is_empty());
+    })
+}
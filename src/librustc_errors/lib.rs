@@ -13,7 +13,7 @@ use Level::*;
 
 use emitter::{is_case_difference, Emitter, EmitterWriter};
 use registry::Registry;
-use rustc_data_structures::fx::{FxHashSet, FxIndexMap};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexMap};
 use rustc_data_structures::stable_hasher::StableHasher;
 use rustc_data_structures::sync::{self, Lock, Lrc};
 use rustc_data_structures::AtomicRef;
@@ -31,12 +31,16 @@ pub mod annotate_snippet_emitter_writer;
 mod diagnostic;
 mod diagnostic_builder;
 pub mod emitter;
+pub mod fix;
 pub mod json;
 mod lock;
 pub mod registry;
+pub mod sarif;
 mod snippet;
 mod styled_buffer;
+pub mod translation;
 pub use snippet::Style;
+pub use translation::TranslatableMessage;
 
 pub type PResult<'a, T> = Result<T, DiagnosticBuilder<'a>>;
 
@@ -318,6 +322,13 @@ struct HandlerInner {
 
     /// The warning count, used for a recap upon finishing
     deduplicated_warn_count: usize,
+
+    /// The root of each error that was emitted, by the primary span of the error. An error is
+    /// its own root, unless it is a consequence of another error (see `Diagnostic::caused_by`).
+    error_roots: FxHashMap<Span, Span>,
+    /// The number of errors that weren't shown because they are consequences of the error
+    /// whose primary span is the key.
+    hidden_consequences: FxIndexMap<Span, usize>,
 }
 
 /// A key denoting where from a diagnostic was stashed.
@@ -350,6 +361,9 @@ pub struct HandlerFlags {
     pub macro_backtrace: bool,
     /// If true, identical diagnostics are reported only once.
     pub deduplicate_diagnostics: bool,
+    /// If false, the errors that are consequences of an error that was emitted are only
+    /// counted, and not shown. (rustc: see `-Z verbose-diagnostics`)
+    pub verbose_diagnostics: bool,
 }
 
 impl Drop for HandlerInner {
@@ -427,6 +441,8 @@ impl Handler {
                 emitted_diagnostic_codes: Default::default(),
                 emitted_diagnostics: Default::default(),
                 stashed_diagnostics: Default::default(),
+                error_roots: Default::default(),
+                hidden_consequences: Default::default(),
             }),
         }
     }
@@ -454,6 +470,8 @@ impl Handler {
         inner.emitted_diagnostic_codes = Default::default();
         inner.emitted_diagnostics = Default::default();
         inner.stashed_diagnostics = Default::default();
+        inner.error_roots = Default::default();
+        inner.hidden_consequences = Default::default();
     }
 
     /// Stash a given diagnostic with the given `Span` and `StashKey` as the key for later stealing.
@@ -735,6 +753,21 @@ impl HandlerInner {
 
         (*TRACK_DIAGNOSTICS)(diagnostic);
 
+        if diagnostic.level == Level::Error {
+            let root = diagnostic.caused_by.and_then(|cause| self.error_roots.get(&cause).copied());
+            if let Some(span) = diagnostic.span.primary_span() {
+                self.error_roots.entry(span).or_insert(root.unwrap_or(span));
+            }
+            // Consequences of errors that were emitted are collapsed under their root.
+            if let Some(root) = root {
+                if !self.flags.verbose_diagnostics {
+                    *self.hidden_consequences.entry(root).or_insert(0) += 1;
+                    self.bump_err_count();
+                    return;
+                }
+            }
+        }
+
         if let Some(ref code) = diagnostic.code {
             self.emitted_diagnostic_codes.insert(code.clone());
         }
@@ -770,8 +803,26 @@ impl HandlerInner {
         self.flags.treat_err_as_bug.map(|c| self.err_count() >= c).unwrap_or(false)
     }
 
+    /// Points at the roots of the errors that weren't shown, with their number.
+    fn emit_hidden_consequences(&mut self) {
+        for (root, count) in std::mem::take(&mut self.hidden_consequences) {
+            let mut diag = Diagnostic::new(
+                Level::Note,
+                &format!(
+                    "{} more error{} caused by this error {} not shown",
+                    count,
+                    pluralize!(count),
+                    if count == 1 { "is" } else { "are" }
+                ),
+            );
+            diag.set_span(root).help("pass `-Z verbose-diagnostics` to show all the errors");
+            self.emit_diagnostic(&diag);
+        }
+    }
+
     fn print_error_count(&mut self, registry: &Registry) {
         self.emit_stashed_diagnostics();
+        self.emit_hidden_consequences();
 
         let warnings = match self.deduplicated_warn_count {
             0 => String::new(),
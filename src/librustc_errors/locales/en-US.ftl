@@ -0,0 +1,9 @@
+# The English text of the translatable messages of the compiler.
+#
+# This catalog is bundled with the compiler: the English text of a message is built from it when
+# the diagnostic is created. The catalogs of the other languages are installed in
+# `lib/rustlib/locales` in the sysroot, and are selected with `-Z translate-lang`.
+
+typeck-field-multiply-specified = field `{$ident}` specified more than once
+typeck-field-multiply-specified-label = used more than once
+typeck-field-first-use = first use of `{$ident}`
@@ -0,0 +1,328 @@
+//! A SARIF emitter for errors.
+//!
+//! SARIF, the Static Analysis Results Interchange Format, is the JSON format that code scanning
+//! services and editors read the findings of analysis tools from. Unlike the JSON emitter, which
+//! prints a line per diagnostic, this emitter collects the diagnostics of the compilation and
+//! prints them as a single SARIF 2.1.0 log once it is dropped.
+//!
+//! Diagnostics that point at the source code are *results* of the log: their primary spans are
+//! the locations of the result, their secondary spans and their children are related locations,
+//! and their suggestions are *fixes*. Diagnostics without a span, such as the count of errors
+//! printed at the end of the compilation, are notifications of the invocation of the compiler.
+
+use rustc_span::source_map::{FilePathMapping, SourceMap};
+
+use crate::emitter::Emitter;
+use crate::registry::Registry;
+use crate::{CodeSuggestion, DiagnosticId, Level, Substitution};
+
+use rustc_data_structures::sync::Lrc;
+use rustc_serialize::json::{Json, ToJson};
+use rustc_span::{FileName, MultiSpan, Span};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::mem;
+use std::path::Component;
+use std::thread;
+
+#[cfg(test)]
+mod tests;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+pub struct SarifEmitter {
+    dst: Box<dyn Write + Send>,
+    registry: Option<Registry>,
+    sm: Lrc<SourceMap>,
+    /// The diagnostics that point at the source code.
+    results: Vec<Json>,
+    /// The diagnostics without a span.
+    notifications: Vec<Json>,
+    /// The descriptions of the error codes and lints of the results, by name.
+    rules: BTreeMap<String, Json>,
+    /// Whether an error was emitted, in which case the compilation failed.
+    failed: bool,
+}
+
+impl SarifEmitter {
+    pub fn stderr(registry: Option<Registry>, source_map: Lrc<SourceMap>) -> SarifEmitter {
+        SarifEmitter::new(Box::new(io::BufWriter::new(io::stderr())), registry, source_map)
+    }
+
+    pub fn basic() -> SarifEmitter {
+        let file_path_mapping = FilePathMapping::empty();
+        SarifEmitter::stderr(None, Lrc::new(SourceMap::new(file_path_mapping)))
+    }
+
+    pub fn new(
+        dst: Box<dyn Write + Send>,
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+    ) -> SarifEmitter {
+        SarifEmitter {
+            dst,
+            registry,
+            sm: source_map,
+            results: Vec::new(),
+            notifications: Vec::new(),
+            rules: BTreeMap::new(),
+            failed: false,
+        }
+    }
+
+    /// Builds the log of the compilation out of the diagnostics emitted so far.
+    fn log(&mut self) -> Json {
+        let driver = object(vec![
+            ("name", "rustc".to_json()),
+            ("informationUri", "https://www.rust-lang.org/".to_json()),
+            ("rules", Json::Array(mem::take(&mut self.rules).into_iter().map(|r| r.1).collect())),
+        ]);
+        let invocation = object(vec![
+            ("executionSuccessful", Json::Boolean(!self.failed)),
+            ("toolExecutionNotifications", Json::Array(mem::take(&mut self.notifications))),
+        ]);
+        let run = object(vec![
+            ("tool", object(vec![("driver", driver)])),
+            ("invocations", Json::Array(vec![invocation])),
+            // Columns are counted in `char`s, and not in UTF-16 code units, SARIF's default.
+            ("columnKind", "unicodeCodePoints".to_json()),
+            ("results", Json::Array(mem::take(&mut self.results))),
+        ]);
+        object(vec![
+            ("$schema", SCHEMA.to_json()),
+            ("version", "2.1.0".to_json()),
+            ("runs", Json::Array(vec![run])),
+        ])
+    }
+
+    /// Registers the rule a result refers to, with the explanation of its error code if it has
+    /// one, and returns its name.
+    fn rule(&mut self, code: &DiagnosticId) -> String {
+        let (name, help_uri) = match code {
+            DiagnosticId::Error(code) => {
+                (code, Some(format!("https://doc.rust-lang.org/error-index.html#{}", code)))
+            }
+            DiagnosticId::Lint(name) => (name, None),
+        };
+        let registry = &self.registry;
+        self.rules.entry(name.clone()).or_insert_with(|| {
+            let mut rule = vec![("id", name.to_json())];
+            let explanation = registry.as_ref().and_then(|r| r.try_find_description(name).ok());
+            if let Some(Some(explanation)) = explanation {
+                rule.push(("fullDescription", message(explanation.to_owned())));
+            }
+            if let Some(help_uri) = help_uri {
+                rule.push(("helpUri", help_uri.to_json()));
+            }
+            object(rule)
+        });
+        name.clone()
+    }
+
+    /// Converts the primary, or the secondary, spans of `msp` to locations, whose messages are
+    /// the labels of the spans.
+    fn locations(&self, msp: &MultiSpan, primary: bool) -> Vec<Json> {
+        msp.span_labels()
+            .into_iter()
+            .filter(|label| label.is_primary == primary && !label.span.is_dummy())
+            .map(|label| self.location(label.span, label.label))
+            .collect()
+    }
+
+    fn location(&self, span: Span, label: Option<String>) -> Json {
+        let mut location = vec![(
+            "physicalLocation",
+            object(vec![
+                ("artifactLocation", self.artifact_location(span)),
+                ("region", self.region(span)),
+            ]),
+        )];
+        if let Some(label) = label {
+            location.push(("message", message(label)));
+        }
+        object(location)
+    }
+
+    fn artifact_location(&self, span: Span) -> Json {
+        object(vec![("uri", uri(&self.sm.span_to_filename(span)).to_json())])
+    }
+
+    /// The lines and columns of the region are 1-based, and its end column is the one following
+    /// the span. The byte offsets are relative to the start of the file as it is on disk.
+    fn region(&self, span: Span) -> Json {
+        let start = self.sm.lookup_char_pos(span.lo());
+        let end = self.sm.lookup_char_pos(span.hi());
+        let byte_start = start.file.original_relative_byte_pos(span.lo()).0;
+        let byte_end = start.file.original_relative_byte_pos(span.hi()).0;
+        object(vec![
+            ("startLine", start.line.to_json()),
+            ("startColumn", (start.col.0 + 1).to_json()),
+            ("endLine", end.line.to_json()),
+            ("endColumn", (end.col.0 + 1).to_json()),
+            ("byteOffset", byte_start.to_json()),
+            ("byteLength", (byte_end - byte_start).to_json()),
+        ])
+    }
+
+    /// Converts one of the alternatives of a suggestion to a fix, with the replacements of the
+    /// suggestion grouped by file.
+    fn fix(&self, suggestion: &CodeSuggestion, substitution: &Substitution) -> Json {
+        let mut changes: Vec<(String, Vec<Json>)> = Vec::new();
+        for part in &substitution.parts {
+            let file = uri(&self.sm.span_to_filename(part.span));
+            let replacement = object(vec![
+                ("deletedRegion", self.region(part.span)),
+                ("insertedContent", message(part.snippet.clone())),
+            ]);
+            match changes.iter_mut().find(|(f, _)| *f == file) {
+                Some((_, replacements)) => replacements.push(replacement),
+                None => changes.push((file, vec![replacement])),
+            }
+        }
+        let changes = changes
+            .into_iter()
+            .map(|(file, replacements)| {
+                object(vec![
+                    ("artifactLocation", object(vec![("uri", file.to_json())])),
+                    ("replacements", Json::Array(replacements)),
+                ])
+            })
+            .collect();
+        let applicability = format!("{:?}", suggestion.applicability);
+        object(vec![
+            ("description", message(suggestion.msg.clone())),
+            ("artifactChanges", Json::Array(changes)),
+            ("properties", object(vec![("applicability", applicability.to_json())])),
+        ])
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: &crate::Diagnostic) {
+        let level = match diag.level {
+            Level::Bug | Level::Fatal | Level::Error => "error",
+            Level::Warning => "warning",
+            Level::Note | Level::Help | Level::FailureNote => "note",
+            Level::Cancelled => return,
+        };
+        if level == "error" {
+            self.failed = true;
+        }
+
+        // The children without a span are part of the message, the others are related
+        // locations, with the message of the child.
+        let mut text = diag.message();
+        let mut related_locations = self.locations(&diag.span, false);
+        for child in &diag.children {
+            let span = child.render_span.as_ref().unwrap_or(&child.span);
+            let child_message = format!("{}: {}", child.level, child.message());
+            let spans: Vec<_> = span.primary_spans().iter().filter(|sp| !sp.is_dummy()).collect();
+            if spans.is_empty() {
+                text.push('\n');
+                text.push_str(&child_message);
+            } else {
+                related_locations.extend(
+                    spans.into_iter().map(|&sp| self.location(sp, Some(child_message.clone()))),
+                );
+            }
+        }
+
+        let locations = self.locations(&diag.span, true);
+        if locations.is_empty() {
+            self.notifications
+                .push(object(vec![("level", level.to_json()), ("message", message(text))]));
+            return;
+        }
+
+        let mut result = Vec::new();
+        if let Some(code) = &diag.code {
+            result.push(("ruleId", self.rule(code).to_json()));
+        }
+        result.push(("level", level.to_json()));
+        result.push(("message", message(text)));
+        result.push(("locations", Json::Array(locations)));
+        if !related_locations.is_empty() {
+            result.push(("relatedLocations", Json::Array(related_locations)));
+        }
+        let mut fixes = Vec::new();
+        for sugg in &diag.suggestions {
+            fixes.extend(sugg.substitutions.iter().map(|subst| self.fix(sugg, subst)));
+        }
+        if !fixes.is_empty() {
+            result.push(("fixes", Json::Array(fixes)));
+        }
+        self.results.push(object(result));
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        Some(&self.sm)
+    }
+
+    fn should_show_explain(&self) -> bool {
+        // The explanations of the error codes are the descriptions of the rules of the log.
+        false
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        let log = self.log();
+        let result = writeln!(&mut self.dst, "{}", log).and_then(|_| self.dst.flush());
+        if let Err(e) = result {
+            // The emitter is dropped while unwinding after a fatal error, when panicking again
+            // would abort the compiler.
+            if !thread::panicking() {
+                panic!("failed to print diagnostics: {:?}", e);
+            }
+        }
+    }
+}
+
+fn object(fields: Vec<(&str, Json)>) -> Json {
+    Json::Object(fields.into_iter().map(|(key, value)| (key.to_owned(), value)).collect())
+}
+
+/// A SARIF message, or the contents of a file, made of plain text.
+fn message(text: String) -> Json {
+    object(vec![("text", text.to_json())])
+}
+
+/// Converts the name of a file to the URI of an artifact: a `file://` URI for an absolute path,
+/// and a relative reference for the other names, with `/` as the separator on every platform.
+fn uri(file: &FileName) -> String {
+    let path = match file {
+        FileName::Real(path) => path,
+        // The other files aren't on the disk, their names only tell which ones they are.
+        _ => return percent_encode(&file.to_string()),
+    };
+    let segments: Vec<_> = path
+        .components()
+        .filter_map(|component| match component {
+            Component::Prefix(prefix) => {
+                Some(percent_encode(&prefix.as_os_str().to_string_lossy()))
+            }
+            Component::RootDir | Component::CurDir => None,
+            Component::ParentDir => Some("..".to_owned()),
+            Component::Normal(name) => Some(percent_encode(&name.to_string_lossy())),
+        })
+        .collect();
+    if path.has_root() {
+        format!("file:///{}", segments.join("/"))
+    } else {
+        segments.join("/")
+    }
+}
+
+/// Percent-encodes the bytes of `segment` that can't appear in a segment of the path of a URI.
+fn percent_encode(segment: &str) -> String {
+    let mut encoded = String::new();
+    for &byte in segment.as_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => encoded.push(byte as char),
+            b'-' | b'.' | b'_' | b'~' | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+'
+            | b',' | b';' | b'=' | b':' | b'@' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
@@ -0,0 +1,151 @@
+use super::*;
+
+use crate::{Applicability, Handler};
+use rustc_serialize::json::from_str;
+use rustc_span::{BytePos, Span};
+
+use std::path::Path;
+use std::str;
+use std::sync::{Arc, Mutex};
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+fn with_default_globals<R>(f: impl FnOnce() -> R) -> R {
+    let globals = rustc_span::Globals::new(rustc_span::edition::DEFAULT_EDITION);
+    rustc_span::GLOBALS.set(&globals, || rustc_span::GLOBALS.set(&globals, f))
+}
+
+/// Emits diagnostics about `code` with `f`, and returns the log printed by the emitter.
+fn emit(code: &str, f: impl FnOnce(&Handler)) -> Json {
+    with_default_globals(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("test.rs").to_owned().into(), code.to_owned());
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let se = SarifEmitter::new(Box::new(Shared { data: output.clone() }), None, sm);
+        let handler = Handler::with_emitter(true, None, Box::new(se));
+        f(&handler);
+        drop(handler);
+
+        let bytes = output.lock().unwrap();
+        from_str(str::from_utf8(&bytes).unwrap()).unwrap()
+    })
+}
+
+#[test]
+fn result() {
+    let log = emit("fn main() {\n    let x = 1;\n}\n", |handler| {
+        let span = Span::with_root_ctxt(BytePos(20), BytePos(21));
+        handler
+            .struct_span_warn(span, "unused variable: `x`")
+            .code(DiagnosticId::Lint("unused_variables".to_owned()))
+            .note("`#[warn(unused_variables)]` on by default")
+            .span_suggestion(
+                span,
+                "if this is intentional, prefix it with an underscore",
+                "_x".to_owned(),
+                Applicability::MachineApplicable,
+            )
+            .emit();
+    });
+
+    let run = &log["runs"][0];
+    assert_eq!(log["version"].as_string(), Some("2.1.0"));
+    assert_eq!(run["tool"]["driver"]["rules"][0]["id"].as_string(), Some("unused_variables"));
+    assert_eq!(run["invocations"][0]["executionSuccessful"].as_boolean(), Some(true));
+
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"].as_string(), Some("unused_variables"));
+    assert_eq!(result["level"].as_string(), Some("warning"));
+    assert_eq!(
+        result["message"]["text"].as_string(),
+        Some("unused variable: `x`\nnote: `#[warn(unused_variables)]` on by default")
+    );
+
+    let location = &result["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"].as_string(), Some("test.rs"));
+    let region = &location["region"];
+    assert_eq!(region["startLine"].as_u64(), Some(2));
+    assert_eq!(region["startColumn"].as_u64(), Some(9));
+    assert_eq!(region["endLine"].as_u64(), Some(2));
+    assert_eq!(region["endColumn"].as_u64(), Some(10));
+    assert_eq!(region["byteOffset"].as_u64(), Some(20));
+    assert_eq!(region["byteLength"].as_u64(), Some(1));
+
+    let fix = &result["fixes"][0];
+    assert_eq!(fix["properties"]["applicability"].as_string(), Some("MachineApplicable"));
+    let replacement = &fix["artifactChanges"][0]["replacements"][0];
+    assert_eq!(replacement["deletedRegion"], *region);
+    assert_eq!(replacement["insertedContent"]["text"].as_string(), Some("_x"));
+}
+
+#[test]
+fn related_locations() {
+    let log = emit("fn f(a: u8) {}\nfn g() { f(); }\n", |handler| {
+        let mut msp = MultiSpan::from_span(Span::with_root_ctxt(BytePos(24), BytePos(27)));
+        msp.push_span_label(Span::with_root_ctxt(BytePos(24), BytePos(25)), "missing `a`".into());
+        handler
+            .struct_span_err(msp, "this function takes 1 argument but 0 arguments were supplied")
+            .code(DiagnosticId::Error("E0061".to_owned()))
+            .span_note(Span::with_root_ctxt(BytePos(0), BytePos(11)), "defined here")
+            .emit();
+    });
+
+    let run = &log["runs"][0];
+    assert_eq!(run["invocations"][0]["executionSuccessful"].as_boolean(), Some(false));
+    assert_eq!(
+        run["tool"]["driver"]["rules"][0]["helpUri"].as_string(),
+        Some("https://doc.rust-lang.org/error-index.html#E0061")
+    );
+
+    let related = run["results"][0]["relatedLocations"].as_array().unwrap();
+    assert_eq!(related.len(), 2);
+    assert_eq!(related[0]["message"]["text"].as_string(), Some("missing `a`"));
+    assert_eq!(related[1]["message"]["text"].as_string(), Some("note: defined here"));
+    let region = &related[1]["physicalLocation"]["region"];
+    assert_eq!(region["startLine"].as_u64(), Some(1));
+    assert_eq!(region["endColumn"].as_u64(), Some(12));
+}
+
+#[test]
+fn notification() {
+    let log = emit("", |handler| {
+        handler.err("aborting due to previous error");
+    });
+
+    let run = &log["runs"][0];
+    assert!(run["results"].as_array().unwrap().is_empty());
+    let invocation = &run["invocations"][0];
+    assert_eq!(invocation["executionSuccessful"].as_boolean(), Some(false));
+    let notification = &invocation["toolExecutionNotifications"][0];
+    assert_eq!(notification["level"].as_string(), Some("error"));
+    assert_eq!(notification["message"]["text"].as_string(), Some("aborting due to previous error"));
+}
+
+#[test]
+fn uris() {
+    let real = |path: &str| FileName::Real(Path::new(path).to_owned());
+    assert_eq!(uri(&real("test.rs")), "test.rs");
+    assert_eq!(uri(&real("./src/../lib.rs")), "src/../lib.rs");
+    assert_eq!(uri(&real("src/my mod/100%.rs")), "src/my%20mod/100%25.rs");
+    assert_eq!(uri(&real("src/é.rs")), "src/%C3%A9.rs");
+    if cfg!(windows) {
+        assert_eq!(uri(&real(r"C:\src\lib.rs")), "file:///C:/src/lib.rs");
+        assert_eq!(uri(&real(r"src\lib.rs")), "src/lib.rs");
+    } else {
+        assert_eq!(uri(&real("/src/lib.rs")), "file:///src/lib.rs");
+    }
+    assert_eq!(uri(&FileName::Anon(0)), "%3Canon%3E");
+}
@@ -0,0 +1,258 @@
+//! Translation of the messages of diagnostics.
+//!
+//! A translatable message is the identifier of a message in the catalogs of messages, together
+//! with the values of the named arguments of the message. A diagnostic built with translatable
+//! messages still carries their English text, which comes from the English catalog bundled with
+//! the compiler, so that it can be emitted as is. When another language is selected with
+//! `-Z translate-lang`, the `TranslatingEmitter` rebuilds the messages from the catalog of that
+//! language before emitting the diagnostic, and keeps the English text of the messages that
+//! the catalog doesn't have.
+//!
+//! Catalogs are written in a subset of the syntax of Fluent:
+//!
+//! ```text
+//! # A comment.
+//! typeck-field-multiply-specified = field `{$ident}` specified more than once
+//! ```
+//!
+//! Each message is written `identifier = text`, and the indented lines that follow it are
+//! further lines of its text. The arguments of the message are inserted with `{$name}`, and
+//! braces can't be used otherwise.
+
+use crate::emitter::Emitter;
+use crate::snippet::Style;
+use crate::Diagnostic;
+
+use lazy_static::lazy_static;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::sync::Lrc;
+use rustc_span::source_map::SourceMap;
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+
+#[cfg(test)]
+mod tests;
+
+/// The language of the catalog bundled with the compiler.
+pub const ENGLISH: &str = "en-US";
+
+lazy_static! {
+    static ref ENGLISH_CATALOG: Catalog =
+        Catalog::parse(include_str!("locales/en-US.ftl")).expect("invalid English catalog");
+}
+
+/// A message that can be translated.
+#[derive(Clone, Debug, PartialEq, Hash, RustcEncodable, RustcDecodable)]
+pub struct TranslatableMessage {
+    pub id: String,
+    /// The names of the arguments, and their values.
+    pub args: Vec<(String, String)>,
+}
+
+impl TranslatableMessage {
+    pub fn new(id: &str) -> TranslatableMessage {
+        TranslatableMessage { id: id.to_owned(), args: vec![] }
+    }
+
+    /// Gives a value to the argument `name` of the message.
+    pub fn arg(mut self, name: &str, value: impl ToString) -> TranslatableMessage {
+        self.args.push((name.to_owned(), value.to_string()));
+        self
+    }
+
+    /// The text of the message in English.
+    pub fn english(&self) -> String {
+        ENGLISH_CATALOG.format(self).unwrap_or_else(|| {
+            panic!("message `{}` isn't in the English catalog, or lacks arguments", self.id)
+        })
+    }
+}
+
+/// The messages of a language, by identifier.
+#[derive(Default)]
+pub struct Catalog {
+    messages: FxHashMap<String, String>,
+}
+
+impl Catalog {
+    /// Loads the catalog of `lang`, which is installed in the sysroot, and the catalog at `path`,
+    /// whose messages take precedence. Returns `None` if there is nothing to translate.
+    pub fn load(
+        lang: Option<&str>,
+        sysroot: &Path,
+        path: Option<&Path>,
+    ) -> Result<Option<Catalog>, String> {
+        let mut catalog = Catalog::default();
+        if let Some(lang) = lang.filter(|&lang| lang != ENGLISH) {
+            let installed = sysroot.join("lib/rustlib/locales").join(format!("{}.ftl", lang));
+            if installed.exists() {
+                catalog.messages.extend(Catalog::read(&installed)?.messages);
+            } else if path.is_none() {
+                return Err(format!(
+                    "no translation catalog for `{}`, expected one at `{}`",
+                    lang,
+                    installed.display()
+                ));
+            }
+        }
+        if let Some(path) = path {
+            catalog.messages.extend(Catalog::read(path)?.messages);
+        }
+        Ok(if catalog.messages.is_empty() { None } else { Some(catalog) })
+    }
+
+    fn read(path: &Path) -> Result<Catalog, String> {
+        let source = fs::read_to_string(path).map_err(|e| {
+            format!("couldn't read translation catalog `{}`: {}", path.display(), e)
+        })?;
+        Catalog::parse(&source)
+            .map_err(|e| format!("invalid translation catalog `{}`: {}", path.display(), e))
+    }
+
+    pub fn parse(source: &str) -> Result<Catalog, String> {
+        let mut messages = FxHashMap::default();
+        let mut current: Option<String> = None;
+        for (i, line) in source.lines().enumerate() {
+            let error = |msg: String| Err(format!("line {}: {}", i + 1, msg));
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let text = if line.starts_with(char::is_whitespace) {
+                let text: &mut String = match &current {
+                    Some(id) => messages.get_mut(id).unwrap(),
+                    None => return error("indented line outside of a message".to_owned()),
+                };
+                if !text.is_empty() {
+                    text.push('\n');
+                }
+                text.push_str(line.trim());
+                line.trim()
+            } else {
+                let (id, text) = match line.find('=') {
+                    Some(eq) => (line[..eq].trim(), line[eq + 1..].trim()),
+                    None => return error("expected `identifier = message`".to_owned()),
+                };
+                if !is_identifier(id) {
+                    return error(format!("invalid identifier `{}`", id));
+                }
+                if messages.insert(id.to_owned(), text.to_owned()).is_some() {
+                    return error(format!("message `{}` is defined twice", id));
+                }
+                current = Some(id.to_owned());
+                text
+            };
+            if let Err(msg) = check_placeables(text) {
+                return error(msg);
+            }
+        }
+        Ok(Catalog { messages })
+    }
+
+    /// Builds the text of `message`, unless the catalog doesn't have it, or some of the arguments
+    /// it refers to weren't given.
+    pub fn format(&self, message: &TranslatableMessage) -> Option<String> {
+        let mut rest = &self.messages.get(&message.id)?[..];
+        let mut text = String::new();
+        while let Some(start) = rest.find('{') {
+            text.push_str(&rest[..start]);
+            // Catalogs are checked to only have well-formed placeables.
+            let end = start + rest[start..].find('}').unwrap();
+            let name = placeable_name(&rest[start + 1..end]).unwrap();
+            let (_, value) = message.args.iter().find(|(arg, _)| arg == name)?;
+            text.push_str(value);
+            rest = &rest[end + 1..];
+        }
+        text.push_str(rest);
+        Some(text)
+    }
+
+    /// Translates the messages of `diag` that are translatable. The messages the catalog doesn't
+    /// have are left in English.
+    pub fn translate<'a>(&self, diag: &'a Diagnostic) -> Cow<'a, Diagnostic> {
+        let translatable = diag.translation.is_some()
+            || !diag.label_translations.is_empty()
+            || diag.children.iter().any(|child| child.translation.is_some());
+        if !translatable {
+            return Cow::Borrowed(diag);
+        }
+
+        let mut diag = diag.clone();
+        if let Some(text) = diag.translation.as_ref().and_then(|msg| self.format(msg)) {
+            diag.message = vec![(text, Style::NoStyle)];
+        }
+        for (span, label) in &diag.label_translations {
+            if let Some(text) = self.format(label) {
+                diag.span.replace_label(*span, &label.english(), text);
+            }
+        }
+        for child in &mut diag.children {
+            if let Some(text) = child.translation.as_ref().and_then(|msg| self.format(msg)) {
+                child.message = vec![(text, Style::NoStyle)];
+            }
+        }
+        Cow::Owned(diag)
+    }
+}
+
+fn is_identifier(id: &str) -> bool {
+    id.starts_with(|c: char| c.is_ascii_alphabetic())
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Returns the name of the argument a placeable, written `{$name}` or `{ $name }`, refers to.
+fn placeable_name(placeable: &str) -> Option<&str> {
+    let placeable = placeable.trim();
+    if placeable.starts_with('$') && is_identifier(&placeable[1..]) {
+        Some(&placeable[1..])
+    } else {
+        None
+    }
+}
+
+fn check_placeables(mut text: &str) -> Result<(), String> {
+    while let Some(start) = text.find('{') {
+        let end = match text[start..].find('}') {
+            Some(len) => start + len,
+            None => return Err("unterminated placeable".to_owned()),
+        };
+        if placeable_name(&text[start + 1..end]).is_none() {
+            return Err(format!("invalid placeable `{}`", &text[start..=end]));
+        }
+        text = &text[end + 1..];
+    }
+    if text.contains('}') { Err("unexpected `}`".to_owned()) } else { Ok(()) }
+}
+
+/// An emitter that translates the messages of the diagnostics before passing them to another
+/// emitter, which prints them.
+pub struct TranslatingEmitter {
+    emitter: Box<dyn Emitter + Send>,
+    catalog: Catalog,
+}
+
+impl TranslatingEmitter {
+    pub fn new(emitter: Box<dyn Emitter + Send>, catalog: Catalog) -> TranslatingEmitter {
+        TranslatingEmitter { emitter, catalog }
+    }
+}
+
+impl Emitter for TranslatingEmitter {
+    fn emit_diagnostic(&mut self, diag: &Diagnostic) {
+        let diag = self.catalog.translate(diag);
+        self.emitter.emit_diagnostic(&diag);
+    }
+
+    fn emit_artifact_notification(&mut self, path: &Path, artifact_type: &str) {
+        self.emitter.emit_artifact_notification(path, artifact_type);
+    }
+
+    fn should_show_explain(&self) -> bool {
+        self.emitter.should_show_explain()
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        self.emitter.source_map()
+    }
+}
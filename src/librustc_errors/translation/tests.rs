@@ -0,0 +1,91 @@
+use super::*;
+
+use crate::Level;
+use rustc_span::{BytePos, Span};
+
+const FRENCH: &str = "\
+# Messages of the type checker.
+typeck-field-multiply-specified = champ `{$ident}` spécifié plusieurs fois
+typeck-field-multiply-specified-label = utilisé plusieurs fois
+
+multi-line =
+    première ligne
+    seconde ligne avec { $arg }
+";
+
+fn message(id: &str) -> TranslatableMessage {
+    TranslatableMessage::new(id).arg("ident", "x")
+}
+
+#[test]
+fn parse() {
+    let catalog = Catalog::parse(FRENCH).unwrap();
+    assert_eq!(
+        catalog.format(&message("typeck-field-multiply-specified")).as_deref(),
+        Some("champ `x` spécifié plusieurs fois")
+    );
+    assert_eq!(
+        catalog.format(&TranslatableMessage::new("multi-line").arg("arg", 1)).as_deref(),
+        Some("première ligne\nseconde ligne avec 1")
+    );
+}
+
+#[test]
+fn parse_errors() {
+    let error = |source| Catalog::parse(source).err().unwrap();
+    assert_eq!(error("a = b\n  c\nd"), "line 3: expected `identifier = message`");
+    assert_eq!(error("  a = b"), "line 1: indented line outside of a message");
+    assert_eq!(error("1a = b"), "line 1: invalid identifier `1a`");
+    assert_eq!(error("a = b\na = c"), "line 2: message `a` is defined twice");
+    assert_eq!(error("a = {$b"), "line 1: unterminated placeable");
+    assert_eq!(error("a = {b}"), "line 1: invalid placeable `{b}`");
+    assert_eq!(error("a = b}"), "line 1: unexpected `}`");
+}
+
+#[test]
+fn missing_arguments() {
+    let catalog = Catalog::parse(FRENCH).unwrap();
+    let msg = TranslatableMessage::new("typeck-field-multiply-specified");
+    assert_eq!(catalog.format(&msg), None);
+    assert_eq!(catalog.format(&TranslatableMessage::new("unknown")), None);
+}
+
+#[test]
+fn english() {
+    assert_eq!(
+        message("typeck-field-multiply-specified").english(),
+        "field `x` specified more than once"
+    );
+}
+
+#[test]
+fn translate() {
+    let catalog = Catalog::parse(FRENCH).unwrap();
+    let span = Span::with_root_ctxt(BytePos(0), BytePos(1));
+    let prev_span = Span::with_root_ctxt(BytePos(2), BytePos(3));
+    let mut diag = Diagnostic::new(Level::Error, "");
+    diag.set_span(span)
+        .set_translatable_message(message("typeck-field-multiply-specified"))
+        .span_label_translatable(span, message("typeck-field-multiply-specified-label"))
+        .span_label_translatable(prev_span, message("typeck-field-first-use"))
+        .note_translatable(message("typeck-field-multiply-specified"))
+        .note("not translatable");
+    assert_eq!(diag.message(), "field `x` specified more than once");
+
+    let translated = catalog.translate(&diag);
+    assert_eq!(translated.message(), "champ `x` spécifié plusieurs fois");
+    let labels: Vec<_> = translated.span.span_labels().into_iter().map(|l| l.label).collect();
+    assert_eq!(
+        labels,
+        vec![Some("utilisé plusieurs fois".to_owned()), Some("first use of `x`".to_owned())]
+    );
+    assert_eq!(translated.children[0].message(), "champ `x` spécifié plusieurs fois");
+    assert_eq!(translated.children[1].message(), "not translatable");
+}
+
+#[test]
+fn untranslatable() {
+    let catalog = Catalog::parse(FRENCH).unwrap();
+    let diag = Diagnostic::new(Level::Error, "not translatable");
+    assert!(matches!(catalog.translate(&diag), Cow::Borrowed(_)));
+}
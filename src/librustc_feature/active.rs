@@ -565,6 +565,16 @@ declare_features! (
     /// Allow conditional compilation depending on rust version
     (active, cfg_version, "1.45.0", Some(64796), None),
 
+    // no-tracking-issue-start
+
+    /// Allows overriding the libtest timeout of a single test with `#[test_timeout(secs)]`.
+    (active, test_timeout, "1.45.0", None, None),
+
+    /// Allows rerunning a failing test with `#[test_retries(n)]`, to detect flaky tests.
+    (active, test_retries, "1.45.0", None, None),
+
+    // no-tracking-issue-end
+
     // -------------------------------------------------------------------------
     // feature-group-end: actual feature gates
     // -------------------------------------------------------------------------
@@ -345,8 +345,9 @@ fn configure_and_expand_inner<'a>(
     });
 
     // If we're actually rustdoc then there's no need to actually compile
-    // anything, so switch everything to just looping
-    let mut should_loop = sess.opts.actually_rustdoc;
+    // anything, so switch everything to just looping, unless rustdoc needs
+    // to look at the bodies of functions
+    let mut should_loop = sess.opts.actually_rustdoc && !sess.opts.debugging_opts.keep_fn_bodies;
     if let Some(PpMode::PpmSource(PpSourceMode::PpmEveryBodyLoops)) = sess.opts.pretty {
         should_loop |= true;
     }
@@ -1,7 +1,7 @@
 use crate::interface::parse_cfgspecs;
 
 use rustc_data_structures::fx::FxHashSet;
-use rustc_errors::{emitter::HumanReadableErrorType, registry, ColorConfig};
+use rustc_errors::{emitter::HumanReadableErrorType, registry, Applicability, ColorConfig};
 use rustc_middle::middle::cstore;
 use rustc_session::config::Strip;
 use rustc_session::config::{build_configuration, build_session_options, to_crate_config};
@@ -453,6 +453,7 @@ fn test_debugging_options_tracking_hash() {
 
     // Make sure that changing an [UNTRACKED] option leaves the hash unchanged.
     // This list is in alphabetical order.
+    untracked!(apply_suggestions, Some(Applicability::MaybeIncorrect));
     untracked!(ast_json, true);
     untracked!(ast_json_noexpand, true);
     untracked!(borrowck, String::from("other"));
@@ -508,10 +509,13 @@ fn test_debugging_options_tracking_hash() {
     untracked!(time_llvm_passes, true);
     untracked!(time_passes, true);
     untracked!(trace_macros, true);
+    untracked!(translate_catalog, Some(PathBuf::from("fr.ftl")));
+    untracked!(translate_lang, Some(String::from("fr")));
     untracked!(ui_testing, true);
     untracked!(unpretty, Some("expanded".to_string()));
     untracked!(unstable_options, true);
     untracked!(verbose, true);
+    untracked!(verbose_diagnostics, true);
 
     macro_rules! tracked {
         ($name: ident, $non_default_value: expr) => {
@@ -541,6 +545,7 @@ fn test_debugging_options_tracking_hash() {
     tracked!(inline_in_all_cgus, Some(true));
     tracked!(insert_sideeffect, true);
     tracked!(instrument_mcount, true);
+    tracked!(keep_fn_bodies, true);
     tracked!(link_only, true);
     tracked!(merge_functions, Some(MergeFunctions::Disabled));
     tracked!(mir_emit_retag, true);
@@ -8,6 +8,7 @@ use proc_macro::TokenStream;
 mod hash_stable;
 mod lift;
 mod query;
+mod session_diagnostic;
 mod symbols;
 mod type_foldable;
 
@@ -29,3 +30,7 @@ decl_derive!(
 
 decl_derive!([TypeFoldable, attributes(type_foldable)] => type_foldable::type_foldable_derive);
 decl_derive!([Lift, attributes(lift)] => lift::lift_derive);
+decl_derive!(
+    [SessionDiagnostic, attributes(code, message, label, note, help, suggestion)] =>
+    session_diagnostic::session_diagnostic_derive
+);
@@ -0,0 +1,391 @@
+//! `#[derive(SessionDiagnostic)]`, which turns a struct into an error.
+//!
+//! The attributes of the struct give the error code and the message of the error, and the
+//! attributes of its fields the spans the error points at:
+//!
+//! ```ignore (illustrative)
+//! #[derive(SessionDiagnostic)]
+//! #[code = "E0124"]
+//! pub struct FieldAlreadyDeclared {
+//!     pub field_name: Ident,
+//!     #[message = "field `{field_name}` is already declared"]
+//!     #[label = "field already declared"]
+//!     pub span: Span,
+//!     #[label = "`{field_name}` first declared here"]
+//!     pub prev_span: Span,
+//! }
+//! ```
+//!
+//! - `#[code = "E...."]` on the struct is the error code, which must be registered in
+//!   `rustc_error_codes`.
+//! - `#[message = "..."]` is the message of the error. On a field, the field is also the
+//!   primary span of the error; on the struct, the error has no span.
+//! - `#[label = "..."]` on a field labels the span of the field.
+//! - `#[note = "..."]` and `#[help = "..."]` add a note or a help to the error, with the span
+//!   of the field if they are on a field.
+//! - `#[suggestion(message = "...", code = "...", applicability = "...")]` on a field suggests
+//!   replacing the span of the field with `code`. The applicability is the name of a variant of
+//!   `Applicability`. It can instead be the second element of the field, which is then a
+//!   `(Span, Applicability)`, and it defaults to `Unspecified`.
+//!
+//! The fields with attributes are `Span`s, `(Span, Applicability)`s, or options of those, in
+//! which case the attributes are ignored when the field is `None`. Messages refer to the fields
+//! of the struct by name, as in `{field_name}`, which are formatted with `Display`, unless a
+//! format is given, as in `{field_name:?}`.
+
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::{self, parse_quote, Attribute, Lit, LitStr, Meta, NestedMeta};
+
+const APPLICABILITIES: &[&str] =
+    &["MachineApplicable", "HasPlaceholders", "MaybeIncorrect", "Unspecified"];
+
+pub fn session_diagnostic_derive(mut s: synstructure::Structure<'_>) -> TokenStream {
+    let body = match diagnostic_body(s.ast()) {
+        Ok(body) => body,
+        Err(err) => return err.to_compile_error(),
+    };
+
+    s.add_bounds(synstructure::AddBounds::None);
+    s.add_impl_generic(parse_quote!('__session_diagnostic_sess));
+    s.bound_impl(
+        quote!(::rustc_session::SessionDiagnostic<'__session_diagnostic_sess>),
+        quote! {
+            fn into_diagnostic(
+                self,
+                __sess: &'__session_diagnostic_sess ::rustc_session::Session
+            ) -> ::rustc_errors::DiagnosticBuilder<'__session_diagnostic_sess> {
+                #body
+            }
+        },
+    )
+}
+
+/// The way the value of a field is a span.
+struct SpanField {
+    /// Whether the field is an `Option`.
+    optional: bool,
+    /// Whether the field is a `(Span, Applicability)`.
+    applicability: bool,
+}
+
+fn diagnostic_body(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &ast.data {
+        syn::Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &ast.ident,
+                "`#[derive(SessionDiagnostic)]` can only be used on structs",
+            ));
+        }
+    };
+
+    let mut code = None;
+    let mut message = None;
+    let mut struct_stmts = Vec::new();
+    for attr in &ast.attrs {
+        let name = match attr_name(attr) {
+            Some(name) => name,
+            None => continue,
+        };
+        match &name[..] {
+            "code" => {
+                let value = string_value(attr)?;
+                check_error_code(&value)?;
+                set_once(&mut code, attr, value.value())?;
+            }
+            "message" => {
+                set_once(&mut message, attr, format_string(&string_value(attr)?, fields)?)?
+            }
+            "note" => {
+                let note = format_string(&string_value(attr)?, fields)?;
+                struct_stmts.push(quote! { __diag.note(&#note); });
+            }
+            "help" => {
+                let help = format_string(&string_value(attr)?, fields)?;
+                struct_stmts.push(quote! { __diag.help(&#help); });
+            }
+            _ => return Err(syn::Error::new_spanned(attr, "this attribute goes on a field")),
+        }
+    }
+
+    let mut field_stmts = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let mut stmts = Vec::new();
+        let mut uses_applicability = false;
+        let span_field = span_field(&field.ty);
+        for attr in &field.attrs {
+            let name = match attr_name(attr) {
+                Some(name) => name,
+                None => continue,
+            };
+            if span_field.is_none() {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "diagnostic attributes can only be used on fields of type `Span`, \
+                     `(Span, Applicability)`, or options of those",
+                ));
+            }
+            match &name[..] {
+                "message" => {
+                    let value = format_string(&string_value(attr)?, fields)?;
+                    set_once(&mut message, attr, value)?;
+                    stmts.insert(0, quote! { __diag.set_span(__span); });
+                }
+                "label" => {
+                    let label = format_string(&string_value(attr)?, fields)?;
+                    stmts.push(quote! { __diag.span_label(__span, #label); });
+                }
+                "note" => {
+                    let note = format_string(&string_value(attr)?, fields)?;
+                    stmts.push(quote! { __diag.span_note(__span, &#note); });
+                }
+                "help" => {
+                    let help = format_string(&string_value(attr)?, fields)?;
+                    stmts.push(quote! { __diag.span_help(__span, &#help); });
+                }
+                "suggestion" => {
+                    let with_applicability = span_field.as_ref().unwrap().applicability;
+                    let (msg, code, applicability) = suggestion(attr, fields, with_applicability)?;
+                    let applicability = applicability.unwrap_or_else(|| {
+                        if with_applicability {
+                            uses_applicability = true;
+                            quote!(__applicability)
+                        } else {
+                            quote!(::rustc_errors::Applicability::Unspecified)
+                        }
+                    });
+                    stmts.push(quote! {
+                        __diag.span_suggestion(__span, &#msg, #code, #applicability);
+                    });
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(attr, "this attribute goes on the struct"))
+                }
+            }
+        }
+
+        let span_field = match span_field {
+            Some(span_field) if !stmts.is_empty() => span_field,
+            _ => continue,
+        };
+        let member = match &field.ident {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(index.into()),
+        };
+        let pattern = match (span_field.applicability, uses_applicability) {
+            (false, _) => quote!(__span),
+            (true, false) => quote!((__span, _)),
+            (true, true) => quote!((__span, __applicability)),
+        };
+        field_stmts.push(if span_field.optional {
+            quote! {
+                if let Some(#pattern) = self.#member {
+                    #(#stmts)*
+                }
+            }
+        } else {
+            quote! {
+                let #pattern = self.#member;
+                #(#stmts)*
+            }
+        });
+    }
+
+    let message = match message {
+        Some(message) => message,
+        None => {
+            return Err(syn::Error::new_spanned(
+                &ast.ident,
+                "a diagnostic needs a `#[message = \"...\"]`, on the struct or on a field",
+            ));
+        }
+    };
+    let diag = match code {
+        Some(code) => quote! {
+            __sess.struct_err_with_code(
+                &#message,
+                ::rustc_errors::DiagnosticId::Error(#code.to_owned()),
+            )
+        },
+        None => quote! { __sess.struct_err(&#message) },
+    };
+    Ok(quote! {
+        let mut __diag = #diag;
+        #({ #field_stmts })*
+        #(#struct_stmts)*
+        __diag
+    })
+}
+
+/// The name of `attr`, if it is one of the attributes of the derive.
+fn attr_name(attr: &Attribute) -> Option<String> {
+    let ident = attr.path.get_ident()?.to_string();
+    match &ident[..] {
+        "code" | "message" | "label" | "note" | "help" | "suggestion" => Some(ident),
+        _ => None,
+    }
+}
+
+fn set_once<T>(slot: &mut Option<T>, attr: &Attribute, value: T) -> syn::Result<()> {
+    if slot.is_some() {
+        return Err(syn::Error::new_spanned(attr, "this attribute is given more than once"));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+/// The string of an attribute written `#[name = "..."]`.
+fn string_value(attr: &Attribute) -> syn::Result<LitStr> {
+    match attr.parse_meta()? {
+        Meta::NameValue(syn::MetaNameValue { lit: Lit::Str(s), .. }) => Ok(s),
+        _ => {
+            Err(syn::Error::new_spanned(attr, "expected an attribute written `#[name = \"...\"]`"))
+        }
+    }
+}
+
+fn check_error_code(code: &LitStr) -> syn::Result<()> {
+    let value = code.value();
+    if value.len() == 5 && value.starts_with('E') && value[1..].bytes().all(|b| b.is_ascii_digit())
+    {
+        Ok(())
+    } else {
+        Err(syn::Error::new(code.span(), "error codes are written `E` and four digits"))
+    }
+}
+
+/// Parses `#[suggestion(message = "...", code = "...", applicability = "...")]`.
+fn suggestion(
+    attr: &Attribute,
+    fields: &syn::Fields,
+    with_applicability: bool,
+) -> syn::Result<(TokenStream, TokenStream, Option<TokenStream>)> {
+    let list = match attr.parse_meta()? {
+        Meta::List(list) => list,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "expected `#[suggestion(message = \"...\", code = \"...\")]`",
+            ));
+        }
+    };
+    let (mut msg, mut code, mut applicability) = (None, None, None);
+    for nested in &list.nested {
+        let (name, value) = match nested {
+            NestedMeta::Meta(Meta::NameValue(syn::MetaNameValue {
+                path,
+                lit: Lit::Str(s),
+                ..
+            })) if path.get_ident().is_some() => (path.get_ident().unwrap().to_string(), s),
+            _ => return Err(syn::Error::new_spanned(nested, "expected `name = \"...\"`")),
+        };
+        match &name[..] {
+            "message" => set_once(&mut msg, attr, format_string(value, fields)?)?,
+            "code" => set_once(&mut code, attr, format_string(value, fields)?)?,
+            "applicability" => {
+                if with_applicability {
+                    return Err(syn::Error::new_spanned(
+                        nested,
+                        "the applicability is already given by the field",
+                    ));
+                }
+                if !APPLICABILITIES.contains(&&value.value()[..]) {
+                    return Err(syn::Error::new(
+                        value.span(),
+                        format!("expected one of {}", APPLICABILITIES.join(", ")),
+                    ));
+                }
+                let variant = Ident::new(&value.value(), value.span());
+                set_once(&mut applicability, attr, quote!(::rustc_errors::Applicability::#variant))?
+            }
+            _ => return Err(syn::Error::new_spanned(nested, "unknown suggestion argument")),
+        }
+    }
+    match (msg, code) {
+        (Some(msg), Some(code)) => Ok((msg, code, applicability)),
+        _ => Err(syn::Error::new_spanned(attr, "a suggestion needs a `message` and a `code`")),
+    }
+}
+
+/// Returns how a field of type `ty` is a span, if it is one.
+fn span_field(ty: &syn::Type) -> Option<SpanField> {
+    let (optional, ty) = match option_argument(ty) {
+        Some(ty) => (true, ty),
+        None => (false, ty),
+    };
+    if is_named(ty, "Span") {
+        return Some(SpanField { optional, applicability: false });
+    }
+    match ty {
+        syn::Type::Tuple(tuple)
+            if tuple.elems.len() == 2
+                && is_named(&tuple.elems[0], "Span")
+                && is_named(&tuple.elems[1], "Applicability") =>
+        {
+            Some(SpanField { optional, applicability: true })
+        }
+        _ => None,
+    }
+}
+
+/// Whether `ty` is a path whose last segment is `name`.
+fn is_named(ty: &syn::Type, name: &str) -> bool {
+    match ty {
+        syn::Type::Path(path) if path.qself.is_none() => {
+            path.path.segments.last().map_or(false, |segment| segment.ident == name)
+        }
+        _ => false,
+    }
+}
+
+/// The type `T` of an `Option<T>`.
+fn option_argument(ty: &syn::Type) -> Option<&syn::Type> {
+    if !is_named(ty, "Option") {
+        return None;
+    }
+    let segment = match ty {
+        syn::Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Builds a `format!` of the message `msg`, whose `{name}`s refer to the fields of the struct.
+fn format_string(msg: &LitStr, fields: &syn::Fields) -> syn::Result<TokenStream> {
+    let value = msg.value();
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = &value[..];
+    while let Some(start) = rest.find('{') {
+        if rest[start..].starts_with("{{") {
+            rest = &rest[start + 2..];
+            continue;
+        }
+        let end = match rest[start..].find('}') {
+            Some(len) => start + len,
+            None => return Err(syn::Error::new(msg.span(), "unterminated `{` in the message")),
+        };
+        let arg = &rest[start + 1..end];
+        let name = arg.split(':').next().unwrap().trim();
+        let is_field = fields.iter().any(|f| f.ident.as_ref().map_or(false, |i| i == name));
+        if !is_field {
+            return Err(syn::Error::new(
+                msg.span(),
+                format!("`{{{}}}` doesn't refer to a field of the struct", arg),
+            ));
+        }
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_owned());
+        }
+        rest = &rest[end + 1..];
+    }
+
+    let idents: Vec<_> = names.iter().map(|name| Ident::new(name, msg.span())).collect();
+    Ok(quote! { format!(#msg #(, #idents = self.#idents)*) })
+}
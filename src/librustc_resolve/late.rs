@@ -1618,8 +1618,17 @@ impl<'a, 'b, 'ast> LateResolutionVisitor<'a, 'b, 'ast> {
         let is_expected = &|res| source.is_expected(res);
 
         let report_errors = |this: &mut Self, res: Option<Res>| {
-            let (err, candidates) = this.smart_resolve_report_errors(path, span, source, res);
+            let (mut err, candidates) = this.smart_resolve_report_errors(path, span, source, res);
             let def_id = this.parent_scope.module.normal_ancestor_id;
+            if let (None, Some(err_span)) = (res, err.span.primary_span()) {
+                // A name missing from a module, e.g. because of a missing import, is reported
+                // once, and the errors about its other uses are grouped with that first error.
+                let names = path.iter().map(|segment| segment.ident.name).collect();
+                let first = *this.r.unresolved_paths.entry((def_id, ns, names)).or_insert(err_span);
+                if first != err_span {
+                    err.caused_by(first);
+                }
+            }
             let node_id = this.r.definitions.as_local_node_id(def_id).unwrap();
             let better = res.is_some();
             let suggestion =
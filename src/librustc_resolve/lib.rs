@@ -905,6 +905,9 @@ pub struct Resolver<'a> {
     ambiguity_errors: Vec<AmbiguityError<'a>>,
     /// `use` injections are delayed for better placement and deduplication.
     use_injections: Vec<UseError<'a>>,
+    /// The primary span of the error about the first use of each path that couldn't be
+    /// resolved, by module. The errors about the other uses of the path are its consequences.
+    unresolved_paths: FxHashMap<(DefId, Namespace, Vec<Symbol>), Span>,
     /// Crate-local macro expanded `macro_export` referred to by a module-relative path.
     macro_expanded_macro_export_errors: BTreeSet<(Span, Span)>,
 
@@ -1204,6 +1207,7 @@ impl<'a> Resolver<'a> {
             privacy_errors: Vec::new(),
             ambiguity_errors: Vec::new(),
             use_injections: Vec::new(),
+            unresolved_paths: FxHashMap::default(),
             macro_expanded_macro_export_errors: BTreeSet::new(),
 
             arenas,
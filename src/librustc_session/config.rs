@@ -282,6 +282,8 @@ pub enum ErrorOutputType {
         /// human output.
         json_rendered: HumanReadableErrorType,
     },
+    /// A SARIF log of all the diagnostics of the compilation, for code scanning tools.
+    Sarif,
 }
 
 impl Default for ErrorOutputType {
@@ -638,6 +640,7 @@ impl DebuggingOptions {
             report_delayed_bugs: self.report_delayed_bugs,
             macro_backtrace: self.macro_backtrace,
             deduplicate_diagnostics: self.deduplicate_diagnostics,
+            verbose_diagnostics: self.verbose_diagnostics,
         }
     }
 }
@@ -1132,6 +1135,7 @@ pub fn parse_error_format(
             }
             Some("json") => ErrorOutputType::Json { pretty: false, json_rendered },
             Some("pretty-json") => ErrorOutputType::Json { pretty: true, json_rendered },
+            Some("sarif") => ErrorOutputType::Sarif,
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short(color)),
 
             Some(arg) => early_error(
@@ -1215,6 +1219,9 @@ fn check_debug_option_stability(
                 "`--error-format=human-annotate-rs` is unstable",
             );
         }
+        if let ErrorOutputType::Sarif = error_format {
+            early_error(ErrorOutputType::default(), "`--error-format=sarif` is unstable");
+        }
     }
 }
 
@@ -8,6 +8,7 @@ use crate::utils::NativeLibraryKind;
 use rustc_target::spec::{CodeModel, LinkerFlavor, MergeFunctions, PanicStrategy};
 use rustc_target::spec::{RelocModel, RelroLevel, TargetTriple, TlsModel};
 
+use rustc_errors::Applicability;
 use rustc_feature::UnstableFeatures;
 use rustc_span::edition::Edition;
 use rustc_span::SourceFileHashAlgorithm;
@@ -273,6 +274,8 @@ macro_rules! options {
         pub const parse_tls_model: &str =
             "one of supported TLS models (`rustc --print tls-models`)";
         pub const parse_target_feature: &str = parse_string;
+        pub const parse_apply_suggestions: &str =
+            "either no value, `machine-applicable` or `maybe-incorrect`";
     }
 
     #[allow(dead_code)]
@@ -658,6 +661,15 @@ macro_rules! options {
             true
         }
 
+        fn parse_apply_suggestions(slot: &mut Option<Applicability>, v: Option<&str>) -> bool {
+            *slot = match v {
+                None | Some("machine-applicable") => Some(Applicability::MachineApplicable),
+                Some("maybe-incorrect") => Some(Applicability::MaybeIncorrect),
+                _ => return false,
+            };
+            true
+        }
+
         fn parse_target_feature(slot: &mut String, v: Option<&str>) -> bool {
             match v {
                 Some(s) => {
@@ -789,6 +801,9 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "only allow the listed language features to be enabled in code (space separated)"),
     always_encode_mir: bool = (false, parse_bool, [TRACKED],
         "encode MIR of all functions into the crate metadata (default: no)"),
+    apply_suggestions: Option<Applicability> = (None, parse_apply_suggestions, [UNTRACKED],
+        "rewrite the source files with the suggestions of the diagnostics that are \
+        `machine-applicable` (the default), or also those that are `maybe-incorrect`"),
     asm_comments: bool = (false, parse_bool, [TRACKED],
         "generate comments into the assembly (may change behavior) (default: no)"),
     ast_json: bool = (false, parse_bool, [UNTRACKED],
@@ -878,6 +893,9 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         (default: no)"),
     instrument_mcount: bool = (false, parse_bool, [TRACKED],
         "insert function instrument code for mcount-based tracing (default: no)"),
+    keep_fn_bodies: bool = (false, parse_bool, [TRACKED],
+        "keep the bodies of functions when running rustdoc, instead of replacing them with \
+        `loop {}` (default: no)"),
     keep_hygiene_data: bool = (false, parse_bool, [UNTRACKED],
         "keep hygiene data after analysis (default: no)"),
     link_native_libraries: bool = (true, parse_bool, [UNTRACKED],
@@ -1025,6 +1043,11 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "choose the TLS model to use (`rustc --print tls-models` for details)"),
     trace_macros: bool = (false, parse_bool, [UNTRACKED],
         "for every macro invocation, print its name and arguments (default: no)"),
+    translate_catalog: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "translate the diagnostics with this catalog, which takes precedence over the one \
+        of `-Z translate-lang`"),
+    translate_lang: Option<String> = (None, parse_opt_string, [UNTRACKED],
+        "language of the diagnostics, such as `fr` (default: `en-US`)"),
     treat_err_as_bug: Option<usize> = (None, parse_treat_err_as_bug, [TRACKED],
         "treat error number `val` that occurs as bug"),
     ui_testing: bool = (false, parse_bool, [UNTRACKED],
@@ -1047,6 +1070,9 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "use legacy .ctors section for initializers rather than .init_array"),
     verbose: bool = (false, parse_bool, [UNTRACKED],
         "in general, enable more debug printouts (default: no)"),
+    verbose_diagnostics: bool = (false, parse_bool, [UNTRACKED],
+        "show the errors that are consequences of other errors, instead of only counting them \
+        (default: no)"),
     verify_llvm_ir: bool = (false, parse_bool, [TRACKED],
         "verify LLVM IR (default: no)"),
 
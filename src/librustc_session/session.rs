@@ -17,8 +17,11 @@ use rustc_data_structures::sync::{
 };
 use rustc_errors::annotate_snippet_emitter_writer::AnnotateSnippetEmitterWriter;
 use rustc_errors::emitter::{Emitter, EmitterWriter, HumanReadableErrorType};
+use rustc_errors::fix::FixingEmitter;
 use rustc_errors::json::JsonEmitter;
 use rustc_errors::registry::Registry;
+use rustc_errors::sarif::SarifEmitter;
+use rustc_errors::translation::{Catalog, TranslatingEmitter};
 use rustc_errors::{Applicability, DiagnosticBuilder, DiagnosticId, ErrorReported};
 use rustc_span::edition::Edition;
 use rustc_span::source_map::{self, FileLoader, MultiSpan, RealFileLoader, SourceMap, Span};
@@ -30,7 +33,7 @@ use std::cell::{self, RefCell};
 use std::env;
 use std::io::Write;
 use std::num::NonZeroU32;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -53,6 +56,13 @@ pub enum CtfeBacktrace {
     Immediate,
 }
 
+/// A diagnostic that can be built out of its own data, usually with
+/// `#[derive(SessionDiagnostic)]`, and then emitted with `Session::emit_err`.
+pub trait SessionDiagnostic<'a> {
+    /// Builds the diagnostic, ready to be emitted.
+    fn into_diagnostic(self, sess: &'a Session) -> DiagnosticBuilder<'a>;
+}
+
 /// Represents the data associated with a compilation
 /// session for a single crate.
 pub struct Session {
@@ -285,6 +295,9 @@ impl Session {
     pub fn struct_fatal(&self, msg: &str) -> DiagnosticBuilder<'_> {
         self.diagnostic().struct_fatal(msg)
     }
+    pub fn emit_err<'a>(&'a self, err: impl SessionDiagnostic<'a>) {
+        err.into_diagnostic(self).emit()
+    }
 
     pub fn span_fatal<S: Into<MultiSpan>>(&self, sp: S, msg: &str) -> ! {
         self.diagnostic().span_fatal(sp, msg).raise()
@@ -973,10 +986,11 @@ fn default_emitter(
     sopts: &config::Options,
     registry: rustc_errors::registry::Registry,
     source_map: &Lrc<source_map::SourceMap>,
+    sysroot: &Path,
     emitter_dest: Option<Box<dyn Write + Send>>,
 ) -> Box<dyn Emitter + sync::Send> {
     let macro_backtrace = sopts.debugging_opts.macro_backtrace;
-    match (sopts.error_format, emitter_dest) {
+    let emitter: Box<dyn Emitter + sync::Send> = match (sopts.error_format, emitter_dest) {
         (config::ErrorOutputType::HumanReadable(kind), dst) => {
             let (short, color_config) = kind.unzip();
 
@@ -1031,6 +1045,29 @@ fn default_emitter(
             )
             .ui_testing(sopts.debugging_opts.ui_testing),
         ),
+        (config::ErrorOutputType::Sarif, None) => {
+            Box::new(SarifEmitter::stderr(Some(registry), source_map.clone()))
+        }
+        (config::ErrorOutputType::Sarif, Some(dst)) => {
+            Box::new(SarifEmitter::new(dst, Some(registry), source_map.clone()))
+        }
+    };
+
+    let catalog = Catalog::load(
+        sopts.debugging_opts.translate_lang.as_deref(),
+        sysroot,
+        sopts.debugging_opts.translate_catalog.as_deref(),
+    )
+    .unwrap_or_else(|e| early_error(sopts.error_format, &e));
+    let emitter: Box<dyn Emitter + sync::Send> = match catalog {
+        Some(catalog) => Box::new(TranslatingEmitter::new(emitter, catalog)),
+        None => emitter,
+    };
+    match sopts.debugging_opts.apply_suggestions {
+        Some(applicability) => {
+            Box::new(FixingEmitter::new(emitter, source_map.clone(), applicability))
+        }
+        None => emitter,
     }
 }
 
@@ -1084,7 +1121,11 @@ pub fn build_session_with_source_map(
         sopts.file_path_mapping(),
         hash_kind,
     ));
-    let emitter = default_emitter(&sopts, registry, &source_map, write_dest);
+    let sysroot = match &sopts.maybe_sysroot {
+        Some(sysroot) => sysroot.clone(),
+        None => filesearch::get_or_default_sysroot(),
+    };
+    let emitter = default_emitter(&sopts, registry, &source_map, &sysroot, write_dest);
 
     let span_diagnostic = rustc_errors::Handler::with_emitter_and_flags(
         emitter,
@@ -1113,10 +1154,6 @@ pub fn build_session_with_source_map(
     };
 
     let parse_sess = ParseSess::with_span_handler(span_diagnostic, source_map.clone());
-    let sysroot = match &sopts.maybe_sysroot {
-        Some(sysroot) => sysroot.clone(),
-        None => filesearch::get_or_default_sysroot(),
-    };
 
     let host_triple = config::host_triple();
     let target_triple = sopts.target_triple.triple();
@@ -1355,6 +1392,7 @@ pub fn early_error(output: config::ErrorOutputType, msg: &str) -> ! {
         config::ErrorOutputType::Json { pretty, json_rendered } => {
             Box::new(JsonEmitter::basic(pretty, json_rendered, false))
         }
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::basic()),
     };
     let handler = rustc_errors::Handler::with_emitter(true, None, emitter);
     handler.struct_fatal(msg).emit();
@@ -1370,6 +1408,7 @@ pub fn early_warn(output: config::ErrorOutputType, msg: &str) {
         config::ErrorOutputType::Json { pretty, json_rendered } => {
             Box::new(JsonEmitter::basic(pretty, json_rendered, false))
         }
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::basic()),
     };
     let handler = rustc_errors::Handler::with_emitter(true, None, emitter);
     handler.struct_warn(msg).emit();
@@ -715,6 +715,16 @@ impl MultiSpan {
         replacements_occurred
     }
 
+    /// Replaces the labels of `span` that read `before` with `after`. Used to translate the
+    /// labels of a diagnostic.
+    pub fn replace_label(&mut self, span: Span, before: &str, after: String) {
+        for span_label in &mut self.span_labels {
+            if span_label.0 == span && span_label.1 == before {
+                span_label.1 = after.clone();
+            }
+        }
+    }
+
     /// Returns the strings to highlight. We always ensure that there
     /// is an entry for each of the primary spans -- for each primary
     /// span `P`, if there is at least one label with span `P`, we return
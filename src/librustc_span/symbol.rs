@@ -161,6 +161,7 @@ symbols! {
         attributes,
         attr_literals,
         augmented_assignments,
+        auto_cfg,
         automatically_derived,
         avx512_target_feature,
         await_macro,
@@ -187,6 +188,7 @@ symbols! {
         cfg_attr,
         cfg_attr_multi,
         cfg_doctest,
+        cfg_hide,
         cfg_sanitize,
         cfg_target_feature,
         cfg_target_has_atomic,
@@ -738,7 +740,9 @@ symbols! {
         test_accepted_feature,
         test_case,
         test_removed_feature,
+        test_retries,
         test_runner,
+        test_timeout,
         then_with,
         thread,
         thread_local,
@@ -20,7 +20,8 @@ use rustc_ast::ast;
 use rustc_ast::util::lev_distance::find_best_match_for_name;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_errors::ErrorReported;
-use rustc_errors::{pluralize, struct_span_err, Applicability, DiagnosticBuilder, DiagnosticId};
+use rustc_errors::{pluralize, struct_span_err, struct_span_err_translatable};
+use rustc_errors::{Applicability, DiagnosticBuilder, DiagnosticId, TranslatableMessage};
 use rustc_hir as hir;
 use rustc_hir::def::{CtorKind, DefKind, Res};
 use rustc_hir::def_id::DefId;
@@ -1215,16 +1216,22 @@ impl<'a, 'tcx> FnCtxt<'a, 'tcx> {
             } else {
                 error_happened = true;
                 if let Some(prev_span) = seen_fields.get(&ident) {
-                    let mut err = struct_span_err!(
+                    let mut err = struct_span_err_translatable!(
                         self.tcx.sess,
                         field.ident.span,
                         E0062,
-                        "field `{}` specified more than once",
-                        ident
+                        TranslatableMessage::new("typeck-field-multiply-specified")
+                            .arg("ident", ident),
                     );
 
-                    err.span_label(field.ident.span, "used more than once");
-                    err.span_label(*prev_span, format!("first use of `{}`", ident));
+                    err.span_label_translatable(
+                        field.ident.span,
+                        TranslatableMessage::new("typeck-field-multiply-specified-label"),
+                    );
+                    err.span_label_translatable(
+                        *prev_span,
+                        TranslatableMessage::new("typeck-field-first-use").arg("ident", ident),
+                    );
 
                     err.emit();
                 } else {
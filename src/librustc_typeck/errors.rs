@@ -0,0 +1,16 @@
+//! Errors emitted by typeck.
+
+use rustc_macros::SessionDiagnostic;
+use rustc_span::symbol::Ident;
+use rustc_span::Span;
+
+#[derive(SessionDiagnostic)]
+#[code = "E0124"]
+pub struct FieldAlreadyDeclared {
+    pub field_name: Ident,
+    #[message = "field `{field_name}` is already declared"]
+    #[label = "field already declared"]
+    pub span: Span,
+    #[label = "`{field_name}` first declared here"]
+    pub prev_span: Span,
+}
@@ -0,0 +1,727 @@
+//! Comparing the public API of two versions of a crate, for `--api-diff`.
+//!
+//! Both versions are loaded from their compiled metadata as dependencies of an otherwise empty
+//! crate, and their items are cleaned the same way as the items rustdoc inlines from other
+//! crates. The public items of each version are then flattened into a map from their paths to a
+//! plain-text form of their declarations, and the differences between the two maps are classified
+//! as breaking or not, following the Rust semver guidelines of RFC 1105.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use rustc_ast::ast::CRATE_NODE_ID;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir as hir;
+use rustc_hir::def::{DefKind, Namespace::TypeNS, Res};
+use rustc_hir::def_id::{CrateNum, DefId, CRATE_DEF_INDEX};
+use rustc_session::config::{ExternEntry, ExternLocation, Externs, Input};
+use rustc_span::symbol::{sym, Symbol};
+use rustc_span::{FileName, DUMMY_SP};
+use serde::Serialize;
+
+use crate::clean::{self, inline, AttributesExt, NestedAttributesExt};
+use crate::config::OutputFormat;
+use crate::core::DocContext;
+use crate::doctree;
+use crate::html::format::{print_abi_with_space, PrintWithSpace};
+use crate::html::item_type::ItemType;
+
+/// The name the old version of the crate is loaded under.
+const OLD_CRATE: &str = "rustdoc_api_diff_old";
+/// The name the new version of the crate is loaded under.
+const NEW_CRATE: &str = "rustdoc_api_diff_new";
+
+/// Options for comparing two versions of a crate.
+#[derive(Clone, Debug)]
+pub struct ApiDiffOptions {
+    /// The compiled metadata (`.rmeta` or `.rlib`) of the old version of the crate.
+    pub old: PathBuf,
+    /// The compiled metadata of the new version of the crate.
+    pub new: PathBuf,
+}
+
+impl ApiDiffOptions {
+    /// Returns the input of the empty crate both versions are loaded into.
+    crate fn input(&self) -> Input {
+        Input::Str { name: FileName::Custom("api-diff".to_string()), input: String::new() }
+    }
+
+    /// Adds both versions of the crate to the given `--extern`s, under names which can't clash
+    /// with each other even if both versions have the same crate name.
+    crate fn externs(&self, externs: Externs) -> Externs {
+        let mut externs: BTreeMap<_, _> =
+            externs.iter().map(|(name, entry)| (name.clone(), entry.clone())).collect();
+        for &(name, ref path) in &[(OLD_CRATE, &self.old), (NEW_CRATE, &self.new)] {
+            let location =
+                ExternLocation::ExactPaths(Some(path.display().to_string()).into_iter().collect());
+            externs.insert(
+                name.to_string(),
+                ExternEntry { location, is_private_dep: false, add_prelude: true },
+            );
+        }
+        Externs::new(externs)
+    }
+}
+
+/// Compares the public APIs of the two versions of the crate, and prints their differences to
+/// stdout.
+crate fn run(cx: &DocContext<'_>, output_format: Option<OutputFormat>) {
+    let old = crate_num(cx, OLD_CRATE);
+    let new = crate_num(cx, NEW_CRATE);
+    // The items of both versions are named after the new version, so that renaming the crate
+    // doesn't show up as every item being removed and added back.
+    let root = cx.tcx.crate_name(new);
+    let report = diff(&collect_api(cx, old, root), &collect_api(cx, new, root));
+
+    if output_format.map(|o| o.is_json()).unwrap_or(false) {
+        println!(
+            "{}",
+            serde_json::to_string(&report).expect("failed to convert JSON data to string")
+        );
+    } else {
+        report.print();
+    }
+}
+
+fn crate_num(cx: &DocContext<'_>, extern_name: &str) -> CrateNum {
+    let (_, res) = cx
+        .enter_resolver(|resolver| {
+            resolver.resolve_str_path_error(DUMMY_SP, extern_name, TypeNS, CRATE_NODE_ID)
+        })
+        .unwrap_or_else(|()| panic!("Unable to resolve external crate {}", extern_name));
+    res.def_id().krate
+}
+
+/// Identifies an item across the two versions of the crate. Required and provided trait methods
+/// share the `Method` item type, so that providing a default implementation for a method isn't
+/// seen as a different item.
+type ItemKey = (String, ItemType);
+
+/// A public item of one version of the crate.
+struct ApiItem {
+    ty: ItemType,
+    /// The declaration of the item, without the bounds on its generics.
+    decl: String,
+    /// The bounds on the generics of the item, and the supertraits of traits, one per entry.
+    bounds: BTreeSet<String>,
+    /// The item this one belongs to: the module of an item, the type of a field or of a method,
+    /// the trait of a trait item...
+    parent: Option<ItemKey>,
+    /// Whether values of this struct or enum can be built or matched exhaustively by the users of
+    /// the crate, in which case adding fields or variants to it is a breaking change.
+    exhaustive: bool,
+    /// Whether this trait item must be provided by the implementations of the trait.
+    required: bool,
+    /// Whether this is a trait or a trait item, which the users of the crate implement: their
+    /// bounds can't be loosened without breaking those implementations either.
+    implementable: bool,
+}
+
+impl ApiItem {
+    fn new(ty: ItemType, decl: String, parent: Option<&ItemKey>) -> ApiItem {
+        ApiItem {
+            ty,
+            decl,
+            bounds: BTreeSet::new(),
+            parent: parent.cloned(),
+            exhaustive: false,
+            required: false,
+            implementable: false,
+        }
+    }
+}
+
+/// The public API of one version of the crate.
+type Api = BTreeMap<ItemKey, ApiItem>;
+
+fn collect_api(cx: &DocContext<'_>, cnum: CrateNum, root: Symbol) -> Api {
+    let root = root.to_string();
+    let mut collector = ApiCollector { cx, cnum, root: root.clone(), api: Api::new() };
+    let root_key = collector.insert(&root, ItemType::Module, &format!("mod {}", root), None);
+
+    let root_did = DefId { krate: cnum, index: CRATE_DEF_INDEX };
+    let mut visited = FxHashSet::default();
+    let res = Res::Def(DefKind::Mod, root_did);
+    let items = inline::try_inline(cx, res, cx.tcx.crate_name(cnum), None, &mut visited)
+        .unwrap_or_default();
+    for item in &items {
+        if let clean::ModuleItem(ref module) = item.inner {
+            collector.visit_module(&module.items, &root_key);
+        }
+    }
+
+    // Implementations of traits aren't children of any module, so they're looked up separately.
+    let mut impls = Vec::new();
+    for &did in cx.tcx.all_trait_implementations(cnum).iter() {
+        inline::build_impl(cx, did, None, &mut impls);
+    }
+    collector.visit_module(&impls, &root_key);
+
+    collector.api
+}
+
+struct ApiCollector<'a, 'tcx> {
+    cx: &'a DocContext<'tcx>,
+    cnum: CrateNum,
+    root: String,
+    api: Api,
+}
+
+impl ApiCollector<'_, '_> {
+    fn insert(
+        &mut self,
+        path: &str,
+        ty: ItemType,
+        decl: &str,
+        parent: Option<&ItemKey>,
+    ) -> ItemKey {
+        self.insert_item(path, ApiItem::new(ty, decl.to_string(), parent))
+    }
+
+    fn insert_item(&mut self, path: &str, item: ApiItem) -> ItemKey {
+        let key_ty = if item.ty == ItemType::TyMethod { ItemType::Method } else { item.ty };
+        let key = (path.to_string(), key_ty);
+        self.api.insert(key.clone(), item);
+        key
+    }
+
+    fn visit_module(&mut self, items: &[clean::Item], key: &ItemKey) {
+        // The items of inherent implementations are added to the API as items of their type, so
+        // the implementations are visited once all the types of the module are known.
+        let (impls, items): (Vec<_>, Vec<_>) = items.iter().partition(|item| match item.inner {
+            clean::ImplItem(_) => true,
+            _ => false,
+        });
+        for item in items.into_iter().chain(impls) {
+            self.visit_item(item, key);
+        }
+    }
+
+    /// Adds the public items found in `item` to the API, `parent` being the module it was found
+    /// in.
+    fn visit_item(&mut self, item: &clean::Item, parent: &ItemKey) {
+        if item.attrs.lists(sym::doc).has_word(sym::hidden) {
+            return;
+        }
+        let name = item.name.as_deref().unwrap_or("");
+        let path = format!("{}::{}", parent.0, name);
+        let ty = item.type_();
+
+        match item.inner {
+            clean::ModuleItem(ref module) => {
+                let key = self.insert(&path, ty, &format!("mod {}", name), Some(parent));
+                self.visit_module(&module.items, &key);
+            }
+            clean::StructItem(clean::Struct { struct_type, ref generics, ref fields, .. })
+            | clean::UnionItem(clean::Union { struct_type, ref generics, ref fields, .. }) => {
+                let keyword = if ty == ItemType::Union { "union" } else { "struct" };
+                let decl = format!(
+                    "{} {}{}{}",
+                    keyword,
+                    name,
+                    generic_params(generics),
+                    struct_body(struct_type)
+                );
+                let mut api_item = ApiItem::new(ty, decl, Some(parent));
+                generic_bounds(generics, &mut api_item.bounds);
+                api_item.exhaustive = !item.is_non_exhaustive()
+                    && fields.iter().all(|field| field.visibility == clean::Public);
+                let key = self.insert_item(&path, api_item);
+                self.visit_fields(fields, &key);
+            }
+            clean::EnumItem(ref enum_) => {
+                let decl = format!("enum {}{}", name, generic_params(&enum_.generics));
+                let mut api_item = ApiItem::new(ty, decl, Some(parent));
+                generic_bounds(&enum_.generics, &mut api_item.bounds);
+                api_item.exhaustive = !item.is_non_exhaustive();
+                let key = self.insert_item(&path, api_item);
+                for variant in &enum_.variants {
+                    if let clean::VariantItem(ref v) = variant.inner {
+                        let name = variant.name.as_deref().unwrap_or("");
+                        let decl = match v.kind {
+                            clean::VariantKind::CLike => name.to_string(),
+                            clean::VariantKind::Tuple(ref types) => format!(
+                                "{}({})",
+                                name,
+                                types
+                                    .iter()
+                                    .map(|ty| format!("{:#}", ty.print()))
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            ),
+                            clean::VariantKind::Struct(ref s) => {
+                                let fields = s
+                                    .fields
+                                    .iter()
+                                    .filter_map(|field| match field.inner {
+                                        clean::StructFieldItem(ref ty) => Some(format!(
+                                            "{}: {:#}",
+                                            field.name.as_deref().unwrap_or(""),
+                                            ty.print()
+                                        )),
+                                        _ => None,
+                                    })
+                                    .collect::<Vec<_>>();
+                                format!("{} {{ {} }}", name, fields.join(", "))
+                            }
+                        };
+                        let path = format!("{}::{}", key.0, name);
+                        self.insert(&path, variant.type_(), &decl, Some(&key));
+                    }
+                }
+            }
+            clean::FunctionItem(ref f) | clean::ForeignFunctionItem(ref f) => {
+                let decl = function(name, &f.header, &f.generics, &f.decl);
+                let mut api_item = ApiItem::new(ty, decl, Some(parent));
+                generic_bounds(&f.generics, &mut api_item.bounds);
+                self.insert_item(&path, api_item);
+            }
+            clean::TypedefItem(ref t, _) => {
+                let decl =
+                    format!("type {}{} = {:#}", name, generic_params(&t.generics), t.type_.print());
+                let mut api_item = ApiItem::new(ty, decl, Some(parent));
+                generic_bounds(&t.generics, &mut api_item.bounds);
+                self.insert_item(&path, api_item);
+            }
+            clean::OpaqueTyItem(ref t, _) => {
+                let decl = format!("type {}{} = impl _", name, generic_params(&t.generics));
+                let mut api_item = ApiItem::new(ty, decl, Some(parent));
+                generic_bounds(&t.generics, &mut api_item.bounds);
+                for bound in &t.bounds {
+                    api_item.bounds.insert(format!("impl {:#}", bound.print()));
+                }
+                self.insert_item(&path, api_item);
+            }
+            clean::StaticItem(ref s) | clean::ForeignStaticItem(ref s) => {
+                let decl = format!(
+                    "static {}{}: {:#}",
+                    s.mutability.print_with_space(),
+                    name,
+                    s.type_.print()
+                );
+                self.insert(&path, ty, &decl, Some(parent));
+            }
+            clean::ConstantItem(ref c) => {
+                let decl = format!("const {}: {:#}", name, c.type_.print());
+                self.insert(&path, ty, &decl, Some(parent));
+            }
+            clean::TraitItem(ref t) => {
+                let decl = format!(
+                    "{}{}trait {}{}",
+                    t.unsafety.print_with_space(),
+                    if t.is_auto { "auto " } else { "" },
+                    name,
+                    generic_params(&t.generics)
+                );
+                let mut api_item = ApiItem::new(ty, decl, Some(parent));
+                generic_bounds(&t.generics, &mut api_item.bounds);
+                for bound in &t.bounds {
+                    api_item.bounds.insert(format!("Self: {:#}", bound.print()));
+                }
+                api_item.implementable = true;
+                let key = self.insert_item(&path, api_item);
+                for item in &t.items {
+                    self.visit_assoc_item(item, &key, None, true);
+                }
+            }
+            clean::TraitAliasItem(ref t) => {
+                let decl = format!("trait {}{}", name, generic_params(&t.generics));
+                let mut api_item = ApiItem::new(ty, decl, Some(parent));
+                generic_bounds(&t.generics, &mut api_item.bounds);
+                for bound in &t.bounds {
+                    api_item.bounds.insert(format!("Self: {:#}", bound.print()));
+                }
+                self.insert_item(&path, api_item);
+            }
+            clean::ImplItem(ref i) => match i.trait_ {
+                None => {
+                    let type_name = match i.for_ {
+                        clean::ResolvedPath { ref path, .. } => path.last_name().to_string(),
+                        ref for_ => format!("{:#}", for_.print()),
+                    };
+                    let type_key = (format!("{}::{}", parent.0, type_name), ItemType::Struct);
+                    let type_key = [ItemType::Struct, ItemType::Enum, ItemType::Union]
+                        .iter()
+                        .map(|&ty| (type_key.0.clone(), ty))
+                        .find(|key| self.api.contains_key(key))
+                        .unwrap_or(type_key);
+                    for item in &i.items {
+                        self.visit_assoc_item(item, &type_key, Some(&i.generics), false);
+                    }
+                }
+                Some(ref trait_) => {
+                    let header = format!(
+                        "impl{} {}{} for {}",
+                        generic_params(&i.generics),
+                        if i.polarity == Some(clean::ImplPolarity::Negative) { "!" } else { "" },
+                        self.type_path(trait_),
+                        self.type_path(&i.for_)
+                    );
+                    let mut assoc_types = Vec::new();
+                    for item in &i.items {
+                        if let clean::TypedefItem(ref t, true) = item.inner {
+                            assoc_types.push(format!(
+                                "type {} = {:#};",
+                                item.name.as_deref().unwrap_or(""),
+                                t.type_.print()
+                            ));
+                        }
+                    }
+                    let mut decl = header.clone();
+                    if !assoc_types.is_empty() {
+                        write!(decl, " {{ {} }}", assoc_types.join(" ")).unwrap();
+                    }
+                    let mut api_item = ApiItem::new(ty, decl, None);
+                    generic_bounds(&i.generics, &mut api_item.bounds);
+                    self.insert_item(&header, api_item);
+                }
+            },
+            clean::MacroItem(_) => {
+                self.insert(&path, ty, &format!("macro_rules! {}", name), Some(parent));
+            }
+            clean::ProcMacroItem(_) => {
+                self.insert(&path, ty, &format!("{} {}", ty, name), Some(parent));
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_fields(&mut self, fields: &[clean::Item], parent: &ItemKey) {
+        for field in fields {
+            if field.visibility != clean::Public {
+                continue;
+            }
+            if let clean::StructFieldItem(ref ty) = field.inner {
+                let name = field.name.as_deref().unwrap_or("");
+                let decl = format!("{}: {:#}", name, ty.print());
+                self.insert(&format!("{}::{}", parent.0, name), field.type_(), &decl, Some(parent));
+            }
+        }
+    }
+
+    /// Adds an item of a trait or of an inherent implementation to the API. `generics` are the
+    /// generics of the implementation the item is found in, whose bounds apply to the item too.
+    fn visit_assoc_item(
+        &mut self,
+        item: &clean::Item,
+        parent: &ItemKey,
+        generics: Option<&clean::Generics>,
+        in_trait: bool,
+    ) {
+        if !in_trait && item.visibility != clean::Public {
+            return;
+        }
+        if item.attrs.lists(sym::doc).has_word(sym::hidden) {
+            return;
+        }
+        let name = item.name.as_deref().unwrap_or("");
+        let (decl, required) = match item.inner {
+            clean::TyMethodItem(ref m) => (function(name, &m.header, &m.generics, &m.decl), true),
+            clean::MethodItem(ref m) => (function(name, &m.header, &m.generics, &m.decl), false),
+            clean::AssocConstItem(ref ty, ref default) => {
+                (format!("const {}: {:#}", name, ty.print()), default.is_none())
+            }
+            clean::AssocTypeItem(_, ref default) => (format!("type {}", name), default.is_none()),
+            _ => return,
+        };
+
+        let mut api_item = ApiItem::new(item.type_(), decl, Some(parent));
+        api_item.required = in_trait && required;
+        api_item.implementable = in_trait;
+        if let Some(generics) = generics {
+            generic_bounds(generics, &mut api_item.bounds);
+        }
+        match item.inner {
+            clean::TyMethodItem(clean::TyMethod { ref generics, .. })
+            | clean::MethodItem(clean::Method { ref generics, .. }) => {
+                generic_bounds(generics, &mut api_item.bounds);
+            }
+            clean::AssocTypeItem(ref bounds, _) => {
+                for bound in bounds {
+                    api_item.bounds.insert(format!("{}: {:#}", name, bound.print()));
+                }
+            }
+            _ => {}
+        }
+        self.insert_item(&format!("{}::{}", parent.0, name), api_item);
+    }
+
+    /// Prints a type with the full path of the module it is defined in, so that the types the
+    /// implementations are for can be told apart.
+    fn type_path(&self, ty: &clean::Type) -> String {
+        let did = match *ty {
+            clean::ResolvedPath { did, is_generic: false, .. } => did,
+            _ => return format!("{:#}", ty.print()),
+        };
+        let tcx = self.cx.tcx;
+        let krate = if did.krate == self.cnum {
+            self.root.clone()
+        } else {
+            tcx.crate_name(did.krate).to_string()
+        };
+        let mut path = vec![krate];
+        let data = tcx.def_path(did).data;
+        path.extend(
+            data[..data.len().saturating_sub(1)]
+                .iter()
+                .map(|elem| elem.data.to_string())
+                .filter(|s| !s.is_empty()),
+        );
+        path.push(format!("{:#}", ty.print()));
+        path.join("::")
+    }
+}
+
+/// Prints the generic parameters of an item, without their bounds.
+fn generic_params(generics: &clean::Generics) -> String {
+    let params = generics
+        .params
+        .iter()
+        .filter(|param| !param.is_synthetic_type_param())
+        .map(|param| match param.kind {
+            clean::GenericParamDefKind::Lifetime => param.name.clone(),
+            clean::GenericParamDefKind::Type { default: Some(ref default), .. } => {
+                format!("{} = {:#}", param.name, default.print())
+            }
+            clean::GenericParamDefKind::Type { default: None, .. } => param.name.clone(),
+            clean::GenericParamDefKind::Const { ref ty, .. } => {
+                format!("const {}: {:#}", param.name, ty.print())
+            }
+        })
+        .collect::<Vec<_>>();
+    if params.is_empty() { String::new() } else { format!("<{}>", params.join(", ")) }
+}
+
+/// Adds the bounds on the generic parameters of an item, whether they are given inline or in
+/// its where clause, to `bounds`.
+fn generic_bounds(generics: &clean::Generics, bounds: &mut BTreeSet<String>) {
+    for param in &generics.params {
+        if param.is_synthetic_type_param() {
+            continue;
+        }
+        for bound in param.get_bounds().unwrap_or(&[]) {
+            bounds.insert(format!("{}: {:#}", param.name, bound.print()));
+        }
+    }
+    for predicate in &generics.where_predicates {
+        match *predicate {
+            clean::WherePredicate::BoundPredicate { ref ty, bounds: ref ty_bounds } => {
+                for bound in ty_bounds {
+                    bounds.insert(format!("{:#}: {:#}", ty.print(), bound.print()));
+                }
+            }
+            clean::WherePredicate::RegionPredicate { ref lifetime, bounds: ref lt_bounds } => {
+                for bound in lt_bounds {
+                    bounds.insert(format!("{}: {:#}", lifetime.print(), bound.print()));
+                }
+            }
+            clean::WherePredicate::EqPredicate { ref lhs, ref rhs } => {
+                bounds.insert(format!("{:#} == {:#}", lhs.print(), rhs.print()));
+            }
+        }
+    }
+}
+
+fn struct_body(struct_type: doctree::StructType) -> &'static str {
+    match struct_type {
+        doctree::StructType::Plain => " { .. }",
+        doctree::StructType::Tuple => "(..)",
+        doctree::StructType::Unit => ";",
+    }
+}
+
+/// Prints the signature of a function, leaving out the names of its arguments, which users of
+/// the function don't depend on.
+fn function(
+    name: &str,
+    header: &hir::FnHeader,
+    generics: &clean::Generics,
+    decl: &clean::FnDecl,
+) -> String {
+    let inputs = decl
+        .inputs
+        .values
+        .iter()
+        .map(|arg| {
+            if arg.name == "self" {
+                format!("self: {:#}", arg.type_.print())
+            } else {
+                format!("{:#}", arg.type_.print())
+            }
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "{}{}{}{:#}fn {}{}({}{}){:#}",
+        header.constness.print_with_space(),
+        header.asyncness.print_with_space(),
+        header.unsafety.print_with_space(),
+        print_abi_with_space(header.abi),
+        name,
+        generic_params(generics),
+        inputs.join(", "),
+        if decl.c_variadic { ", ..." } else { "" },
+        decl.output.print()
+    )
+}
+
+/// The differences between the APIs of the two versions of the crate.
+#[derive(Default, Serialize)]
+struct Report {
+    breaking: Vec<Change>,
+    non_breaking: Vec<Change>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Serialize)]
+struct Change {
+    kind: ChangeKind,
+    /// The item type of the item, as used in the names of documentation pages.
+    item_type: &'static str,
+    path: String,
+    /// What changed, for changes to an item present in both versions.
+    reason: Option<String>,
+    old: Option<String>,
+    new: Option<String>,
+}
+
+impl Report {
+    fn push(&mut self, breaking: bool, change: Change) {
+        if breaking { self.breaking.push(change) } else { self.non_breaking.push(change) }
+    }
+
+    fn print(&self) {
+        if self.breaking.is_empty() && self.non_breaking.is_empty() {
+            println!("No changes to the public API");
+            return;
+        }
+        for &(title, changes) in
+            &[("Breaking changes", &self.breaking), ("Non-breaking changes", &self.non_breaking)]
+        {
+            if changes.is_empty() {
+                continue;
+            }
+            println!("{} ({}):", title, changes.len());
+            for change in changes {
+                let kind = match change.kind {
+                    ChangeKind::Added => "added",
+                    ChangeKind::Removed => "removed",
+                    ChangeKind::Changed => "changed",
+                };
+                match change.reason {
+                    Some(ref reason) => {
+                        println!("    {} {} {}: {}", kind, change.item_type, change.path, reason)
+                    }
+                    None => println!("    {} {} {}", kind, change.item_type, change.path),
+                }
+                if let Some(ref old) = change.old {
+                    println!("        old: {}", old);
+                }
+                if let Some(ref new) = change.new {
+                    println!("        new: {}", new);
+                }
+            }
+        }
+    }
+}
+
+fn diff(old: &Api, new: &Api) -> Report {
+    let mut report = Report::default();
+    // Items whose parent was added or removed along with them aren't reported on their own.
+    let in_both = |parent: &Option<ItemKey>| {
+        parent.as_ref().map_or(true, |key| old.contains_key(key) && new.contains_key(key))
+    };
+
+    for (key, old_item) in old {
+        let new_item = match new.get(key) {
+            Some(new_item) => new_item,
+            None => {
+                if in_both(&old_item.parent) {
+                    report.push(
+                        true,
+                        Change {
+                            kind: ChangeKind::Removed,
+                            item_type: old_item.ty.as_str(),
+                            path: key.0.clone(),
+                            reason: None,
+                            old: Some(old_item.decl.clone()),
+                            new: None,
+                        },
+                    );
+                }
+                continue;
+            }
+        };
+        let change = |reason: &str, old: Option<&str>, new: Option<&str>| Change {
+            kind: ChangeKind::Changed,
+            item_type: new_item.ty.as_str(),
+            path: key.0.clone(),
+            reason: Some(reason.to_string()),
+            old: old.map(str::to_string),
+            new: new.map(str::to_string),
+        };
+
+        if old_item.decl != new_item.decl {
+            let reason = "the declaration changed";
+            report.push(true, change(reason, Some(&old_item.decl), Some(&new_item.decl)));
+        }
+        for bound in new_item.bounds.difference(&old_item.bounds) {
+            report.push(true, change(&format!("added the bound `{}`", bound), None, None));
+        }
+        for bound in old_item.bounds.difference(&new_item.bounds) {
+            let reason = format!("removed the bound `{}`", bound);
+            report.push(new_item.implementable, change(&reason, None, None));
+        }
+        if old_item.required != new_item.required {
+            let reason = if new_item.required {
+                "the implementations of the trait now have to provide it"
+            } else {
+                "a default implementation is now provided"
+            };
+            report.push(new_item.required, change(reason, None, None));
+        }
+        if old_item.exhaustive != new_item.exhaustive {
+            let reason = if new_item.exhaustive {
+                "it can now be built or matched exhaustively outside of the crate"
+            } else {
+                "it can't be built or matched exhaustively outside of the crate anymore"
+            };
+            report.push(old_item.exhaustive, change(reason, None, None));
+        }
+    }
+
+    for (key, new_item) in new {
+        if old.contains_key(key) || !in_both(&new_item.parent) {
+            continue;
+        }
+        // Adding fields to structs or variants to enums which users can build or match on, and
+        // adding items that implementations of a trait must provide, breaks the users' code.
+        let breaking = new_item.required
+            || match (new_item.ty, new_item.parent.as_ref().and_then(|key| old.get(key))) {
+                (ItemType::StructField, Some(parent)) | (ItemType::Variant, Some(parent)) => {
+                    parent.exhaustive
+                }
+                _ => false,
+            };
+        report.push(
+            breaking,
+            Change {
+                kind: ChangeKind::Added,
+                item_type: new_item.ty.as_str(),
+                path: key.0.clone(),
+                reason: None,
+                old: None,
+                new: Some(new_item.decl.clone()),
+            },
+        );
+    }
+
+    report
+}
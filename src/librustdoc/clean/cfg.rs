@@ -8,6 +8,7 @@ use std::mem;
 use std::ops;
 
 use rustc_ast::ast::{LitKind, MetaItem, MetaItemKind, NestedMetaItem};
+use rustc_data_structures::fx::FxHashSet;
 use rustc_feature::Features;
 use rustc_session::parse::ParseSess;
 use rustc_span::symbol::{sym, Symbol};
@@ -98,6 +99,26 @@ impl Cfg {
         }
     }
 
+    /// Removes the configuration options found in `hidden` from the configuration, such as
+    /// `cfg(doc)` from `cfg(any(unix, doc))`. Returns `None` if nothing is left of it.
+    pub fn strip_hidden(&self, hidden: &FxHashSet<Cfg>) -> Option<Cfg> {
+        match *self {
+            Cfg::Cfg(..) if hidden.contains(self) => None,
+            Cfg::True | Cfg::False | Cfg::Cfg(..) => Some(self.clone()),
+            Cfg::Not(ref child) => child.strip_hidden(hidden).map(|child| !child),
+            Cfg::All(ref sub_cfgs) => {
+                let mut sub_cfgs = sub_cfgs.iter().filter_map(|c| c.strip_hidden(hidden));
+                let first = sub_cfgs.next()?;
+                Some(sub_cfgs.fold(first, |x, y| x & y))
+            }
+            Cfg::Any(ref sub_cfgs) => {
+                let mut sub_cfgs = sub_cfgs.iter().filter_map(|c| c.strip_hidden(hidden));
+                let first = sub_cfgs.next()?;
+                Some(sub_cfgs.fold(first, |x, y| x | y))
+            }
+        }
+    }
+
     /// Checks whether the given configuration can be matched in the current session.
     ///
     /// Equivalent to `attr::cfg_matches`.
@@ -236,6 +236,28 @@ fn test_cfg_or() {
     })
 }
 
+#[test]
+fn test_strip_hidden() {
+    with_default_globals(|| {
+        let hidden: FxHashSet<Cfg> = vec![word_cfg("doc"), word_cfg("test")].into_iter().collect();
+
+        assert_eq!(word_cfg("unix").strip_hidden(&hidden), Some(word_cfg("unix")));
+        assert_eq!(word_cfg("doc").strip_hidden(&hidden), None);
+        assert_eq!((!word_cfg("test")).strip_hidden(&hidden), None);
+        assert_eq!(
+            (word_cfg("unix") | word_cfg("doc")).strip_hidden(&hidden),
+            Some(word_cfg("unix"))
+        );
+        assert_eq!(
+            (word_cfg("unix") & !word_cfg("test") & name_value_cfg("feature", "std"))
+                .strip_hidden(&hidden),
+            Some(word_cfg("unix") & name_value_cfg("feature", "std"))
+        );
+        assert_eq!((word_cfg("doc") | word_cfg("test")).strip_hidden(&hidden), None);
+        assert_eq!(Cfg::True.strip_hidden(&hidden), Some(Cfg::True));
+    })
+}
+
 #[test]
 fn test_parse_ok() {
     with_default_globals(|| {
@@ -16,19 +16,25 @@ use rustc_session::search_paths::SearchPath;
 use rustc_span::edition::{Edition, DEFAULT_EDITION};
 use rustc_target::spec::TargetTriple;
 
+use crate::api_diff::ApiDiffOptions;
 use crate::core::new_handler;
 use crate::externalfiles::ExternalHtml;
 use crate::html;
 use crate::html::markdown::IdMap;
 use crate::html::static_files;
 use crate::opts;
-use crate::passes::{self, Condition, DefaultPassOption};
+use crate::passes::{self, Condition, CoverageThresholds, DefaultPassOption};
+use crate::scrape_examples::{load_call_locations, AllCallLocations, ScrapeExamplesOptions};
 use crate::theme;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum OutputFormat {
     Json,
     Html,
+    /// Plain text files, one per module and item.
+    Text,
+    /// Section 3 man pages, one per module and item.
+    Man,
 }
 
 impl OutputFormat {
@@ -47,6 +53,8 @@ impl TryFrom<&str> for OutputFormat {
         match value {
             "json" => Ok(OutputFormat::Json),
             "html" => Ok(OutputFormat::Html),
+            "text" => Ok(OutputFormat::Text),
+            "man" => Ok(OutputFormat::Man),
             _ => Err(format!("unknown output format `{}`", value)),
         }
     }
@@ -117,6 +125,9 @@ pub struct Options {
     /// The path to a rustc-like binary to build tests with. If not set, we
     /// default to loading from $sysroot/bin/rustc.
     pub test_builder: Option<PathBuf>,
+    /// Whether to compile the doctests that allow it into a single binary, instead of compiling
+    /// each doctest on its own.
+    pub merge_doctests: bool,
 
     // Options that affect the documentation process
     /// The selected default set of passes to use.
@@ -137,6 +148,23 @@ pub struct Options {
     /// Whether to run the `calculate-doc-coverage` pass, which counts the number of public items
     /// with and without documentation.
     pub show_coverage: bool,
+    /// The coverage percentages below which `--show-coverage` fails.
+    pub coverage_thresholds: CoverageThresholds,
+    /// Whether the output of `--show-coverage` includes the code example counts and the counts of
+    /// each module.
+    pub coverage_details: bool,
+    /// If set, the calls the crate makes to the functions of other crates are written to a file
+    /// instead of documenting the crate.
+    pub scrape_examples_options: Option<ScrapeExamplesOptions>,
+    /// Calls to the functions of the crate found in other crates, which are shown as examples of
+    /// how to use those functions.
+    pub call_locations: AllCallLocations,
+    /// If set, the differences between the public API of an older version of the crate and the
+    /// one of the compiled crate given as input are printed instead of documenting the crate.
+    pub api_diff_options: Option<ApiDiffOptions>,
+    /// Whether to only check the intra-doc links of the crate instead of documenting it, with
+    /// the `intra_doc_link_resolution_failure` lint denied by default.
+    pub check_intra_doc_links: bool,
 
     // Options that alter generated documentation pages
     /// Crate version to note on the sidebar of generated docs.
@@ -182,11 +210,18 @@ impl fmt::Debug for Options {
             .field("manual_passes", &self.manual_passes)
             .field("display_warnings", &self.display_warnings)
             .field("show_coverage", &self.show_coverage)
+            .field("coverage_thresholds", &self.coverage_thresholds)
+            .field("coverage_details", &self.coverage_details)
+            .field("scrape_examples_options", &self.scrape_examples_options)
+            .field("call_locations", &"...")
+            .field("api_diff_options", &self.api_diff_options)
+            .field("check_intra_doc_links", &self.check_intra_doc_links)
             .field("crate_version", &self.crate_version)
             .field("render_options", &self.render_options)
             .field("runtool", &self.runtool)
             .field("runtool_args", &self.runtool_args)
             .field("enable-per-target-ignores", &self.enable_per_target_ignores)
+            .field("merge_doctests", &self.merge_doctests)
             .finish()
     }
 }
@@ -250,6 +285,12 @@ pub struct RenderOptions {
     pub generate_search_filter: bool,
     /// Option (disabled by default) to generate files used by RLS and some other tools.
     pub generate_redirect_pages: bool,
+    /// Whether private items are documented, either because `--document-private-items` was
+    /// passed or because the crate asks for it with `#![doc(document_private_items)]`.
+    pub document_private: bool,
+    /// Whether identifiers in the rendered source files link to the definitions of the items
+    /// they refer to.
+    pub generate_link_to_definition: bool,
 }
 
 impl Options {
@@ -473,11 +514,31 @@ impl Options {
             });
 
         let show_coverage = matches.opt_present("show-coverage");
+        let coverage_thresholds = CoverageThresholds::new(matches, &diag)?;
+        let coverage_details = matches.opt_present("coverage-details");
+        if coverage_details && !show_coverage {
+            diag.struct_err("must use --show-coverage with --coverage-details").emit();
+            return Err(1);
+        }
 
-        let default_passes = if matches.opt_present("no-defaults") {
+        let scrape_examples_options = ScrapeExamplesOptions::new(matches, &diag)?;
+        let call_locations = load_call_locations(matches.opt_strs("with-examples"), &diag)?;
+        let api_diff_options = matches
+            .opt_str("api-diff")
+            .map(|old| ApiDiffOptions { old: PathBuf::from(old), new: input.clone() });
+        let check_intra_doc_links = matches.opt_present("check-intra-doc-links");
+
+        // When scraping examples or comparing two versions of a crate, the crate isn't documented,
+        // so there's no need to run passes.
+        let no_defaults = matches.opt_present("no-defaults")
+            || scrape_examples_options.is_some()
+            || api_diff_options.is_some();
+        let default_passes = if no_defaults {
             passes::DefaultPassOption::None
         } else if show_coverage {
             passes::DefaultPassOption::Coverage
+        } else if check_intra_doc_links {
+            passes::DefaultPassOption::IntraDocLinks
         } else {
             passes::DefaultPassOption::Default
         };
@@ -494,14 +555,29 @@ impl Options {
         let output_format = match matches.opt_str("output-format") {
             Some(s) => match OutputFormat::try_from(s.as_str()) {
                 Ok(o) => {
-                    if o.is_json() && !show_coverage {
-                        diag.struct_err("json output format isn't supported for doc generation")
-                            .emit();
+                    if o != OutputFormat::Html
+                        && !(o.is_json() && show_coverage)
+                        && !nightly_options::is_unstable_enabled(&matches)
+                    {
+                        diag.struct_err(&format!(
+                            "the -Z unstable-options flag must be passed to use the {} output \
+                             format",
+                            s
+                        ))
+                        .emit();
                         return Err(1);
                     } else if !o.is_json() && show_coverage {
-                        diag.struct_err(
-                            "html output format isn't supported for the --show-coverage option",
-                        )
+                        diag.struct_err(&format!(
+                            "{} output format isn't supported for the --show-coverage option",
+                            s
+                        ))
+                        .emit();
+                        return Err(1);
+                    } else if !o.is_json() && api_diff_options.is_some() {
+                        diag.struct_err(&format!(
+                            "{} output format isn't supported for the --api-diff option",
+                            s
+                        ))
                         .emit();
                         return Err(1);
                     }
@@ -532,6 +608,7 @@ impl Options {
         let persist_doctests = matches.opt_str("persist-doctests").map(PathBuf::from);
         let generate_redirect_pages = matches.opt_present("generate-redirect-pages");
         let test_builder = matches.opt_str("test-builder").map(PathBuf::from);
+        let merge_doctests = matches.opt_present("merge-doctests");
         let codegen_options_strs = matches.opt_strs("C");
         let debugging_options_strs = matches.opt_strs("Z");
         let lib_strs = matches.opt_strs("L");
@@ -540,6 +617,7 @@ impl Options {
         let runtool_args = matches.opt_strs("runtool-arg");
         let enable_per_target_ignores = matches.opt_present("enable-per-target-ignores");
         let document_private = matches.opt_present("document-private-items");
+        let generate_link_to_definition = matches.opt_present("generate-link-to-definition");
         let document_hidden = matches.opt_present("document-hidden-items");
 
         let (lint_opts, describe_lints, lint_cap) = get_cmd_lint_options(matches, error_format);
@@ -572,12 +650,19 @@ impl Options {
             manual_passes,
             display_warnings,
             show_coverage,
+            coverage_thresholds,
+            coverage_details,
+            scrape_examples_options,
+            call_locations,
+            api_diff_options,
+            check_intra_doc_links,
             crate_version,
             persist_doctests,
             runtool,
             runtool_args,
             enable_per_target_ignores,
             test_builder,
+            merge_doctests,
             render_options: RenderOptions {
                 output,
                 external_html,
@@ -597,6 +682,8 @@ impl Options {
                 markdown_playground_url,
                 generate_search_filter,
                 generate_redirect_pages,
+                document_private,
+                generate_link_to_definition,
             },
             output_format,
         })
@@ -614,7 +701,9 @@ fn check_deprecated_options(matches: &getopts::Matches, diag: &rustc_errors::Han
 
     for flag in deprecated_flags.iter() {
         if matches.opt_present(flag) {
-            if *flag == "output-format" && matches.opt_present("show-coverage") {
+            if *flag == "output-format"
+                && (matches.opt_present("show-coverage") || matches.opt_present("api-diff"))
+            {
                 continue;
             }
             let mut err =
@@ -5,6 +5,7 @@ use rustc_data_structures::sync::{self, Lrc};
 use rustc_driver::abort_on_err;
 use rustc_errors::emitter::{Emitter, EmitterWriter};
 use rustc_errors::json::JsonEmitter;
+use rustc_errors::sarif::SarifEmitter;
 use rustc_feature::UnstableFeatures;
 use rustc_hir::def::Namespace::TypeNS;
 use rustc_hir::def_id::{CrateNum, DefId, DefIndex, LOCAL_CRATE};
@@ -26,11 +27,13 @@ use std::cell::RefCell;
 use std::mem;
 use std::rc::Rc;
 
+use crate::api_diff;
 use crate::clean;
 use crate::clean::{AttributesExt, MAX_DEF_ID};
 use crate::config::{Options as RustdocOptions, RenderOptions};
-use crate::html::render::RenderInfo;
+use crate::html::render::{span_map, RenderInfo};
 use crate::passes::{self, Condition::*, ConditionalPass};
+use crate::scrape_examples;
 
 pub use rustc_session::config::{CodegenOptions, DebuggingOptions, Input, Options};
 pub use rustc_session::search_paths::SearchPath;
@@ -63,6 +66,8 @@ pub struct DocContext<'tcx> {
     // FIXME(eddyb) make this a `ty::TraitRef<'tcx>` set.
     pub generated_synthetics: RefCell<FxHashSet<(Ty<'tcx>, DefId)>>,
     pub auto_traits: Vec<DefId>,
+    /// Whether `--check-intra-doc-links` was passed, in which case broken links are errors.
+    pub check_intra_doc_links: bool,
 }
 
 impl<'tcx> DocContext<'tcx> {
@@ -165,8 +170,8 @@ impl<'tcx> DocContext<'tcx> {
 
 /// Creates a new diagnostic `Handler` that can be used to emit warnings and errors.
 ///
-/// If the given `error_format` is `ErrorOutputType::Json` and no `SourceMap` is given, a new one
-/// will be created for the handler.
+/// If the given `error_format` is `ErrorOutputType::Json` or `ErrorOutputType::Sarif` and no
+/// `SourceMap` is given, a new one will be created for the handler.
 pub fn new_handler(
     error_format: ErrorOutputType,
     source_map: Option<Lrc<source_map::SourceMap>>,
@@ -196,6 +201,12 @@ pub fn new_handler(
                     .ui_testing(debugging_opts.ui_testing),
             )
         }
+        ErrorOutputType::Sarif => {
+            let source_map = source_map.unwrap_or_else(|| {
+                Lrc::new(source_map::SourceMap::new(source_map::FilePathMapping::empty()))
+            });
+            Box::new(SarifEmitter::stderr(None, source_map))
+        }
     };
 
     rustc_errors::Handler::with_emitter_and_flags(
@@ -266,7 +277,7 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
         externs,
         mut cfgs,
         codegen_options,
-        debugging_options,
+        mut debugging_options,
         target,
         edition,
         maybe_sysroot,
@@ -278,11 +289,36 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
         document_hidden,
         mut manual_passes,
         display_warnings,
-        render_options,
+        mut render_options,
         output_format,
+        scrape_examples_options,
+        call_locations,
+        api_diff_options,
+        coverage_thresholds,
+        coverage_details,
+        check_intra_doc_links,
         ..
     } = options;
 
+    // When comparing two versions of a crate, both are loaded as dependencies of an empty crate
+    // instead of compiling the input.
+    let (input, cpath, externs) = match api_diff_options {
+        Some(ref api_diff_options) => {
+            (api_diff_options.input(), None, api_diff_options.externs(externs))
+        }
+        None => (Input::File(input.clone()), Some(input), externs),
+    };
+
+    // The calls to scrape are in the bodies of functions, which are otherwise replaced by loops.
+    if scrape_examples_options.is_some() {
+        debugging_options.keep_fn_bodies = true;
+    }
+    // Linking the identifiers of function bodies needs their resolutions and types too.
+    let generate_link_to_definition = render_options.generate_link_to_definition;
+    if generate_link_to_definition {
+        debugging_options.keep_fn_bodies = true;
+    }
+
     let extern_names: Vec<String> = externs
         .iter()
         .filter(|(_, entry)| entry.add_prelude)
@@ -293,9 +329,6 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
     // Add the doc cfg into the doc build.
     cfgs.push("doc".to_string());
 
-    let cpath = Some(input.clone());
-    let input = Input::File(input);
-
     let intra_link_resolution_failure_name = lint::builtin::INTRA_DOC_LINK_RESOLUTION_FAILURE.name;
     let missing_docs = rustc_lint::builtin::MISSING_DOCS.name;
     let missing_doc_example = rustc_lint::builtin::MISSING_DOC_CODE_EXAMPLES.name;
@@ -402,6 +435,11 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
                 // Abort if there were any errors so far
                 sess.abort_if_errors();
 
+                if let Some(ref scrape_examples_options) = scrape_examples_options {
+                    scrape_examples::run(tcx, scrape_examples_options);
+                    sess.abort_if_errors();
+                }
+
                 let access_levels = tcx.privacy_access_levels(LOCAL_CRATE);
                 // Convert from a HirId set to a DefId set since we don't always have easy access
                 // to the map from defid -> hirid
@@ -416,6 +454,13 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
                 let mut renderinfo = RenderInfo::default();
                 renderinfo.access_levels = access_levels;
                 renderinfo.output_format = output_format;
+                renderinfo.call_locations =
+                    scrape_examples::resolve_call_locations(tcx, call_locations);
+                renderinfo.coverage_thresholds = coverage_thresholds;
+                renderinfo.coverage_details = coverage_details;
+                if generate_link_to_definition {
+                    renderinfo.span_map = span_map::collect(tcx);
+                }
 
                 let mut ctxt = DocContext {
                     tcx,
@@ -436,11 +481,17 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
                         .cloned()
                         .filter(|trait_def_id| tcx.trait_is_auto(*trait_def_id))
                         .collect(),
+                    check_intra_doc_links,
                 };
                 debug!("crate: {:?}", tcx.hir().krate());
 
                 let mut krate = clean::krate(&mut ctxt);
 
+                if api_diff_options.is_some() {
+                    api_diff::run(&ctxt, output_format);
+                    return (krate, ctxt.renderinfo.into_inner(), render_options);
+                }
+
                 if let Some(ref m) = krate.module {
                     if let None | Some("") = m.doc_value() {
                         let help = "The following guide may be of use:\n\
@@ -544,6 +595,7 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
 
                 ctxt.sess().abort_if_errors();
 
+                render_options.document_private = document_private;
                 (krate, ctxt.renderinfo.into_inner(), render_options)
             })
         })
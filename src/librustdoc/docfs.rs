@@ -9,6 +9,7 @@
 //! needs to read-after-write from a file, then it would be added to this
 //! abstraction.
 
+use std::cell::RefCell;
 use std::fs;
 use std::io;
 use std::path::Path;
@@ -29,21 +30,23 @@ pub trait PathError {
 }
 
 pub struct ErrorStorage {
-    sender: Option<Sender<Option<String>>>,
+    sender: RefCell<Option<Sender<Option<String>>>>,
     receiver: Receiver<Option<String>>,
 }
 
 impl ErrorStorage {
     pub fn new() -> ErrorStorage {
         let (sender, receiver) = channel();
-        ErrorStorage { sender: Some(sender), receiver }
+        ErrorStorage { sender: RefCell::new(Some(sender)), receiver }
     }
 
     /// Prints all stored errors. Returns the number of printed errors.
-    pub fn write_errors(&mut self, diag: &rustc_errors::Handler) -> usize {
+    ///
+    /// No more files can be written once this was called.
+    pub fn write_errors(&self, diag: &rustc_errors::Handler) -> usize {
         let mut printed = 0;
         // In order to drop the sender part of the channel.
-        self.sender = None;
+        self.sender.borrow_mut().take();
 
         for msg in self.receiver.iter() {
             if let Some(ref error) = msg {
@@ -87,7 +90,7 @@ impl DocFS {
             // be to create the file sync so errors are reported eagerly.
             let contents = contents.as_ref().to_vec();
             let path = path.as_ref().to_path_buf();
-            let sender = self.errors.sender.clone().unwrap();
+            let sender = self.errors.sender.borrow().clone().unwrap();
             rayon::spawn(move || match fs::write(&path, &contents) {
                 Ok(_) => {
                     sender.send(None).unwrap_or_else(|_| {
@@ -0,0 +1,90 @@
+//! The interface between rustdoc and its output formats.
+//!
+//! An output format implements `FormatRenderer`, and `run_format` walks the crate for it: every
+//! module is announced before and after its items, so that the renderer can keep track of the
+//! path of the items it is given.
+
+use std::sync::Arc;
+
+use rustc_span::edition::Edition;
+
+use crate::clean;
+use crate::config::{OutputFormat, RenderOptions};
+use crate::html::render::{set_cache, Cache, Error, RenderInfo, SearchIndex};
+
+/// A backend that generates the documentation of a crate in some output format.
+crate trait FormatRenderer: Sized {
+    /// Sets up the renderer, once the cache and the search index of the crate have been built.
+    /// Returns the crate whose items are then rendered, which the renderer may change.
+    fn init(
+        krate: clean::Crate,
+        options: RenderOptions,
+        output_format: OutputFormat,
+        edition: Edition,
+        cache: Arc<Cache>,
+        search_index: SearchIndex,
+        diag: &rustc_errors::Handler,
+    ) -> Result<(Self, clean::Crate), Error>;
+
+    /// Renders an item that isn't a module. Implementations were moved into the cache, and
+    /// aren't given to this method.
+    fn item(&mut self, item: clean::Item) -> Result<(), Error>;
+
+    /// Renders a module, before any of its items.
+    fn mod_item_in(&mut self, item: &clean::Item, item_name: &str) -> Result<(), Error>;
+
+    /// Called once all the items of the current module have been rendered.
+    fn mod_item_out(&mut self, item_name: &str) -> Result<(), Error>;
+
+    /// Called once the whole crate has been rendered.
+    fn after_krate(
+        &mut self,
+        krate: &clean::Crate,
+        diag: &rustc_errors::Handler,
+    ) -> Result<(), Error>;
+}
+
+/// Builds the cache of `krate` and renders all of its items with the renderer `T`.
+crate fn run_format<T: FormatRenderer>(
+    krate: clean::Crate,
+    options: RenderOptions,
+    renderinfo: RenderInfo,
+    output_format: OutputFormat,
+    edition: Edition,
+    diag: &rustc_errors::Handler,
+) -> Result<(), Error> {
+    let (krate, search_index, cache) =
+        Cache::from_krate(renderinfo, &options.extern_html_root_urls, &options.output, krate);
+    let cache = Arc::new(cache);
+    // The printers of `html::format` look up the paths of the items in the cache.
+    set_cache(cache.clone());
+
+    let (mut renderer, mut krate) =
+        T::init(krate, options, output_format, edition, cache, search_index, diag)?;
+    let mut item = match krate.module.take() {
+        Some(i) => i,
+        None => return Ok(()),
+    };
+    item.name = Some(krate.name.clone());
+    render_item(&mut renderer, item)?;
+    renderer.after_krate(&krate, diag)
+}
+
+fn render_item<T: FormatRenderer>(renderer: &mut T, item: clean::Item) -> Result<(), Error> {
+    if item.is_mod() {
+        let name = item.name.clone().unwrap();
+        renderer.mod_item_in(&item, &name)?;
+        let module = match item.inner {
+            clean::StrippedItem(box clean::ModuleItem(m)) | clean::ModuleItem(m) => m,
+            _ => unreachable!(),
+        };
+        for it in module.items {
+            render_item(renderer, it)?;
+        }
+        renderer.mod_item_out(&name)
+    } else if item.name.is_some() {
+        renderer.item(item)
+    } else {
+        Ok(())
+    }
+}
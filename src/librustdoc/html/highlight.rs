@@ -10,13 +10,14 @@ use crate::html::escape::Escape;
 use std::fmt::Display;
 use std::io;
 use std::io::prelude::*;
+use std::ops::Range;
 
 use rustc_ast::token::{self, Token};
 use rustc_parse::lexer;
 use rustc_session::parse::ParseSess;
 use rustc_span::source_map::SourceMap;
 use rustc_span::symbol::{kw, sym};
-use rustc_span::{FileName, Span};
+use rustc_span::{BytePos, FileName, Span};
 
 /// Highlights `src`, returning the HTML output.
 pub fn render_with_highlighting(
@@ -24,6 +25,18 @@ pub fn render_with_highlighting(
     class: Option<&str>,
     playground_button: Option<&str>,
     tooltip: Option<(&str, &str)>,
+) -> String {
+    render_with_links(src, class, playground_button, tooltip, &[])
+}
+
+/// Highlights `src` like `render_with_highlighting`, and turns the tokens found at the given byte
+/// ranges of `src` into links to the given URLs. The ranges must be sorted.
+pub fn render_with_links(
+    src: &str,
+    class: Option<&str>,
+    playground_button: Option<&str>,
+    tooltip: Option<(&str, &str)>,
+    links: &[(Range<u32>, String)],
 ) -> String {
     debug!("highlighting: ================\n{}\n==============", src);
     let mut out = Vec::new();
@@ -42,8 +55,9 @@ pub fn render_with_highlighting(
         .source_map()
         .new_source_file(FileName::Custom(String::from("rustdoc-highlighting")), src.to_owned());
     let highlight_result = rustc_driver::catch_fatal_errors(|| {
+        let start_pos = sf.start_pos;
         let lexer = lexer::StringReader::new(&sess, sf, None);
-        let mut classifier = Classifier::new(lexer, sess.source_map());
+        let mut classifier = Classifier::new(lexer, sess.source_map(), start_pos, links);
 
         let mut highlighted_source = vec![];
         if classifier.write_source(&mut highlighted_source).is_err() {
@@ -77,6 +91,10 @@ struct Classifier<'a> {
     lexer: lexer::StringReader<'a>,
     peek_token: Option<Token>,
     source_map: &'a SourceMap,
+    /// Position of the start of the highlighted source in `source_map`.
+    start_pos: BytePos,
+    /// Byte ranges of the highlighted source to link, and the URLs to link them to.
+    links: &'a [(Range<u32>, String)],
 
     // State of the classifier.
     in_attribute: bool,
@@ -133,6 +151,12 @@ trait Writer {
     /// The latter can be thought of as a shorthand for the former, which is
     /// more flexible.
     fn string<T: Display>(&mut self, text: T, klass: Class) -> io::Result<()>;
+
+    /// Called when we start processing a span of text that links to `href`.
+    fn enter_link(&mut self, href: &str) -> io::Result<()>;
+
+    /// Called at the end of a span of text that links somewhere.
+    fn exit_link(&mut self) -> io::Result<()>;
 }
 
 // Implement `Writer` for anything that can be written to, this just implements
@@ -152,6 +176,14 @@ impl<U: Write> Writer for U {
     fn exit_span(&mut self) -> io::Result<()> {
         write!(self, "</span>")
     }
+
+    fn enter_link(&mut self, href: &str) -> io::Result<()> {
+        write!(self, "<a href=\"{}\">", Escape(href))
+    }
+
+    fn exit_link(&mut self) -> io::Result<()> {
+        write!(self, "</a>")
+    }
 }
 
 enum HighlightError {
@@ -166,11 +198,18 @@ impl From<io::Error> for HighlightError {
 }
 
 impl<'a> Classifier<'a> {
-    fn new(lexer: lexer::StringReader<'a>, source_map: &'a SourceMap) -> Classifier<'a> {
+    fn new(
+        lexer: lexer::StringReader<'a>,
+        source_map: &'a SourceMap,
+        start_pos: BytePos,
+        links: &'a [(Range<u32>, String)],
+    ) -> Classifier<'a> {
         Classifier {
             lexer,
             peek_token: None,
             source_map,
+            start_pos,
+            links,
             in_attribute: false,
             in_macro: false,
             in_macro_nonterminal: false,
@@ -385,11 +424,27 @@ impl<'a> Classifier<'a> {
 
         // Anything that didn't return above is the simple case where we the
         // class just spans a single token, so we can use the `string` method.
-        out.string(Escape(&self.snip(token.span)), klass)?;
+        match self.link(token.span) {
+            Some(href) => {
+                out.enter_link(href)?;
+                out.string(Escape(&self.snip(token.span)), klass)?;
+                out.exit_link()?;
+            }
+            None => out.string(Escape(&self.snip(token.span)), klass)?,
+        }
 
         Ok(())
     }
 
+    /// Returns the URL the token at `sp` links to, if any.
+    fn link(&self, sp: Span) -> Option<&'a str> {
+        let lo = (sp.lo() - self.start_pos).0;
+        let hi = (sp.hi() - self.start_pos).0;
+        let i = self.links.binary_search_by_key(&lo, |(range, _)| range.start).ok()?;
+        let (range, href) = &self.links[i];
+        if range.end == hi { Some(href.as_str()) } else { None }
+    }
+
     // Helper function to get a snippet from the source_map.
     fn snip(&self, sp: Span) -> String {
         self.source_map.span_to_snippet(sp).unwrap()
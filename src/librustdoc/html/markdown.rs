@@ -31,6 +31,7 @@ use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::default::Default;
 use std::fmt::Write;
+use std::mem;
 use std::ops::Range;
 use std::str;
 
@@ -79,6 +80,27 @@ pub struct MarkdownHtml<'a>(
 );
 /// A tuple struct like `Markdown` that renders only the first paragraph.
 pub struct MarkdownSummaryLine<'a>(pub &'a str, pub &'a [(String, String)]);
+/// When `into_blocks` is called, this struct converts the contained markdown string to plain text,
+/// for the output formats other than HTML.
+pub struct MarkdownText<'a>(
+    pub &'a str,
+    /// A list of link replacements.
+    pub &'a [(String, String)],
+);
+
+/// A block of documentation converted to plain text by `MarkdownText`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TextBlock {
+    /// A heading, with its level.
+    Heading(u32, String),
+    Paragraph(String),
+    /// A code block or a table. The hidden lines of Rust code blocks are left out.
+    Code(String),
+    /// An item of a list or a footnote, with its marker (such as `-`, `1.` or `[^1]:`).
+    Item(String, Vec<TextBlock>),
+    Quote(Vec<TextBlock>),
+    Rule,
+}
 
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum ErrorCodes {
@@ -681,6 +703,14 @@ pub struct LangString {
     pub error_codes: Vec<String>,
     pub allow_fail: bool,
     pub edition: Option<Edition>,
+    /// The message the output of the doctest must contain when it panics, given by
+    /// `should_panic = "message"`.
+    pub should_panic_message: Option<String>,
+    /// The environment variables the doctest is compiled and run with, given by
+    /// `env(KEY=VALUE, ...)`.
+    pub env: Vec<(String, String)>,
+    /// The `--cfg` options the doctest is compiled with, given by `cfg(...)`.
+    pub cfgs: Vec<String>,
 }
 
 #[derive(Eq, PartialEq, Clone, Debug)]
@@ -703,6 +733,9 @@ impl LangString {
             error_codes: Vec::new(),
             allow_fail: false,
             edition: None,
+            should_panic_message: None,
+            env: Vec::new(),
+            cfgs: Vec::new(),
         }
     }
 
@@ -727,15 +760,43 @@ impl LangString {
         let mut ignores = vec![];
 
         data.original = string.to_owned();
-        let tokens = string.split(|c: char| !(c == '_' || c == '-' || c.is_alphanumeric()));
 
-        for token in tokens {
+        for token in tokens(string) {
             match token.trim() {
                 "" => {}
                 "should_panic" => {
                     data.should_panic = true;
                     seen_rust_tags = !seen_other_tags;
                 }
+                x if attr_value(x, "should_panic").is_some() => {
+                    data.should_panic = true;
+                    data.should_panic_message = attr_value(x, "should_panic");
+                    seen_rust_tags = !seen_other_tags;
+                }
+                x if attr_list(x, "env").is_some() => {
+                    for var in attr_list(x, "env").unwrap() {
+                        match var.find('=') {
+                            Some(eq) => {
+                                let value = unquote(var[eq + 1..].trim());
+                                data.env.push((var[..eq].trim().to_owned(), value));
+                            }
+                            None => {
+                                if let Some(ref extra) = extra {
+                                    extra.error_invalid_codeblock_attr(
+                                        &format!("invalid environment variable `{}`", var),
+                                        "environment variables are written as \
+                                         `env(KEY=VALUE)`",
+                                    );
+                                }
+                            }
+                        }
+                    }
+                    seen_rust_tags = !seen_other_tags;
+                }
+                x if attr_list(x, "cfg").is_some() => {
+                    data.cfgs.extend(attr_list(x, "cfg").unwrap().into_iter().map(str::to_owned));
+                    seen_rust_tags = !seen_other_tags;
+                }
                 "no_run" => {
                     data.no_run = true;
                     seen_rust_tags = !seen_other_tags;
@@ -839,6 +900,124 @@ impl LangString {
     }
 }
 
+/// Splits the attributes of a code block, such as `rust,should_panic = "boom",env(A=1)`. An
+/// attribute is a word that can be followed by a value, which is part of the same token: for
+/// `env` and `cfg`, right after the word between parentheses, and for any word, after `=` between
+/// double quotes.
+fn tokens(string: &str) -> Vec<&str> {
+    let is_word = |c: char| c == '_' || c == '-' || c.is_alphanumeric();
+    let mut tokens = Vec::new();
+    let mut rest = string;
+    while let Some(start) = rest.find(is_word) {
+        rest = &rest[start..];
+        let word_len = rest.find(|c: char| !is_word(c)).unwrap_or(rest.len());
+        let after = rest[word_len..].trim_start();
+        // Comments like `ignore (not real code)` or `ignore(not real code)` are common, so
+        // parentheses are only a value when they follow `env` or `cfg` directly.
+        let takes_list = matches!(&rest[..word_len], "env" | "cfg");
+        let value = if takes_list && rest[word_len..].starts_with('(') {
+            Some(&rest[word_len..])
+        } else if after.starts_with('=') && after[1..].trim_start().starts_with('"') {
+            Some(after[1..].trim_start())
+        } else {
+            None
+        };
+        let len = match value {
+            Some(value) => rest.len() - value.len() + delimited_len(value),
+            None => word_len,
+        };
+        tokens.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+    tokens
+}
+
+/// Returns the length of the value between parentheses or double quotes `s` starts with, or the
+/// length of `s` if the value isn't closed. Parentheses can be nested and contain quoted strings,
+/// in which `\` escapes the next character.
+fn delimited_len(s: &str) -> usize {
+    let quoted = s.starts_with('"');
+    let mut in_string = quoted;
+    let mut escaped = false;
+    let mut depth = 0;
+    for (i, c) in s.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' if quoted => return i + 1,
+            '"' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => {
+                if depth == 0 {
+                    return i + 1;
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+/// Returns the value of an attribute written `name = "value"`.
+fn attr_value(token: &str, name: &str) -> Option<String> {
+    if !token.starts_with(name) {
+        return None;
+    }
+    let value = token[name.len()..].trim_start();
+    if value.starts_with('=') { Some(unquote(value[1..].trim_start())) } else { None }
+}
+
+/// Returns the comma-separated values of an attribute written `name(a, b)`.
+fn attr_list<'a>(token: &'a str, name: &str) -> Option<Vec<&'a str>> {
+    if !token.starts_with(name) {
+        return None;
+    }
+    let list = &token[name.len()..];
+    if !list.starts_with('(') || !list.ends_with(')') || list.len() < 2 {
+        return None;
+    }
+    let mut list = &list[1..list.len() - 1];
+    let mut values = Vec::new();
+    while !list.is_empty() {
+        // Values are split at the commas that aren't in a string or in parentheses.
+        let mut len = 0;
+        for (i, c) in list.char_indices() {
+            if i < len {
+                continue;
+            }
+            match c {
+                ',' => break,
+                '"' | '(' => len = i + delimited_len(&list[i..]),
+                _ => len = i + c.len_utf8(),
+            }
+        }
+        let value = list[..len].trim();
+        if !value.is_empty() {
+            values.push(value);
+        }
+        list = list[len..].trim_start_matches(',');
+    }
+    Some(values)
+}
+
+/// Removes the double quotes around `s`, if there are any, and the escapes of the characters
+/// between them.
+fn unquote(s: &str) -> String {
+    if s.len() < 2 || !s.starts_with('"') || !s.ends_with('"') {
+        return s.to_owned();
+    }
+    let mut unquoted = String::new();
+    let mut chars = s[1..s.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.extend(chars.next()),
+            c => unquoted.push(c),
+        }
+    }
+    unquoted
+}
+
 impl Markdown<'_> {
     pub fn to_string(self) -> String {
         let Markdown(md, links, mut ids, codes, edition, playground) = self;
@@ -947,6 +1126,173 @@ impl MarkdownSummaryLine<'_> {
     }
 }
 
+impl MarkdownText<'_> {
+    pub fn into_blocks(self) -> Vec<TextBlock> {
+        let MarkdownText(md, links) = self;
+
+        // This is actually common enough to special-case
+        if md.is_empty() {
+            return Vec::new();
+        }
+
+        let replacer = |_: &str, s: &str| {
+            if let Some(&(_, ref replace)) = links.iter().find(|link| &*link.0 == s) {
+                Some((replace.clone(), s.to_owned()))
+            } else {
+                None
+            }
+        };
+
+        let mut builder = TextBlocks::default();
+        for event in Parser::new_with_broken_link_callback(md, opts(), Some(&replacer)) {
+            builder.push(event);
+        }
+        builder.finish()
+    }
+}
+
+/// Builds the `TextBlock`s of a markdown string from its events.
+#[derive(Default)]
+struct TextBlocks {
+    /// The blocks that contain the current event, with the marker of the list items and
+    /// footnotes among them. The outermost one is the whole document.
+    containers: Vec<(Option<String>, Vec<TextBlock>)>,
+    /// The text of the current paragraph, heading or table.
+    text: String,
+    /// The next number of each list the current event is in, or `None` for unordered lists.
+    lists: Vec<Option<u64>>,
+    /// The code block the current event is in, and whether it contains Rust code.
+    code: Option<(String, bool)>,
+}
+
+impl TextBlocks {
+    fn blocks(&mut self) -> &mut Vec<TextBlock> {
+        if self.containers.is_empty() {
+            self.containers.push((None, Vec::new()));
+        }
+        &mut self.containers.last_mut().unwrap().1
+    }
+
+    fn flush(&mut self) {
+        let text = mem::take(&mut self.text);
+        let text = text.trim();
+        if !text.is_empty() {
+            self.blocks().push(TextBlock::Paragraph(text.to_owned()));
+        }
+    }
+
+    fn push(&mut self, event: Event<'_>) {
+        if let Some((ref mut code, is_rust)) = self.code {
+            match event {
+                Event::Text(text) => code.push_str(&text),
+                Event::End(Tag::CodeBlock(_)) => {
+                    let code = if is_rust {
+                        let lines = code.lines().filter_map(|l| map_line(l).for_html());
+                        lines.collect::<Vec<Cow<'_, str>>>().join("\n")
+                    } else {
+                        code.trim_end().to_owned()
+                    };
+                    self.code = None;
+                    self.blocks().push(TextBlock::Code(code));
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                self.flush();
+                let is_rust = match kind {
+                    CodeBlockKind::Fenced(ref lang) => {
+                        LangString::parse_without_check(&lang, ErrorCodes::No, false).rust
+                    }
+                    CodeBlockKind::Indented => true,
+                };
+                self.code = Some((String::new(), is_rust));
+            }
+            Event::End(Tag::Paragraph) => self.flush(),
+            Event::End(Tag::Heading(level)) => {
+                let text = mem::take(&mut self.text);
+                self.blocks().push(TextBlock::Heading(level, text.trim().to_owned()));
+            }
+            Event::Start(Tag::BlockQuote) => {
+                self.flush();
+                self.containers.push((None, Vec::new()));
+            }
+            Event::Start(Tag::List(start)) => {
+                self.flush();
+                self.lists.push(start);
+            }
+            Event::End(Tag::List(_)) => {
+                self.lists.pop();
+            }
+            Event::Start(Tag::Item) => {
+                self.flush();
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}.", *number - 1)
+                    }
+                    _ => "-".to_owned(),
+                };
+                self.containers.push((Some(marker), Vec::new()));
+            }
+            Event::Start(Tag::FootnoteDefinition(name)) => {
+                self.flush();
+                self.containers.push((Some(format!("[^{}]:", name)), Vec::new()));
+            }
+            Event::End(Tag::BlockQuote | Tag::Item | Tag::FootnoteDefinition(_)) => {
+                self.flush();
+                if let Some((marker, blocks)) = self.containers.pop() {
+                    let block = match marker {
+                        Some(marker) => TextBlock::Item(marker, blocks),
+                        None => TextBlock::Quote(blocks),
+                    };
+                    self.blocks().push(block);
+                }
+            }
+            Event::Start(Tag::TableCell) if !self.text.ends_with('\n') && !self.text.is_empty() => {
+                self.text.push_str(" | ");
+            }
+            Event::End(Tag::TableHead | Tag::TableRow) => self.text.push('\n'),
+            Event::End(Tag::Table(_)) => {
+                let table = mem::take(&mut self.text);
+                self.blocks().push(TextBlock::Code(table.trim_end().to_owned()));
+            }
+            // Links to other websites are kept, since the reader can't follow them otherwise.
+            Event::End(Tag::Link(_, ref url, _))
+                if url.starts_with("http://") || url.starts_with("https://") =>
+            {
+                write!(self.text, " <{}>", url).unwrap();
+            }
+            Event::Text(text) | Event::Html(text) => self.text.push_str(&text),
+            Event::Code(code) => write!(self.text, "`{}`", code).unwrap(),
+            Event::SoftBreak => self.text.push(' '),
+            Event::HardBreak => self.text.push('\n'),
+            Event::FootnoteReference(name) => write!(self.text, "[^{}]", name).unwrap(),
+            Event::TaskListMarker(checked) => {
+                self.text.push_str(if checked { "[x] " } else { "[ ] " })
+            }
+            Event::Rule => {
+                self.flush();
+                self.blocks().push(TextBlock::Rule);
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(mut self) -> Vec<TextBlock> {
+        self.flush();
+        // Unclosed containers can't happen with well-formed events, but their blocks are kept.
+        let mut blocks = Vec::new();
+        for (_, container) in self.containers.drain(..) {
+            blocks.extend(container);
+        }
+        blocks
+    }
+}
+
 pub fn plain_summary_line(md: &str) -> String {
     struct ParserWrapper<'a> {
         inner: Parser<'a>,
@@ -1,5 +1,6 @@
 use super::plain_summary_line;
 use super::{ErrorCodes, IdMap, Ignore, LangString, Markdown, MarkdownHtml};
+use super::{MarkdownText, TextBlock};
 use rustc_span::edition::{Edition, DEFAULT_EDITION};
 use std::cell::RefCell;
 
@@ -76,6 +77,9 @@ fn test_lang_string_parse() {
                 original: s.to_owned(),
                 allow_fail,
                 edition,
+                should_panic_message: None,
+                env: Vec::new(),
+                cfgs: Vec::new(),
             }
         )
     }
@@ -92,6 +96,8 @@ fn test_lang_string_parse() {
     t("sh", false, false, Ignore::None, false, false, false, false, v(), None);
     t("ignore", false, false, Ignore::All, true, false, false, false, v(), None);
     t("ignore-foo", false, false, ignore_foo, true, false, false, false, v(), None);
+    t("ignore(not real code)", false, false, Ignore::All, true, false, false, false, v(), None);
+    t("rust,ignore(reason)", false, false, Ignore::All, true, false, false, false, v(), None);
     t("should_panic", true, false, Ignore::None, true, false, false, false, v(), None);
     t("no_run", false, true, Ignore::None, true, false, false, false, v(), None);
     t("test_harness", false, false, Ignore::None, true, true, false, false, v(), None);
@@ -129,6 +135,27 @@ fn test_lang_string_parse() {
     );
 }
 
+#[test]
+fn test_lang_string_parse_values() {
+    let parse = |s| LangString::parse(s, ErrorCodes::Yes, true, None);
+
+    let data = parse(r#"should_panic = "out of bounds", env(A=1, B="x, y"), cfg(feature = "z")"#);
+    assert!(data.should_panic && data.rust);
+    assert_eq!(data.should_panic_message.as_deref(), Some("out of bounds"));
+    let env = vec![("A".to_owned(), "1".to_owned()), ("B".to_owned(), "x, y".to_owned())];
+    assert_eq!(data.env, env);
+    assert_eq!(data.cfgs, vec![r#"feature = "z""#.to_owned()]);
+
+    let data = parse(r#"should_panic="say \"hi\"",cfg(foo,bar)"#);
+    assert_eq!(data.should_panic_message.as_deref(), Some(r#"say "hi""#));
+    assert_eq!(data.cfgs, vec!["foo".to_owned(), "bar".to_owned()]);
+
+    // Parentheses after a space are a comment, not a value.
+    let data = parse("ignore (see cfg (foo))");
+    assert_eq!(data.ignore, Ignore::All);
+    assert!(data.cfgs.is_empty());
+}
+
 #[test]
 fn test_header() {
     fn t(input: &str, expect: &str) {
@@ -223,6 +250,35 @@ fn test_plain_summary_line() {
     t("## header", "header");
 }
 
+#[test]
+fn test_markdown_text() {
+    fn t(input: &str, expect: Vec<TextBlock>) {
+        let links = vec![("Vec".to_owned(), "../alloc/vec/struct.Vec.html".to_owned())];
+        let output = MarkdownText(input, &links).into_blocks();
+        assert_eq!(output, expect, "original: {}", input);
+    }
+
+    use TextBlock::*;
+    t("hello *world*\nagain", vec![Paragraph("hello world again".to_owned())]);
+    t("# Examples", vec![Heading(1, "Examples".to_owned())]);
+    t(
+        "see [Vec] and [Rust](https://www.rust-lang.org)",
+        vec![Paragraph("see Vec and Rust <https://www.rust-lang.org>".to_owned())],
+    );
+    t("```\n# fn hidden() {}\nshown();\n```", vec![Code("shown();".to_owned())]);
+    t("```text\n# not hidden\n```", vec![Code("# not hidden".to_owned())]);
+    t(
+        "- one\n- two\n\n1. first",
+        vec![
+            Item("-".to_owned(), vec![Paragraph("one".to_owned())]),
+            Item("-".to_owned(), vec![Paragraph("two".to_owned())]),
+            Item("1.".to_owned(), vec![Paragraph("first".to_owned())]),
+        ],
+    );
+    t("> quoted", vec![Quote(vec![Paragraph("quoted".to_owned())])]);
+    t("a | b\n--|--\n1 | 2", vec![Code("a | b\n1 | 2".to_owned())]);
+}
+
 #[test]
 fn test_markdown_html_escape() {
     fn t(input: &str, expect: &str) {
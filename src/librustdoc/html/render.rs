@@ -37,6 +37,7 @@ use std::fmt::{self, Formatter, Write};
 use std::fs::{self, File};
 use std::io::prelude::*;
 use std::io::{self, BufReader};
+use std::mem;
 use std::path::{Component, Path, PathBuf};
 use std::rc::Rc;
 use std::str;
@@ -62,6 +63,7 @@ use crate::clean::{self, AttributesExt, Deprecation, GetDefId, SelfTy, TypeKind}
 use crate::config::{OutputFormat, RenderOptions};
 use crate::docfs::{DocFS, ErrorStorage, PathError};
 use crate::doctree;
+use crate::formats::FormatRenderer;
 use crate::html::escape::Escape;
 use crate::html::format::fmt_impl_for_trait_page;
 use crate::html::format::Function;
@@ -71,14 +73,18 @@ use crate::html::item_type::ItemType;
 use crate::html::markdown::{self, ErrorCodes, IdMap, Markdown, MarkdownHtml, MarkdownSummaryLine};
 use crate::html::sources;
 use crate::html::{highlight, layout, static_files};
+use crate::passes::CoverageThresholds;
+use crate::scrape_examples::{FnCallLocations, SyntaxRange};
 
 #[cfg(test)]
 mod tests;
 
 mod cache;
+crate mod span_map;
 
-use cache::Cache;
 crate use cache::ExternalLocation::{self, *};
+crate use cache::{Cache, SearchIndex};
+crate use span_map::{LinkFromSrc, SpanMap};
 
 /// A pair of name and its optional document.
 pub type NameDoc = (String, Option<String>);
@@ -139,11 +145,8 @@ macro_rules! try_err {
 /// about where in the tree-like hierarchy rendering is occurring and controls
 /// how the current page is being rendered.
 ///
-/// It is intended that this context is a lightweight object which can be fairly
-/// easily cloned because it is cloned per work-job (about once per item in the
-/// rustdoc tree).
-#[derive(Clone)]
-struct Context {
+/// This is the HTML output format: `formats::run_format` walks the crate for it.
+crate struct Context {
     /// Current hierarchy of components leading down to what's currently being
     /// rendered
     pub current: Vec<String>,
@@ -154,10 +157,17 @@ struct Context {
     /// real location of an item. This is used to allow external links to
     /// publicly reused items to redirect to the right location.
     pub render_redirect_pages: bool,
+    /// How many of the modules containing the current one were stripped from the
+    /// documentation. Their items only get redirect pages.
+    stripped_depth: usize,
     /// The map used to ensure all generated 'id=' attributes are unique.
     id_map: Rc<RefCell<IdMap>>,
     pub shared: Arc<SharedContext>,
     pub cache: Arc<Cache>,
+    /// The items listed in `all.html`, which is written once the whole crate was rendered.
+    all: AllTypes,
+    /// The errors of the writes done by `shared.fs`, which are reported at the end.
+    errors: Arc<ErrorStorage>,
 }
 
 crate struct SharedContext {
@@ -246,7 +256,7 @@ pub struct Impl {
 }
 
 impl Impl {
-    fn inner_impl(&self) -> &clean::Impl {
+    crate fn inner_impl(&self) -> &clean::Impl {
         match self.impl_item.inner {
             clean::ImplItem(ref impl_) => impl_,
             _ => panic!("non-impl item found in impl"),
@@ -270,6 +280,10 @@ pub struct RenderInfo {
     pub deref_mut_trait_did: Option<DefId>,
     pub owned_box_did: Option<DefId>,
     pub output_format: Option<OutputFormat>,
+    pub call_locations: FxHashMap<DefId, FnCallLocations>,
+    pub coverage_thresholds: CoverageThresholds,
+    pub coverage_details: bool,
+    pub span_map: SpanMap,
 }
 
 // Helper structs for rendering items/sidebars and carrying along contextual
@@ -285,7 +299,13 @@ struct IndexItem {
     desc: String,
     parent: Option<DefId>,
     parent_idx: Option<usize>,
+    /// The positions of the path, the type and the signature of the item in the tables of the
+    /// search index, which are set when it is built.
+    path_idx: Option<usize>,
+    type_idx: Option<usize>,
+    signature_idx: Option<usize>,
     search_type: Option<IndexItemFunctionType>,
+    search_signature: Option<FunctionSignature>,
 }
 
 impl Serialize for IndexItem {
@@ -300,8 +320,14 @@ impl Serialize for IndexItem {
             self.name
         );
 
-        (self.ty, &self.name, &self.path, &self.desc, self.parent_idx, &self.search_type)
-            .serialize(serializer)
+        let Self { ty, ref name, ref desc, parent_idx, type_idx, .. } = *self;
+        let path_idx = self.path_idx.expect("the index of the path wasn't set");
+        // The signature is left out for the items that aren't functions, to keep the index small.
+        match self.signature_idx {
+            Some(signature_idx) => (ty, name, path_idx, desc, parent_idx, type_idx, signature_idx)
+                .serialize(serializer),
+            None => (ty, name, path_idx, desc, parent_idx, type_idx).serialize(serializer),
+        }
     }
 }
 
@@ -391,6 +417,60 @@ impl Serialize for IndexItemFunctionType {
     }
 }
 
+/// A type of the signature of a function, used to search functions by type signature.
+#[derive(Debug)]
+enum SignatureType {
+    /// A type parameter, numbered by order of appearance in the signature.
+    Generic(usize),
+    /// A type and its type arguments. References, raw pointers, slices, arrays and tuples are
+    /// named `&`, `&mut`, `*const`, `*mut`, `[]` and `()`.
+    Named(String, Vec<SignatureType>),
+    /// A type that can't be searched for, which matches any type.
+    Unknown,
+}
+
+impl Serialize for SignatureType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            SignatureType::Generic(index) => serializer.serialize_u64(index as u64),
+            SignatureType::Named(ref name, ref args) => {
+                let mut seq = serializer.serialize_seq(None)?;
+                seq.serialize_element(name)?;
+                if !args.is_empty() {
+                    seq.serialize_element(args)?;
+                }
+                seq.end()
+            }
+            SignatureType::Unknown => serializer.serialize_none(),
+        }
+    }
+}
+
+/// Full signature of functions/methods in the search index, including the `self` argument.
+#[derive(Debug)]
+struct FunctionSignature {
+    inputs: Vec<SignatureType>,
+    /// `None` if the function doesn't return anything.
+    output: Option<SignatureType>,
+}
+
+impl Serialize for FunctionSignature {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(None)?;
+        seq.serialize_element(&self.inputs)?;
+        if let Some(ref output) = self.output {
+            seq.serialize_element(output)?;
+        }
+        seq.end()
+    }
+}
+
 #[derive(Debug)]
 pub struct TypeWithKind {
     ty: RenderType,
@@ -443,144 +523,275 @@ pub fn initial_ids() -> Vec<String> {
     .collect()
 }
 
-/// Generates the documentation for `crate` into the directory `dst`
-pub fn run(
-    mut krate: clean::Crate,
-    options: RenderOptions,
-    renderinfo: RenderInfo,
-    diag: &rustc_errors::Handler,
-    edition: Edition,
-) -> Result<(), Error> {
-    // need to save a copy of the options for rendering the index page
-    let md_opts = options.clone();
-    let RenderOptions {
-        output,
-        external_html,
-        id_map,
-        playground_url,
-        sort_modules_alphabetically,
-        themes,
-        extension_css,
-        extern_html_root_urls,
-        resource_suffix,
-        static_root_path,
-        generate_search_filter,
-        generate_redirect_pages,
-        ..
-    } = options;
-
-    let src_root = match krate.src {
-        FileName::Real(ref p) => match p.parent() {
-            Some(p) => p.to_path_buf(),
-            None => PathBuf::new(),
-        },
-        _ => PathBuf::new(),
-    };
-    let mut errors = Arc::new(ErrorStorage::new());
-    // If user passed in `--playground-url` arg, we fill in crate name here
-    let mut playground = None;
-    if let Some(url) = playground_url {
-        playground = Some(markdown::Playground { crate_name: Some(krate.name.clone()), url });
-    }
-    let mut layout = layout::Layout {
-        logo: String::new(),
-        favicon: String::new(),
-        external_html,
-        krate: krate.name.clone(),
-        css_file_extension: extension_css,
-        generate_search_filter,
-    };
-    let mut issue_tracker_base_url = None;
-    let mut include_sources = true;
-
-    // Crawl the crate attributes looking for attributes which control how we're
-    // going to emit HTML
-    if let Some(attrs) = krate.module.as_ref().map(|m| &m.attrs) {
-        for attr in attrs.lists(sym::doc) {
-            match (attr.name_or_empty(), attr.value_str()) {
-                (sym::html_favicon_url, Some(s)) => {
-                    layout.favicon = s.to_string();
-                }
-                (sym::html_logo_url, Some(s)) => {
-                    layout.logo = s.to_string();
-                }
-                (sym::html_playground_url, Some(s)) => {
-                    playground = Some(markdown::Playground {
-                        crate_name: Some(krate.name.clone()),
-                        url: s.to_string(),
-                    });
-                }
-                (sym::issue_tracker_base_url, Some(s)) => {
-                    issue_tracker_base_url = Some(s.to_string());
-                }
-                (sym::html_no_source, None) if attr.is_word() => {
-                    include_sources = false;
+impl FormatRenderer for Context {
+    fn init(
+        krate: clean::Crate,
+        options: RenderOptions,
+        _output_format: OutputFormat,
+        edition: Edition,
+        cache: Arc<Cache>,
+        search_index: SearchIndex,
+        diag: &rustc_errors::Handler,
+    ) -> Result<(Context, clean::Crate), Error> {
+        // need to save a copy of the options for rendering the index page
+        let md_opts = options.clone();
+        let RenderOptions {
+            output,
+            external_html,
+            id_map,
+            playground_url,
+            sort_modules_alphabetically,
+            themes,
+            extension_css,
+            resource_suffix,
+            static_root_path,
+            generate_search_filter,
+            generate_redirect_pages,
+            ..
+        } = options;
+
+        let src_root = match krate.src {
+            FileName::Real(ref p) => match p.parent() {
+                Some(p) => p.to_path_buf(),
+                None => PathBuf::new(),
+            },
+            _ => PathBuf::new(),
+        };
+        let errors = Arc::new(ErrorStorage::new());
+        // If user passed in `--playground-url` arg, we fill in crate name here
+        let mut playground = None;
+        if let Some(url) = playground_url {
+            playground = Some(markdown::Playground { crate_name: Some(krate.name.clone()), url });
+        }
+        let mut layout = layout::Layout {
+            logo: String::new(),
+            favicon: String::new(),
+            external_html,
+            krate: krate.name.clone(),
+            css_file_extension: extension_css,
+            generate_search_filter,
+        };
+        let mut issue_tracker_base_url = None;
+        let mut include_sources = true;
+
+        // Crawl the crate attributes looking for attributes which control how we're
+        // going to emit HTML
+        if let Some(attrs) = krate.module.as_ref().map(|m| &m.attrs) {
+            for attr in attrs.lists(sym::doc) {
+                match (attr.name_or_empty(), attr.value_str()) {
+                    (sym::html_favicon_url, Some(s)) => {
+                        layout.favicon = s.to_string();
+                    }
+                    (sym::html_logo_url, Some(s)) => {
+                        layout.logo = s.to_string();
+                    }
+                    (sym::html_playground_url, Some(s)) => {
+                        playground = Some(markdown::Playground {
+                            crate_name: Some(krate.name.clone()),
+                            url: s.to_string(),
+                        });
+                    }
+                    (sym::issue_tracker_base_url, Some(s)) => {
+                        issue_tracker_base_url = Some(s.to_string());
+                    }
+                    (sym::html_no_source, None) if attr.is_word() => {
+                        include_sources = false;
+                    }
+                    _ => {}
                 }
-                _ => {}
             }
         }
+        let mut scx = SharedContext {
+            collapsed: krate.collapsed,
+            src_root,
+            include_sources,
+            local_sources: Default::default(),
+            issue_tracker_base_url,
+            layout,
+            created_dirs: Default::default(),
+            sort_modules_alphabetically,
+            themes,
+            resource_suffix,
+            static_root_path,
+            generate_redirect_pages,
+            fs: DocFS::new(&errors),
+            edition,
+            codes: ErrorCodes::from(UnstableFeatures::from_environment().is_nightly_build()),
+            playground,
+        };
+
+        let dst = output;
+        scx.ensure_dir(&dst)?;
+        CURRENT_DEPTH.with(|s| s.set(0));
+
+        // The source pages are rendered once the cache is available, since the links of their
+        // identifiers to other crates are found through it.
+        let krate = sources::render(&dst, &mut scx, krate)?;
+        let mut cx = Context {
+            current: Vec::new(),
+            dst,
+            render_redirect_pages: false,
+            stripped_depth: 0,
+            id_map: Rc::new(RefCell::new(id_map)),
+            shared: Arc::new(scx),
+            cache,
+            all: AllTypes::new(),
+            errors,
+        };
+
+        // Write shared runs within a flock; disable thread dispatching of IO temporarily.
+        Arc::get_mut(&mut cx.shared).unwrap().fs.set_sync_only(true);
+        write_shared(&cx, &krate, search_index, &md_opts, diag)?;
+        Arc::get_mut(&mut cx.shared).unwrap().fs.set_sync_only(false);
+
+        Ok((cx, krate))
     }
-    let mut scx = SharedContext {
-        collapsed: krate.collapsed,
-        src_root,
-        include_sources,
-        local_sources: Default::default(),
-        issue_tracker_base_url,
-        layout,
-        created_dirs: Default::default(),
-        sort_modules_alphabetically,
-        themes,
-        resource_suffix,
-        static_root_path,
-        generate_redirect_pages,
-        fs: DocFS::new(&errors),
-        edition,
-        codes: ErrorCodes::from(UnstableFeatures::from_environment().is_nightly_build()),
-        playground,
-    };
 
-    let dst = output;
-    scx.ensure_dir(&dst)?;
-    krate = sources::render(&dst, &mut scx, krate)?;
-    let (new_crate, index, cache) =
-        Cache::from_krate(renderinfo, &extern_html_root_urls, &dst, krate);
-    krate = new_crate;
-    let cache = Arc::new(cache);
-    let mut cx = Context {
-        current: Vec::new(),
-        dst,
-        render_redirect_pages: false,
-        id_map: Rc::new(RefCell::new(id_map)),
-        shared: Arc::new(scx),
-        cache: cache.clone(),
-    };
+    fn item(&mut self, item: clean::Item) -> Result<(), Error> {
+        // Stripped items only get a redirect page, like the items of stripped modules.
+        let render_redirect_pages = self.render_redirect_pages;
+        self.render_redirect_pages |= item.is_stripped();
+        let res = self.item_page(&item);
+        self.render_redirect_pages = render_redirect_pages;
+        res
+    }
 
-    // Freeze the cache now that the index has been built. Put an Arc into TLS
-    // for future parallelization opportunities
-    CACHE_KEY.with(|v| *v.borrow_mut() = cache.clone());
-    CURRENT_DEPTH.with(|s| s.set(0));
+    fn mod_item_in(&mut self, item: &clean::Item, item_name: &str) -> Result<(), Error> {
+        // Stripped modules survive the rustdoc passes (i.e., `strip-private`)
+        // if they contain impls for public types. These modules can also
+        // contain items such as publicly re-exported structures.
+        //
+        // External crates will provide links to these structures, so
+        // these modules are recursed into, but not rendered normally
+        // (a flag on the context).
+        if item.is_stripped() || self.stripped_depth > 0 {
+            self.stripped_depth += 1;
+        }
+        self.render_redirect_pages = self.stripped_depth > 0;
 
-    // Write shared runs within a flock; disable thread dispatching of IO temporarily.
-    Arc::get_mut(&mut cx.shared).unwrap().fs.set_sync_only(true);
-    write_shared(&cx, &krate, index, &md_opts, diag)?;
-    Arc::get_mut(&mut cx.shared).unwrap().fs.set_sync_only(false);
+        // modules are special because they add a namespace. We also need to
+        // recurse into the items of the module as well.
+        let scx = &self.shared;
+        if item_name.is_empty() {
+            panic!("Unexpected empty destination: {:?}", self.current);
+        }
+        self.dst.push(item_name);
+        self.current.push(item_name.to_owned());
 
-    // And finally render the whole crate's documentation
-    let ret = cx.krate(krate);
-    let nb_errors = Arc::get_mut(&mut errors).map_or_else(|| 0, |errors| errors.write_errors(diag));
-    if ret.is_err() {
-        ret
-    } else if nb_errors > 0 {
-        Err(Error::new(io::Error::new(io::ErrorKind::Other, "I/O error"), ""))
-    } else {
+        info!("Recursing into {}", self.dst.display());
+
+        let buf = self.render_item(item, false);
+        // buf will be empty if the module is stripped and there is no redirect for it
+        if !buf.is_empty() {
+            self.shared.ensure_dir(&self.dst)?;
+            let joint_dst = self.dst.join("index.html");
+            scx.fs.write(&joint_dst, buf.as_bytes())?;
+        }
+
+        let m = match item.inner {
+            clean::StrippedItem(box clean::ModuleItem(ref m)) | clean::ModuleItem(ref m) => m,
+            _ => unreachable!(),
+        };
+
+        // Render sidebar-items.js used throughout this module.
+        if !self.render_redirect_pages {
+            let items = self.build_sidebar_items(m);
+            let js_dst = self.dst.join("sidebar-items.js");
+            let v = format!("initSidebarItems({});", serde_json::to_string(&items).unwrap());
+            scx.fs.write(&js_dst, &v)?;
+        }
         Ok(())
     }
+
+    fn mod_item_out(&mut self, _item_name: &str) -> Result<(), Error> {
+        info!("Recursed; leaving {}", self.dst.display());
+
+        // Go back to where we were at
+        self.dst.pop();
+        self.current.pop();
+        self.stripped_depth = self.stripped_depth.saturating_sub(1);
+        self.render_redirect_pages = self.stripped_depth > 0;
+        Ok(())
+    }
+
+    fn after_krate(
+        &mut self,
+        krate: &clean::Crate,
+        diag: &rustc_errors::Handler,
+    ) -> Result<(), Error> {
+        let final_file = self.dst.join(&krate.name).join("all.html");
+        let settings_file = self.dst.join("settings.html");
+
+        let mut root_path = self.dst.to_str().expect("invalid path").to_owned();
+        if !root_path.ends_with('/') {
+            root_path.push('/');
+        }
+        let mut page = layout::Page {
+            title: "List of all items in this crate",
+            css_class: "mod",
+            root_path: "../",
+            static_root_path: self.shared.static_root_path.as_deref(),
+            description: "List of all items in this crate",
+            keywords: BASIC_KEYWORDS,
+            resource_suffix: &self.shared.resource_suffix,
+            extra_scripts: &[],
+            static_extra_scripts: &[],
+        };
+        let sidebar = if let Some(ref version) = self.cache.crate_version {
+            format!(
+                "<p class='location'>Crate {}</p>\
+                     <div class='block version'>\
+                         <p>Version {}</p>\
+                     </div>\
+                     <a id='all-types' href='index.html'><p>Back to index</p></a>",
+                krate.name,
+                Escape(version),
+            )
+        } else {
+            String::new()
+        };
+        let all = mem::replace(&mut self.all, AllTypes::new());
+        let v = layout::render(
+            &self.shared.layout,
+            &page,
+            sidebar,
+            |buf: &mut Buffer| all.print(buf),
+            &self.shared.themes,
+        );
+        self.shared.fs.write(&final_file, v.as_bytes())?;
+
+        // Generating settings page.
+        page.title = "Rustdoc settings";
+        page.description = "Settings of Rustdoc";
+        page.root_path = "./";
+
+        let mut themes = self.shared.themes.clone();
+        let sidebar = "<p class='location'>Settings</p><div class='sidebar-elems'></div>";
+        themes.push(PathBuf::from("settings.css"));
+        let v = layout::render(
+            &self.shared.layout,
+            &page,
+            sidebar,
+            settings(
+                self.shared.static_root_path.as_deref().unwrap_or("./"),
+                &self.shared.resource_suffix,
+            ),
+            &themes,
+        );
+        self.shared.fs.write(&settings_file, v.as_bytes())?;
+
+        if self.errors.write_errors(diag) > 0 {
+            Err(Error::new(io::Error::new(io::ErrorKind::Other, "I/O error"), ""))
+        } else {
+            Ok(())
+        }
+    }
 }
 
 fn write_shared(
     cx: &Context,
     krate: &clean::Crate,
-    search_index: String,
+    search_index: SearchIndex,
     options: &RenderOptions,
     diag: &rustc_errors::Handler,
 ) -> Result<(), Error> {
@@ -906,10 +1117,22 @@ themePicker.onblur = handleThemeButtonsBlur;
         cx.shared.fs.write(&dst, v.as_bytes())?;
     }
 
-    // Update the search index
+    // Update the search index. The items of each crate are written to their own file, which is
+    // only loaded when searching, and `search-index.js` only lists the crates.
+    let shard_dir = cx.dst.join("search-index");
+    try_err!(cx.shared.fs.create_dir_all(&shard_dir), &shard_dir);
+    let shard = shard_dir.join(&format!("{}{}.js", krate.name, cx.shared.resource_suffix));
+    cx.shared.fs.write(
+        &shard,
+        format!(
+            "searchIndexShardLoaded(\"{}\", JSON.parse('{}'));\n",
+            krate.name, search_index.items
+        ),
+    )?;
+
     let dst = cx.dst.join(&format!("search-index{}.js", cx.shared.resource_suffix));
     let (mut all_indexes, mut krates) = try_err!(collect_json(&dst, &krate.name), &dst);
-    all_indexes.push(search_index);
+    all_indexes.push(format!("\"{}\":{}", krate.name, search_index.doc));
 
     // Sort the indexes by crate so the file will be generated identically even
     // with rustdoc running in parallel.
@@ -1316,92 +1539,6 @@ impl Context {
         "../".repeat(self.current.len())
     }
 
-    /// Main method for rendering a crate.
-    ///
-    /// This currently isn't parallelized, but it'd be pretty easy to add
-    /// parallelization to this function.
-    fn krate(self, mut krate: clean::Crate) -> Result<(), Error> {
-        let mut item = match krate.module.take() {
-            Some(i) => i,
-            None => return Ok(()),
-        };
-        let final_file = self.dst.join(&krate.name).join("all.html");
-        let settings_file = self.dst.join("settings.html");
-
-        let crate_name = krate.name.clone();
-        item.name = Some(krate.name);
-
-        let mut all = AllTypes::new();
-
-        {
-            // Render the crate documentation
-            let mut work = vec![(self.clone(), item)];
-
-            while let Some((mut cx, item)) = work.pop() {
-                cx.item(item, &mut all, |cx, item| work.push((cx.clone(), item)))?
-            }
-        }
-
-        let mut root_path = self.dst.to_str().expect("invalid path").to_owned();
-        if !root_path.ends_with('/') {
-            root_path.push('/');
-        }
-        let mut page = layout::Page {
-            title: "List of all items in this crate",
-            css_class: "mod",
-            root_path: "../",
-            static_root_path: self.shared.static_root_path.as_deref(),
-            description: "List of all items in this crate",
-            keywords: BASIC_KEYWORDS,
-            resource_suffix: &self.shared.resource_suffix,
-            extra_scripts: &[],
-            static_extra_scripts: &[],
-        };
-        let sidebar = if let Some(ref version) = self.cache.crate_version {
-            format!(
-                "<p class='location'>Crate {}</p>\
-                     <div class='block version'>\
-                         <p>Version {}</p>\
-                     </div>\
-                     <a id='all-types' href='index.html'><p>Back to index</p></a>",
-                crate_name,
-                Escape(version),
-            )
-        } else {
-            String::new()
-        };
-        let v = layout::render(
-            &self.shared.layout,
-            &page,
-            sidebar,
-            |buf: &mut Buffer| all.print(buf),
-            &self.shared.themes,
-        );
-        self.shared.fs.write(&final_file, v.as_bytes())?;
-
-        // Generating settings page.
-        page.title = "Rustdoc settings";
-        page.description = "Settings of Rustdoc";
-        page.root_path = "./";
-
-        let mut themes = self.shared.themes.clone();
-        let sidebar = "<p class='location'>Settings</p><div class='sidebar-elems'></div>";
-        themes.push(PathBuf::from("settings.css"));
-        let v = layout::render(
-            &self.shared.layout,
-            &page,
-            sidebar,
-            settings(
-                self.shared.static_root_path.as_deref().unwrap_or("./"),
-                &self.shared.resource_suffix,
-            ),
-            &themes,
-        );
-        self.shared.fs.write(&settings_file, v.as_bytes())?;
-
-        Ok(())
-    }
-
     fn render_item(&self, it: &clean::Item, pushname: bool) -> String {
         // A little unfortunate that this is done like this, but it sure
         // does make formatting *a lot* nicer.
@@ -1474,100 +1611,37 @@ impl Context {
         }
     }
 
-    /// Non-parallelized version of rendering an item. This will take the input
-    /// item, render its contents, and then invoke the specified closure with
-    /// all sub-items which need to be rendered.
-    ///
-    /// The rendering driver uses this closure to queue up more work.
-    fn item<F>(&mut self, item: clean::Item, all: &mut AllTypes, mut f: F) -> Result<(), Error>
-    where
-        F: FnMut(&mut Context, clean::Item),
-    {
-        // Stripped modules survive the rustdoc passes (i.e., `strip-private`)
-        // if they contain impls for public types. These modules can also
-        // contain items such as publicly re-exported structures.
-        //
-        // External crates will provide links to these structures, so
-        // these modules are recursed into, but not rendered normally
-        // (a flag on the context).
-        if !self.render_redirect_pages {
-            self.render_redirect_pages = item.is_stripped();
-        }
-
-        if item.is_mod() {
-            // modules are special because they add a namespace. We also need to
-            // recurse into the items of the module as well.
-            let name = item.name.as_ref().unwrap().to_string();
-            let scx = &self.shared;
-            if name.is_empty() {
-                panic!("Unexpected empty destination: {:?}", self.current);
-            }
-            let prev = self.dst.clone();
-            self.dst.push(&name);
-            self.current.push(name);
-
-            info!("Recursing into {}", self.dst.display());
-
-            let buf = self.render_item(&item, false);
-            // buf will be empty if the module is stripped and there is no redirect for it
-            if !buf.is_empty() {
-                self.shared.ensure_dir(&self.dst)?;
-                let joint_dst = self.dst.join("index.html");
-                scx.fs.write(&joint_dst, buf.as_bytes())?;
-            }
-
-            let m = match item.inner {
-                clean::StrippedItem(box clean::ModuleItem(m)) | clean::ModuleItem(m) => m,
-                _ => unreachable!(),
-            };
+    /// Renders the page of an item that isn't a module, and its redirect pages.
+    fn item_page(&mut self, item: &clean::Item) -> Result<(), Error> {
+        let buf = self.render_item(item, true);
+        // buf will be empty if the item is stripped and there is no redirect for it
+        if !buf.is_empty() {
+            let name = item.name.as_ref().unwrap();
+            let item_type = item.type_();
+            let file_name = &item_path(item_type, name);
+            self.shared.ensure_dir(&self.dst)?;
+            let joint_dst = self.dst.join(file_name);
+            self.shared.fs.write(&joint_dst, buf.as_bytes())?;
 
-            // Render sidebar-items.js used throughout this module.
             if !self.render_redirect_pages {
-                let items = self.build_sidebar_items(&m);
-                let js_dst = self.dst.join("sidebar-items.js");
-                let v = format!("initSidebarItems({});", serde_json::to_string(&items).unwrap());
-                scx.fs.write(&js_dst, &v)?;
+                let path = full_path(self, item);
+                self.all.append(path, &item_type);
             }
-
-            for item in m.items {
-                f(self, item);
+            if self.shared.generate_redirect_pages {
+                // Redirect from a sane URL using the namespace to Rustdoc's
+                // URL for the page.
+                let redir_name = format!("{}.{}.html", name, item_type.name_space());
+                let redir_dst = self.dst.join(redir_name);
+                let v = layout::redirect(file_name);
+                self.shared.fs.write(&redir_dst, v.as_bytes())?;
             }
-
-            info!("Recursed; leaving {}", self.dst.display());
-
-            // Go back to where we were at
-            self.dst = prev;
-            self.current.pop().unwrap();
-        } else if item.name.is_some() {
-            let buf = self.render_item(&item, true);
-            // buf will be empty if the item is stripped and there is no redirect for it
-            if !buf.is_empty() {
-                let name = item.name.as_ref().unwrap();
-                let item_type = item.type_();
-                let file_name = &item_path(item_type, name);
-                self.shared.ensure_dir(&self.dst)?;
-                let joint_dst = self.dst.join(file_name);
-                self.shared.fs.write(&joint_dst, buf.as_bytes())?;
-
-                if !self.render_redirect_pages {
-                    all.append(full_path(self, &item), &item_type);
-                }
-                if self.shared.generate_redirect_pages {
-                    // Redirect from a sane URL using the namespace to Rustdoc's
-                    // URL for the page.
-                    let redir_name = format!("{}.{}.html", name, item_type.name_space());
-                    let redir_dst = self.dst.join(redir_name);
-                    let v = layout::redirect(file_name);
-                    self.shared.fs.write(&redir_dst, v.as_bytes())?;
-                }
-                // If the item is a macro, redirect from the old macro URL (with !)
-                // to the new one (without).
-                if item_type == ItemType::Macro {
-                    let redir_name = format!("{}.{}!.html", item_type, name);
-                    let redir_dst = self.dst.join(redir_name);
-                    let v = layout::redirect(file_name);
-                    self.shared.fs.write(&redir_dst, v.as_bytes())?;
-                }
+            // If the item is a macro, redirect from the old macro URL (with !)
+            // to the new one (without).
+            if item_type == ItemType::Macro {
+                let redir_name = format!("{}.{}!.html", item_type, name);
+                let redir_dst = self.dst.join(redir_name);
+                let v = layout::redirect(file_name);
+                self.shared.fs.write(&redir_dst, v.as_bytes())?;
             }
         }
         Ok(())
@@ -2396,7 +2470,69 @@ fn item_function(w: &mut Buffer, cx: &Context, it: &clean::Item, f: &clean::Func
         decl = Function { decl: &f.decl, header_len, indent: 0, asyncness: f.header.asyncness }
             .print()
     );
-    document(w, cx, it)
+    document(w, cx, it);
+    render_call_locations(w, cx, it)
+}
+
+/// Renders the calls to the function `it` that were found in other crates and given with
+/// `--with-examples`. Every call is shown within the item it was made from, with the lines of
+/// the call highlighted.
+fn render_call_locations(w: &mut Buffer, cx: &Context, it: &clean::Item) {
+    let calls = match cx.cache.call_locations.get(&it.def_id) {
+        Some(calls) if !calls.is_empty() => calls,
+        _ => return,
+    };
+
+    write!(w, "<details class='scraped-examples'><summary>Examples found in repository</summary>");
+    for call_data in calls.values() {
+        // Several calls can be made from the same item, which is then only shown once.
+        let mut items: Vec<(&SyntaxRange, Vec<&SyntaxRange>)> = Vec::new();
+        for loc in &call_data.locations {
+            match items.iter_mut().find(|(item, _)| item.byte_span == loc.enclosing_item.byte_span)
+            {
+                Some((_, calls)) => calls.push(&loc.call_expr),
+                None => items.push((&loc.enclosing_item, vec![&loc.call_expr])),
+            }
+        }
+
+        for (item, calls) in items {
+            let (lo, hi) = (item.byte_span.0 as usize, item.byte_span.1 as usize);
+            // Start from the beginning of the first line, so the item keeps its indentation.
+            let src = call_data.contents.get(..lo).and_then(|before| {
+                let lo = before.rfind('\n').map_or(0, |i| i + 1);
+                call_data.contents.get(lo..hi)
+            });
+            let src = match src {
+                Some(src) => src,
+                None => continue,
+            };
+
+            write!(
+                w,
+                "<div class='scraped-example'><div class='scraped-example-title'>{}</div>\
+                 <div class='example-wrap'><pre class='line-numbers'>",
+                Escape(&call_data.display_name)
+            );
+            for line in item.line_span.0..=item.line_span.1 {
+                let highlighted =
+                    calls.iter().any(|call| call.line_span.0 <= line && line <= call.line_span.1);
+                write!(
+                    w,
+                    "<span{}>{}</span>\n",
+                    if highlighted { " class='line-highlighted'" } else { "" },
+                    line
+                );
+            }
+            write!(w, "</pre>");
+            write!(
+                w,
+                "{}",
+                highlight::render_with_highlighting(src, Some("scraped-example-code"), None, None)
+            );
+            write!(w, "</div></div>");
+        }
+    }
+    write!(w, "</details>");
 }
 
 fn render_implementor(
@@ -2588,6 +2724,7 @@ fn item_trait(w: &mut Buffer, cx: &Context, it: &clean::Item, t: &clean::Trait)
         render_stability_since(w, m, t);
         write!(w, "</h3>");
         document(w, cx, m);
+        render_call_locations(w, cx, m);
     }
 
     if !types.is_empty() {
@@ -3730,6 +3867,7 @@ fn render_impl(
                     document_stability(w, cx, item, is_hidden);
                     if show_def_docs {
                         document_full(w, item, cx, "", is_hidden);
+                        render_call_locations(w, cx, item);
                     }
                 }
             } else {
@@ -4425,7 +4563,7 @@ fn sidebar_enum(buf: &mut Buffer, it: &clean::Item, e: &clean::Enum) {
     }
 }
 
-fn item_ty_to_strs(ty: &ItemType) -> (&'static str, &'static str) {
+crate fn item_ty_to_strs(ty: &ItemType) -> (&'static str, &'static str) {
     match *ty {
         ItemType::ExternCrate | ItemType::Import => ("reexports", "Re-exports"),
         ItemType::Module => ("modules", "Modules"),
@@ -4621,3 +4759,8 @@ fn collect_paths_for_type(first_ty: clean::Type) -> Vec<String> {
 crate fn cache() -> Arc<Cache> {
     CACHE_KEY.with(|c| c.borrow().clone())
 }
+
+/// Makes `cache` the cache of the crate being documented.
+crate fn set_cache(cache: Arc<Cache>) {
+    CACHE_KEY.with(|v| *v.borrow_mut() = cache);
+}
@@ -1,18 +1,22 @@
 use crate::clean::{self, AttributesExt, GetDefId};
 use crate::fold::DocFolder;
+use crate::scrape_examples::FnCallLocations;
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_hir::def_id::{CrateNum, DefId, CRATE_DEF_INDEX};
+use rustc_hir::Mutability;
 use rustc_middle::middle::privacy::AccessLevels;
 use rustc_span::source_map::FileName;
 use rustc_span::symbol::sym;
 use std::collections::BTreeMap;
 use std::mem;
 use std::path::{Path, PathBuf};
+use std::slice;
 
 use serde::Serialize;
 
 use super::{plain_summary_line, shorten, Impl, IndexItem, IndexItemFunctionType, ItemType};
-use super::{Generic, RenderInfo, RenderType, TypeWithKind};
+use super::{FunctionSignature, Generic, RenderInfo, RenderType, SignatureType, TypeWithKind};
+use super::span_map::SpanMap;
 
 /// Indicates where an external crate can be found.
 pub enum ExternalLocation {
@@ -95,6 +99,8 @@ crate struct Cache {
     stack: Vec<String>,
     parent_stack: Vec<DefId>,
     parent_is_trait_impl: bool,
+    /// The type `Self` stands for in the impl being crawled, if any.
+    impl_self_type: Option<clean::Type>,
     search_index: Vec<IndexItem>,
     stripped_mod: bool,
     pub deref_trait_did: Option<DefId>,
@@ -107,7 +113,7 @@ crate struct Cache {
     // then the fully qualified name of the structure isn't presented in `paths`
     // yet when its implementation methods are being indexed. Caches such methods
     // and their parent id here and indexes them at the end of crate parsing.
-    orphan_impl_items: Vec<(DefId, clean::Item)>,
+    orphan_impl_items: Vec<(DefId, clean::Item, Option<FunctionSignature>)>,
 
     // Similarly to `orphan_impl_items`, sometimes trait impls are picked up
     // even though the trait itself is not exported. This can happen if a trait
@@ -121,6 +127,13 @@ crate struct Cache {
     /// Aliases added through `#[doc(alias = "...")]`. Since a few items can have the same alias,
     /// we need the alias element to have an array of items.
     pub(super) aliases: BTreeMap<String, Vec<usize>>,
+
+    /// Calls to local functions found in other crates, given with `--with-examples`.
+    pub call_locations: FxHashMap<DefId, FnCallLocations>,
+
+    /// Links of the identifiers of the local source files to their definitions, when
+    /// `--generate-link-to-definition` is passed.
+    pub span_map: SpanMap,
 }
 
 impl Cache {
@@ -129,7 +142,7 @@ impl Cache {
         extern_html_root_urls: &BTreeMap<String, String>,
         dst: &Path,
         mut krate: clean::Crate,
-    ) -> (clean::Crate, String, Cache) {
+    ) -> (clean::Crate, SearchIndex, Cache) {
         // Crawl the crate to build various caches used for the output
         let RenderInfo {
             inlined: _,
@@ -139,6 +152,8 @@ impl Cache {
             deref_trait_did,
             deref_mut_trait_did,
             owned_box_did,
+            call_locations,
+            span_map,
             ..
         } = renderinfo;
 
@@ -155,6 +170,7 @@ impl Cache {
             parent_stack: Vec::new(),
             search_index: Vec::new(),
             parent_is_trait_impl: false,
+            impl_self_type: None,
             extern_locations: Default::default(),
             primitive_locations: Default::default(),
             stripped_mod: false,
@@ -168,6 +184,8 @@ impl Cache {
             owned_box_did,
             masked_crates: mem::take(&mut krate.masked_crates),
             aliases: Default::default(),
+            call_locations,
+            span_map,
         };
 
         // Cache where all our extern crates are located
@@ -325,7 +343,14 @@ impl DocFolder for Cache {
                             desc: shorten(plain_summary_line(item.doc_value())),
                             parent,
                             parent_idx: None,
+                            path_idx: None,
+                            type_idx: None,
+                            signature_idx: None,
                             search_type: get_index_search_type(&item),
+                            search_signature: get_index_signature(
+                                &item,
+                                self.impl_self_type.as_ref(),
+                            ),
                         });
 
                         for alias in item.attrs.get_doc_aliases() {
@@ -339,7 +364,8 @@ impl DocFolder for Cache {
                 (Some(parent), None) if is_inherent_impl_item => {
                     // We have a parent, but we don't know where they're
                     // defined yet. Wait for later to index this item.
-                    self.orphan_impl_items.push((parent, item.clone()));
+                    let signature = get_index_signature(&item, self.impl_self_type.as_ref());
+                    self.orphan_impl_items.push((parent, item.clone(), signature));
                 }
                 _ => {}
             }
@@ -393,6 +419,7 @@ impl DocFolder for Cache {
 
         // Maintain the parent stack
         let orig_parent_is_trait_impl = self.parent_is_trait_impl;
+        let orig_impl_self_type = self.impl_self_type.take();
         let parent_pushed = match item.inner {
             clean::TraitItem(..)
             | clean::EnumItem(..)
@@ -406,6 +433,7 @@ impl DocFolder for Cache {
             }
             clean::ImplItem(ref i) => {
                 self.parent_is_trait_impl = i.trait_.is_some();
+                self.impl_self_type = Some(i.for_.clone());
                 match i.for_ {
                     clean::ResolvedPath { did, .. } => {
                         self.parent_stack.push(did);
@@ -488,6 +516,7 @@ impl DocFolder for Cache {
         }
         self.stripped_mod = orig_stripped_mod;
         self.parent_is_trait_impl = orig_parent_is_trait_impl;
+        self.impl_self_type = orig_impl_self_type;
         ret
     }
 }
@@ -531,18 +560,35 @@ fn extern_location(
         .unwrap_or(Unknown) // Well, at least we tried.
 }
 
+/// The search index of a crate, escaped to be put in a JavaScript string.
+crate struct SearchIndex {
+    /// The summary line of the documentation of the crate, as a JSON string.
+    pub doc: String,
+    /// The items of the crate, as a JSON object.
+    pub items: String,
+}
+
 /// Builds the search index from the collected metadata
-fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
+fn build_index(krate: &clean::Crate, cache: &mut Cache) -> SearchIndex {
     let mut defid_to_pathid = FxHashMap::default();
     let mut crate_items = Vec::with_capacity(cache.search_index.len());
     let mut crate_paths = vec![];
-
-    let Cache { ref mut search_index, ref orphan_impl_items, ref paths, ref mut aliases, .. } =
-        *cache;
+    // The module paths, function types and signatures are often shared by many items, so each of
+    // them is only written once, in a table the items refer to.
+    let mut module_paths = vec![];
+    let mut path_to_idx = FxHashMap::default();
+    let mut types = vec![];
+    let mut type_to_idx = FxHashMap::default();
+    let mut signatures = vec![];
+    let mut signature_to_idx = FxHashMap::default();
+
+    let Cache {
+        ref mut search_index, ref mut orphan_impl_items, ref paths, ref mut aliases, ..
+    } = *cache;
 
     // Attach all orphan items to the type's definition if the type
     // has since been learned.
-    for &(did, ref item) in orphan_impl_items {
+    for (did, item, signature) in orphan_impl_items.drain(..) {
         if let Some(&(ref fqp, _)) = paths.get(&did) {
             search_index.push(IndexItem {
                 ty: item.type_(),
@@ -551,7 +597,11 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
                 desc: shorten(plain_summary_line(item.doc_value())),
                 parent: Some(did),
                 parent_idx: None,
+                path_idx: None,
+                type_idx: None,
+                signature_idx: None,
                 search_type: get_index_search_type(&item),
+                search_signature: signature,
             });
             for alias in item.attrs.get_doc_aliases() {
                 aliases
@@ -564,7 +614,6 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
 
     // Reduce `DefId` in paths into smaller sequential numbers,
     // and prune the paths that do not appear in the index.
-    let mut lastpath = String::new();
     let mut lastpathid = 0usize;
 
     for item in search_index {
@@ -585,12 +634,24 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
             }
         });
 
-        // Omit the parent path if it is same to that of the prior item.
-        if lastpath == item.path {
-            item.path.clear();
-        } else {
-            lastpath = item.path.clone();
-        }
+        item.path_idx = Some(*path_to_idx.entry(item.path.clone()).or_insert_with(|| {
+            module_paths.push(item.path.clone());
+            module_paths.len() - 1
+        }));
+        item.type_idx = item.search_type.as_ref().map(|ty| {
+            let key = serde_json::to_string(ty).expect("failed serde conversion");
+            *type_to_idx.entry(key).or_insert_with(|| {
+                types.push(ty);
+                types.len() - 1
+            })
+        });
+        item.signature_idx = item.search_signature.as_ref().map(|signature| {
+            let key = serde_json::to_string(signature).expect("failed serde conversion");
+            *signature_to_idx.entry(key).or_insert_with(|| {
+                signatures.push(signature);
+                signatures.len() - 1
+            })
+        });
         crate_items.push(&*item);
     }
 
@@ -602,11 +663,17 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
 
     #[derive(Serialize)]
     struct CrateData<'a> {
-        doc: String,
+        doc: &'a str,
         #[serde(rename = "i")]
         items: Vec<&'a IndexItem>,
         #[serde(rename = "p")]
         paths: Vec<(ItemType, String)>,
+        #[serde(rename = "m")]
+        module_paths: Vec<String>,
+        #[serde(rename = "f")]
+        types: Vec<&'a IndexItemFunctionType>,
+        #[serde(rename = "s")]
+        signatures: Vec<&'a FunctionSignature>,
         // The String is alias name and the vec is the list of the elements with this alias.
         //
         // To be noted: the `usize` elements are indexes to `items`.
@@ -616,22 +683,27 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
     }
 
     // Collect the index into a string
-    format!(
-        r#""{}":{}"#,
-        krate.name,
-        serde_json::to_string(&CrateData {
-            doc: crate_doc,
-            items: crate_items,
-            paths: crate_paths,
-            aliases,
-        })
-        .expect("failed serde conversion")
-        // All these `replace` calls are because we have to go through JS string for JSON content.
-        .replace(r"\", r"\\")
+    let items = serde_json::to_string(&CrateData {
+        doc: &crate_doc,
+        items: crate_items,
+        paths: crate_paths,
+        module_paths,
+        types,
+        signatures,
+        aliases,
+    })
+    .expect("failed serde conversion");
+    let doc = serde_json::to_string(&crate_doc).expect("failed serde conversion");
+
+    SearchIndex { doc: escape_js_string(&doc), items: escape_js_string(&items) }
+}
+
+fn escape_js_string(json: &str) -> String {
+    // All these `replace` calls are because we have to go through JS string for JSON content.
+    json.replace(r"\", r"\\")
         .replace("'", r"\'")
         // We need to escape double quotes for the JSON.
         .replace("\\\"", "\\\\\"")
-    )
 }
 
 fn get_index_search_type(item: &clean::Item) -> Option<IndexItemFunctionType> {
@@ -657,6 +729,87 @@ fn get_index_search_type(item: &clean::Item) -> Option<IndexItemFunctionType> {
     Some(IndexItemFunctionType { inputs, output })
 }
 
+/// Builds the full signature of a function, used to search functions by type signature. `Self`
+/// is replaced by `self_type`, the type of the impl the function is in.
+fn get_index_signature(
+    item: &clean::Item,
+    self_type: Option<&clean::Type>,
+) -> Option<FunctionSignature> {
+    let decl = match item.inner {
+        clean::FunctionItem(ref f) => &f.decl,
+        clean::MethodItem(ref m) => &m.decl,
+        clean::TyMethodItem(ref m) => &m.decl,
+        _ => return None,
+    };
+
+    let mut generics = Vec::new();
+    let inputs = decl
+        .inputs
+        .values
+        .iter()
+        .map(|arg| get_signature_type(&arg.type_, self_type, &mut generics))
+        .collect();
+    let output = match decl.output {
+        clean::FnRetTy::Return(clean::Tuple(ref types)) if types.is_empty() => None,
+        clean::FnRetTy::Return(ref ty) => Some(get_signature_type(ty, self_type, &mut generics)),
+        clean::FnRetTy::DefaultReturn => None,
+    };
+
+    Some(FunctionSignature { inputs, output })
+}
+
+/// Converts a type of a signature. `generics` are the names of the type parameters found so
+/// far, whose positions are the numbers they are given in the index.
+fn get_signature_type(
+    clean_type: &clean::Type,
+    self_type: Option<&clean::Type>,
+    generics: &mut Vec<String>,
+) -> SignatureType {
+    let (name, types): (&str, &[clean::Type]) = match *clean_type {
+        clean::Generic(ref name) => match self_type {
+            Some(self_type) if name == "Self" => {
+                return get_signature_type(self_type, None, generics);
+            }
+            _ => {
+                let index = generics.iter().position(|g| g == name).unwrap_or_else(|| {
+                    generics.push(name.clone());
+                    generics.len() - 1
+                });
+                return SignatureType::Generic(index);
+            }
+        },
+        // `impl Trait` can be any type, like a type parameter that only appears once.
+        clean::ImplTrait(_) => {
+            generics.push(String::new());
+            return SignatureType::Generic(generics.len() - 1);
+        }
+        clean::ResolvedPath { ref path, is_generic: false, .. } => {
+            let name = match path.segments.last() {
+                Some(segment) => segment.name.to_ascii_lowercase(),
+                None => return SignatureType::Unknown,
+            };
+            let args = clean_type.generics().unwrap_or_default();
+            let args = args.iter().map(|ty| get_signature_type(ty, self_type, generics)).collect();
+            return SignatureType::Named(name, args);
+        }
+        clean::Primitive(p) => (p.as_str(), &[]),
+        clean::Tuple(ref types) => ("()", &types[..]),
+        clean::Slice(ref ty) | clean::Array(ref ty, _) => ("[]", slice::from_ref(&**ty)),
+        clean::RawPointer(mutability, ref ty) => {
+            let name = if mutability == Mutability::Mut { "*mut" } else { "*const" };
+            (name, slice::from_ref(&**ty))
+        }
+        clean::BorrowedRef { mutability, ref type_, .. } => {
+            let name = if mutability == Mutability::Mut { "&mut" } else { "&" };
+            (name, slice::from_ref(&**type_))
+        }
+        clean::Never => ("!", &[]),
+        _ => return SignatureType::Unknown,
+    };
+    let args = types.iter().map(|ty| get_signature_type(ty, self_type, generics)).collect();
+    SignatureType::Named(name.to_string(), args)
+}
+
 fn get_index_type(clean_type: &clean::Type) -> RenderType {
     RenderType {
         ty: clean_type.def_id(),
@@ -0,0 +1,173 @@
+//! Finding what the identifiers of the local source files refer to, so that the rendered source
+//! pages can link them to their definitions (`--generate-link-to-definition`).
+
+use std::ops::Range;
+use std::path::PathBuf;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir as hir;
+use rustc_hir::def::{DefKind, Res};
+use rustc_hir::def_id::{DefId, LocalDefId};
+use rustc_hir::intravisit::{self, NestedVisitorMap, Visitor};
+use rustc_hir::HirId;
+use rustc_middle::hir::map::Map;
+use rustc_middle::ty::{TyCtxt, TypeckTables};
+use rustc_span::{FileName, Span};
+
+/// Where an identifier of a source page links to.
+#[derive(Clone, Debug)]
+pub enum LinkFromSrc {
+    /// A line of a source file of the local crate.
+    Local { file: PathBuf, line: usize },
+    /// The documentation page of an item of another crate.
+    External(DefId),
+}
+
+/// The links of each source file of the local crate: the byte range of the linked identifier in
+/// the file, sorted by the start of the range, and where it links to.
+pub type SpanMap = FxHashMap<PathBuf, Vec<(Range<u32>, LinkFromSrc)>>;
+
+/// Visits the local crate, and records the definition each path, method call and local variable
+/// refers to.
+struct SpanMapVisitor<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    /// The type-check results of the body being visited.
+    tables: Option<&'tcx TypeckTables<'tcx>>,
+    links: SpanMap,
+}
+
+impl<'tcx> SpanMapVisitor<'tcx> {
+    /// Returns the source file of `span` and the byte range it covers in it, if it was written
+    /// in a file of the local crate.
+    fn file_range(&self, span: Span) -> Option<(PathBuf, Range<u32>)> {
+        // Code written by macros doesn't appear as such in the source.
+        if span.is_dummy() || span.from_expansion() {
+            return None;
+        }
+        let source_map = self.tcx.sess.source_map();
+        let lo = source_map.lookup_byte_offset(span.lo());
+        match lo.sf.name {
+            FileName::Real(ref path) => {
+                Some((path.clone(), lo.pos.0..(lo.pos + (span.hi() - span.lo())).0))
+            }
+            _ => None,
+        }
+    }
+
+    fn local_target(&self, span: Span) -> Option<LinkFromSrc> {
+        let (file, _) = self.file_range(span)?;
+        let line = self.tcx.sess.source_map().lookup_char_pos(span.lo()).line;
+        Some(LinkFromSrc::Local { file, line })
+    }
+
+    fn def_target(&self, def_id: DefId) -> Option<LinkFromSrc> {
+        if def_id.is_local() {
+            self.local_target(self.tcx.def_span(def_id))
+        } else {
+            Some(LinkFromSrc::External(def_id))
+        }
+    }
+
+    /// Modules written in their own file link to the start of that file, other modules to their
+    /// declaration.
+    fn module_target(&self, def_id: LocalDefId) -> Option<LinkFromSrc> {
+        let (module, span, _) = self.tcx.hir().get_module(def_id);
+        if span.contains(module.inner) {
+            self.local_target(span)
+        } else {
+            let (file, _) = self.file_range(module.inner)?;
+            Some(LinkFromSrc::Local { file, line: 1 })
+        }
+    }
+
+    fn res_target(&self, res: Res) -> Option<LinkFromSrc> {
+        match res {
+            Res::Def(DefKind::Mod, def_id) if def_id.is_local() => {
+                self.module_target(def_id.expect_local())
+            }
+            Res::Def(_, def_id) => self.def_target(def_id),
+            Res::Local(hir_id) => self.local_target(self.tcx.hir().span(hir_id)),
+            _ => None,
+        }
+    }
+
+    fn link(&mut self, span: Span, target: Option<LinkFromSrc>) {
+        if let (Some((file, range)), Some(target)) = (self.file_range(span), target) {
+            self.links.entry(file).or_default().push((range, target));
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for SpanMapVisitor<'tcx> {
+    type Map = Map<'tcx>;
+
+    fn nested_visit_map(&mut self) -> NestedVisitorMap<Self::Map> {
+        NestedVisitorMap::OnlyBodies(self.tcx.hir())
+    }
+
+    fn visit_nested_body(&mut self, id: hir::BodyId) {
+        let old_tables = self.tables.replace(self.tcx.body_tables(id));
+        let body = self.tcx.hir().body(id);
+        self.visit_body(body);
+        self.tables = old_tables;
+    }
+
+    fn visit_path(&mut self, path: &'tcx hir::Path<'tcx>, _id: HirId) {
+        if let Some((last, segments)) = path.segments.split_last() {
+            for segment in segments {
+                if let Some(res) = segment.res {
+                    self.link(segment.ident.span, self.res_target(res));
+                }
+            }
+            self.link(last.ident.span, self.res_target(last.res.unwrap_or(path.res)));
+        }
+        intravisit::walk_path(self, path);
+    }
+
+    fn visit_item(&mut self, item: &'tcx hir::Item<'tcx>) {
+        // `mod foo;` links to the file the module is written in.
+        if let hir::ItemKind::Mod(ref module) = item.kind {
+            if !item.span.contains(module.inner) {
+                let def_id = self.tcx.hir().local_def_id(item.hir_id);
+                self.link(item.ident.span, self.module_target(def_id));
+            }
+        }
+        intravisit::walk_item(self, item);
+    }
+
+    fn visit_expr(&mut self, ex: &'tcx hir::Expr<'tcx>) {
+        if let Some(tables) = self.tables {
+            match ex.kind {
+                hir::ExprKind::MethodCall(segment, ..) => {
+                    if let Some(def_id) = tables.type_dependent_def_id(ex.hir_id) {
+                        self.link(segment.ident.span, self.def_target(def_id));
+                    }
+                }
+                // Paths like `Vec::new`, which are only resolved during type-checking.
+                hir::ExprKind::Path(ref qpath) => {
+                    if let hir::QPath::TypeRelative(_, segment) = qpath {
+                        let res = tables.qpath_res(qpath, ex.hir_id);
+                        self.link(segment.ident.span, self.res_target(res));
+                    }
+                }
+                _ => {}
+            }
+        }
+        intravisit::walk_expr(self, ex);
+    }
+}
+
+/// Collects the links of the source files of the local crate.
+crate fn collect(tcx: TyCtxt<'_>) -> SpanMap {
+    let mut visitor = SpanMapVisitor { tcx, tables: None, links: SpanMap::default() };
+    tcx.hir().krate().visit_all_item_likes(&mut visitor.as_deep_visitor());
+
+    // The same path can be visited more than once, for example when a `use` imports a name in
+    // several namespaces.
+    let mut links = visitor.links;
+    for file_links in links.values_mut() {
+        file_links.sort_by_key(|(range, _)| range.start);
+        file_links.dedup_by_key(|(range, _)| range.start);
+    }
+    links
+}
@@ -1,14 +1,19 @@
 use crate::clean;
 use crate::docfs::PathError;
 use crate::fold::DocFolder;
+use crate::html::format::href;
 use crate::html::format::Buffer;
 use crate::html::highlight;
 use crate::html::layout;
-use crate::html::render::{Error, SharedContext, BASIC_KEYWORDS};
+use crate::html::render::{cache, Error, LinkFromSrc, SharedContext};
+use crate::html::render::{BASIC_KEYWORDS, CURRENT_DEPTH};
 use rustc_hir::def_id::LOCAL_CRATE;
 use rustc_span::source_map::FileName;
+use std::collections::BTreeSet;
 use std::ffi::OsStr;
 use std::fs;
+use std::iter;
+use std::ops::Range;
 use std::path::{Component, Path, PathBuf};
 
 crate fn render(
@@ -19,8 +24,30 @@ crate fn render(
     info!("emitting source files");
     let dst = dst.join("src").join(&krate.name);
     scx.ensure_dir(&dst)?;
-    let mut folder = SourceCollector { dst, scx };
-    Ok(folder.fold_crate(krate))
+    let mut folder = SourceCollector { dst, krate_name: krate.name.clone(), scx };
+    let krate = folder.fold_crate(krate);
+
+    // The identifiers of the source pages can link to files without any documented item in them,
+    // such as the files of private modules, which need to be rendered as well.
+    let cache = cache();
+    let files: BTreeSet<&PathBuf> = cache
+        .span_map
+        .iter()
+        .flat_map(|(file, links)| {
+            let targets = links.iter().filter_map(|(_, target)| match target {
+                LinkFromSrc::Local { file, .. } => Some(file),
+                LinkFromSrc::External(_) => None,
+            });
+            iter::once(file).chain(targets)
+        })
+        .collect();
+    for file in files {
+        if !folder.scx.include_sources {
+            break;
+        }
+        folder.emit_source_or_skip(&FileName::Real(file.clone()));
+    }
+    Ok(krate)
 }
 
 /// Helper struct to render all source code to HTML pages
@@ -29,6 +56,9 @@ struct SourceCollector<'a> {
 
     /// Root destination to place all HTML output into
     dst: PathBuf,
+
+    /// Name of the crate whose sources are rendered
+    krate_name: String,
 }
 
 impl<'a> DocFolder for SourceCollector<'a> {
@@ -41,29 +71,34 @@ impl<'a> DocFolder for SourceCollector<'a> {
             // skip non-local files
             && item.source.cnum == LOCAL_CRATE
         {
-            // If it turns out that we couldn't read this file, then we probably
-            // can't read any of the files (generating html output from json or
-            // something like that), so just don't include sources for the
-            // entire crate. The other option is maintaining this mapping on a
-            // per-file basis, but that's probably not worth it...
-            self.scx.include_sources = match self.emit_source(&item.source.filename) {
-                Ok(()) => true,
-                Err(e) => {
-                    println!(
-                        "warning: source code was requested to be rendered, \
-                              but processing `{}` had an error: {}",
-                        item.source.filename, e
-                    );
-                    println!("         skipping rendering of source code");
-                    false
-                }
-            };
+            self.emit_source_or_skip(&item.source.filename);
         }
         self.fold_item_recur(item)
     }
 }
 
 impl<'a> SourceCollector<'a> {
+    /// Renders the given filename, or stops rendering source files if it can't be read.
+    fn emit_source_or_skip(&mut self, filename: &FileName) {
+        // If it turns out that we couldn't read this file, then we probably
+        // can't read any of the files (generating html output from json or
+        // something like that), so just don't include sources for the
+        // entire crate. The other option is maintaining this mapping on a
+        // per-file basis, but that's probably not worth it...
+        self.scx.include_sources = match self.emit_source(filename) {
+            Ok(()) => true,
+            Err(e) => {
+                println!(
+                    "warning: source code was requested to be rendered, \
+                          but processing `{}` had an error: {}",
+                    filename, e
+                );
+                println!("         skipping rendering of source code");
+                false
+            }
+        };
+    }
+
     /// Renders the given filename into its corresponding HTML source file.
     fn emit_source(&mut self, filename: &FileName) -> Result<(), Error> {
         let p = match *filename {
@@ -83,8 +118,8 @@ impl<'a> SourceCollector<'a> {
         };
 
         // Remove the utf-8 BOM if any
-        let contents =
-            if contents.starts_with("\u{feff}") { &contents[3..] } else { &contents[..] };
+        let bom_len = if contents.starts_with("\u{feff}") { 3 } else { 0 };
+        let contents = &contents[bom_len..];
 
         // Create the intermediate directories
         let mut cur = self.dst.clone();
@@ -101,6 +136,7 @@ impl<'a> SourceCollector<'a> {
         fname.push(".html");
         cur.push(&fname);
         href.push_str(&fname.to_string_lossy());
+        let links = self.links(p, &root_path);
 
         let title = format!(
             "{} -- source",
@@ -122,13 +158,54 @@ impl<'a> SourceCollector<'a> {
             &self.scx.layout,
             &page,
             "",
-            |buf: &mut _| print_src(buf, &contents),
+            |buf: &mut _| print_src(buf, &contents, &links),
             &self.scx.themes,
         );
         self.scx.fs.write(&cur, v.as_bytes())?;
         self.scx.local_sources.insert(p.clone(), href);
         Ok(())
     }
+
+    /// Returns the links of the identifiers of the source file `file`, as the byte ranges of the
+    /// identifiers in the rendered contents and the URLs they link to from a page at `root_path`.
+    /// Like the contents, the ranges don't include the BOM of the file.
+    fn links(&self, file: &Path, root_path: &str) -> Vec<(Range<u32>, String)> {
+        let cache = cache();
+        let links = match cache.span_map.get(file) {
+            Some(links) => links,
+            None => return Vec::new(),
+        };
+
+        // `href` gives the URLs of items relative to the current depth, which is the number of
+        // `../` in the root path.
+        CURRENT_DEPTH.with(|depth| depth.set(root_path.len() / 3));
+        let links = links
+            .iter()
+            .filter_map(|(range, target)| {
+                let url = match *target {
+                    LinkFromSrc::Local { ref file, line } => {
+                        format!("{}{}#{}", root_path, self.src_href(file)?, line)
+                    }
+                    LinkFromSrc::External(def_id) => href(def_id)?.0,
+                };
+                Some((range.clone(), url))
+            })
+            .collect();
+        CURRENT_DEPTH.with(|depth| depth.set(0));
+        links
+    }
+
+    /// Returns the URL of the rendered source file `file`, relative to the root of the output.
+    fn src_href(&self, file: &Path) -> Option<String> {
+        let mut href = format!("src/{}/", self.krate_name);
+        clean_path(&self.scx.src_root, file, false, |component| {
+            href.push_str(&component.to_string_lossy());
+            href.push('/');
+        });
+        href.push_str(&file.file_name()?.to_string_lossy());
+        href.push_str(".html");
+        Some(href)
+    }
 }
 
 /// Takes a path to a source file and cleans the path to it. This canonicalizes
@@ -160,7 +237,7 @@ where
 
 /// Wrapper struct to render the source code of a file. This will do things like
 /// adding line numbers to the left-hand side.
-fn print_src(buf: &mut Buffer, s: &str) {
+fn print_src(buf: &mut Buffer, s: &str, links: &[(Range<u32>, String)]) {
     let lines = s.lines().count();
     let mut cols = 0;
     let mut tmp = lines;
@@ -173,5 +250,5 @@ fn print_src(buf: &mut Buffer, s: &str) {
         write!(buf, "<span id=\"{0}\">{0:1$}</span>\n", i, cols);
     }
     write!(buf, "</pre>");
-    write!(buf, "{}", highlight::render_with_highlighting(s, None, None, None));
+    write!(buf, "{}", highlight::render_with_links(s, None, None, None, links));
 }
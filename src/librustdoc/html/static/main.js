@@ -1,5 +1,5 @@
 // From rust:
-/* global ALIASES, currentCrate, rootPath */
+/* global ALIASES, currentCrate, resourcesSuffix, rootPath */
 
 // Local js definitions:
 /* global addClass, getCurrentValue, hasClass */
@@ -522,7 +522,9 @@ function getSearchElement() {
         return s1_len + s2_len;
     }
 
-    function initSearch(rawSearchIndex) {
+    // `searchCrates` maps the documented crates to the summary of their documentation. Their search
+    // indexes are only loaded when they are searched.
+    function initSearch(searchCrates) {
         var MAX_LEV_DISTANCE = 3;
         var MAX_RESULTS = 200;
         var GENERICS_DATA = 1;
@@ -532,6 +534,11 @@ function getSearchElement() {
         var NO_TYPE_FILTER = -1;
         var currentResults, index, searchIndex;
         var ALIASES = {};
+        // The search indexes of the crates that were loaded, and the crates whose search index
+        // was requested.
+        var rawSearchIndex = {};
+        var requestedCrates = {};
+        var loadingCrates = 0;
         var params = getQueryStringParams();
 
         // Populate search bar with query string search term when provided,
@@ -896,6 +903,266 @@ function getSearchElement() {
                 return literalSearch === true ? false : lev_distance;
             }
 
+            // Parses a type signature query, such as `Vec<T>, usize -> Option<T>`.
+            //
+            // Types are parsed into objects with a `name` and `args` (the type arguments).
+            // References, raw pointers, slices, arrays and tuples are named `&`, `&mut`,
+            // `*const`, `*mut`, `[]` and `()`, like in the search index. `_` is parsed as a
+            // wildcard, which matches any type. Single uppercase letters, optionally followed
+            // by digits (like `T` or `U2`), are type parameters: they match any type, but all
+            // the occurrences of a type parameter have to match the same type.
+            //
+            // Returns the `inputs` and the `output` of the query, which are `null` when any
+            // inputs or output are accepted, or `null` if the query couldn't be parsed.
+            function parseSignatureQuery(raw) {
+                var arrow = raw.indexOf("->");
+                var inputs = raw.substring(0, arrow).trim();
+                var output = raw.substring(arrow + 2).trim();
+
+                function tokenize(s) {
+                    // The empty token marks the end of the query.
+                    return (s.match(/[A-Za-z0-9_]+|::|[&*\[\]();,<>!]/g) || []).concat([""]);
+                }
+
+                function parseTypeList(tokens, pos, close) {
+                    var list = [];
+                    if (tokens[pos.i] === close) {
+                        pos.i += 1;
+                        return list;
+                    }
+                    while (true) {
+                        var type = parseType(tokens, pos);
+                        if (type === null) {
+                            return null;
+                        }
+                        list.push(type);
+                        if (tokens[pos.i] === close) {
+                            pos.i += 1;
+                            return list;
+                        } else if (tokens[pos.i] !== ",") {
+                            return null;
+                        }
+                        pos.i += 1;
+                    }
+                }
+
+                function parseType(tokens, pos) {
+                    var token = tokens[pos.i];
+                    var inner;
+                    pos.i += 1;
+                    if (token === "&" || token === "*") {
+                        var name = token === "&" ? "&" : "*const";
+                        if (tokens[pos.i] === "mut") {
+                            name = token + "mut";
+                            pos.i += 1;
+                        } else if (token === "*" && tokens[pos.i] === "const") {
+                            pos.i += 1;
+                        }
+                        inner = parseType(tokens, pos);
+                        return inner === null ? null : {name: name, args: [inner]};
+                    } else if (token === "[") {
+                        inner = parseType(tokens, pos);
+                        // Skip the length of arrays, which isn't part of the search index.
+                        while (tokens[pos.i] !== "]" && tokens[pos.i] !== "") {
+                            pos.i += 1;
+                        }
+                        if (inner === null || tokens[pos.i] !== "]") {
+                            return null;
+                        }
+                        pos.i += 1;
+                        return {name: "[]", args: [inner]};
+                    } else if (token === "(") {
+                        var elems = parseTypeList(tokens, pos, ")");
+                        return elems === null ? null : {name: "()", args: elems};
+                    } else if (token === "!") {
+                        return {name: "!", args: []};
+                    } else if (token === "_") {
+                        return {wildcard: true};
+                    } else if (!/^[A-Za-z0-9_]+$/.test(token)) {
+                        return null;
+                    }
+                    var isPath = false;
+                    // Only the last segment of paths is part of the search index.
+                    while (tokens[pos.i] === "::" && /^[A-Za-z0-9_]+$/.test(tokens[pos.i + 1])) {
+                        token = tokens[pos.i + 1];
+                        isPath = true;
+                        pos.i += 2;
+                    }
+                    if (!isPath && /^[A-Z][0-9]*$/.test(token)) {
+                        return {generic: token};
+                    }
+                    var args = [];
+                    if (tokens[pos.i] === "<") {
+                        pos.i += 1;
+                        args = parseTypeList(tokens, pos, ">");
+                        if (args === null) {
+                            return null;
+                        }
+                    }
+                    return {name: token.toLowerCase(), args: args};
+                }
+
+                var parsedInputs = null;
+                if (inputs !== "" && inputs !== "*") {
+                    parsedInputs = parseTypeList(tokenize(inputs), {i: 0}, "");
+                    if (parsedInputs === null) {
+                        return null;
+                    }
+                }
+                var parsedOutput = null;
+                if (output === "") {
+                    // Functions that don't return anything return `()`.
+                    parsedOutput = {name: "()", args: []};
+                } else if (output !== "*") {
+                    var tokens = tokenize(output);
+                    var pos = {i: 0};
+                    parsedOutput = parseType(tokens, pos);
+                    if (parsedOutput === null || tokens[pos.i] !== "") {
+                        return null;
+                    }
+                }
+                return {inputs: parsedInputs, output: parsedOutput};
+            }
+
+            function copyBindings(bindings) {
+                var copy = {query: {}, index: {}};
+                var key;
+                for (key in bindings.query) {
+                    if (bindings.query.hasOwnProperty(key)) {
+                        copy.query[key] = bindings.query[key];
+                    }
+                }
+                for (key in bindings.index) {
+                    if (bindings.index.hasOwnProperty(key)) {
+                        copy.index[key] = bindings.index[key];
+                    }
+                }
+                return copy;
+            }
+
+            // Binds the type parameter `param` to the type `type`, which is given as a string,
+            // unless it is already bound to another type.
+            function bindType(bindings, param, type, cost) {
+                if (!bindings.hasOwnProperty(param)) {
+                    bindings[param] = type;
+                    return cost;
+                }
+                return bindings[param] === type ? cost : -1;
+            }
+
+            // Checks if the type `query` of a query matches the type `type` of the search index.
+            //
+            // In the search index, type parameters are numbers, and the types that couldn't be
+            // indexed are `null`. Other types are arrays of their name and, if any, of their type
+            // arguments.
+            //
+            // `bindings` are the types the type parameters of the query and of the search index
+            // are bound to, and are updated with the ones of the match. Returns how well the
+            // types match, 0 being the best, or -1 if they don't.
+            function unifyTypes(query, type, bindings) {
+                if (query.wildcard === true) {
+                    return 0;
+                }
+                if (type === null) {
+                    // We don't know the type, so it may match, but it isn't a good match.
+                    return 2;
+                }
+                if (query.generic !== undefined) {
+                    return bindType(bindings.query, query.generic, "i" + JSON.stringify(type),
+                                    typeof type === "number" ? 0 : 1);
+                }
+                if (typeof type === "number") {
+                    // A type parameter of the function can be any type of the query.
+                    return bindType(bindings.index, type, "q" + JSON.stringify(query), 1);
+                }
+                var args = type.length > 1 ? type[1] : [];
+                var cost;
+                if (type[0] !== query.name) {
+                    if (query.name === "&" && type[0] === "&mut") {
+                        // `&mut T` can be used where `&T` is expected.
+                        cost = unifyTypeArgs(query, args, bindings);
+                    } else if (type[0] === "&" || type[0] === "&mut") {
+                        // A reference to a type can be used much like the type itself.
+                        cost = unifyTypes(query, args[0], bindings);
+                    } else {
+                        return -1;
+                    }
+                    return cost < 0 ? -1 : cost + 1;
+                }
+                return unifyTypeArgs(query, args, bindings);
+            }
+
+            function unifyTypeArgs(query, args, bindings) {
+                // The type arguments of the query can be left out, but only the trailing ones.
+                // Tuples have to have the same number of elements.
+                if (query.args.length > args.length ||
+                        (query.name === "()" && query.args.length !== args.length)) {
+                    return -1;
+                }
+                var total = 0;
+                for (var i = 0; i < query.args.length; ++i) {
+                    var cost = unifyTypes(query.args[i], args[i], bindings);
+                    if (cost < 0) {
+                        return -1;
+                    }
+                    total += cost;
+                }
+                return total;
+            }
+
+            // Finds the best way to match each of the `queries` with a different type of `types`,
+            // in any order. Returns the cost of the match, or -1 if there isn't any.
+            function unifyTypeSets(queries, types, used, bindings) {
+                if (queries.length === 0) {
+                    return 0;
+                }
+                var best = -1;
+                for (var i = 0; i < types.length; ++i) {
+                    if (used[i] === true) {
+                        continue;
+                    }
+                    var newBindings = copyBindings(bindings);
+                    var cost = unifyTypes(queries[0], types[i], newBindings);
+                    if (cost < 0) {
+                        continue;
+                    }
+                    used[i] = true;
+                    var rest = unifyTypeSets(queries.slice(1), types, used, newBindings);
+                    used[i] = false;
+                    if (rest >= 0 && (best < 0 || cost + rest < best)) {
+                        best = cost + rest;
+                    }
+                }
+                return best;
+            }
+
+            // Checks if a function matches a type signature query, as returned by
+            // `parseSignatureQuery`. `sig` is the signature of the function in the search index:
+            // its inputs and, if it returns anything, its output. Returns how well the function
+            // matches, 0 being the best, or -1 if it doesn't.
+            function checkSignature(sig, query) {
+                var bindings = {query: {}, index: {}};
+                var cost = 0;
+                if (query.output !== null) {
+                    cost = unifyTypes(query.output, sig.length > 1 ? sig[1] : ["()"], bindings);
+                    if (cost < 0) {
+                        return -1;
+                    }
+                }
+                if (query.inputs === null) {
+                    return cost;
+                }
+                if (query.inputs.length > sig[0].length) {
+                    return -1;
+                }
+                var inputsCost = unifyTypeSets(query.inputs, sig[0], [], bindings);
+                if (inputsCost < 0) {
+                    return -1;
+                }
+                // Functions taking more arguments than the query gives are less relevant.
+                return cost + inputsCost + sig[0].length - query.inputs.length;
+            }
+
             function checkPath(contains, lastElem, ty) {
                 if (contains.length === 0) {
                     return 0;
@@ -1079,70 +1346,40 @@ function getSearchElement() {
                 query.search = val;
             // searching by type
             } else if (val.search("->") > -1) {
-                var trimmer = function(s) { return s.trim(); };
-                var parts = val.split("->").map(trimmer);
-                var input = parts[0];
-                // sort inputs so that order does not matter
-                var inputs = input.split(",").map(trimmer).sort();
-                for (i = 0; i < inputs.length; ++i) {
-                    inputs[i] = extractGenerics(inputs[i]);
-                }
-                var output = extractGenerics(parts[1]);
+                var signature = parseSignatureQuery(query.query);
+                var makeResult = function(id, lev) {
+                    return {
+                        id: id,
+                        index: -1,
+                        lev: lev,
+                        dontValidate: true,
+                    };
+                };
 
-                for (i = 0; i < nSearchWords; ++i) {
-                    if (filterCrates !== undefined && searchIndex[i].crate !== filterCrates) {
+                for (i = 0; signature !== null && i < nSearchWords; ++i) {
+                    ty = searchIndex[i];
+                    if (!ty.sig || (filterCrates !== undefined && ty.crate !== filterCrates) ||
+                            typePassesFilter(typeFilter, ty.ty) === false) {
                         continue;
                     }
-                    var type = searchIndex[i].type;
-                    ty = searchIndex[i];
-                    if (!type) {
+                    var cost = checkSignature(ty.sig, signature);
+                    if (cost < 0) {
                         continue;
                     }
                     fullId = generateId(ty);
-
-                    // allow searching for void (no output) functions as well
-                    var typeOutput = type.length > OUTPUT_DATA ? type[OUTPUT_DATA].name : "";
-                    returned = checkReturned(ty, output, true, NO_TYPE_FILTER);
-                    if (output.name === "*" || returned === true) {
-                        in_args = false;
-                        var is_module = false;
-
-                        if (input === "*") {
-                            is_module = true;
-                        } else {
-                            var allFound = true;
-                            for (var it = 0; allFound === true && it < inputs.length; it++) {
-                                allFound = checkType(type, inputs[it], true);
-                            }
-                            in_args = allFound;
-                        }
-                        if (in_args === true) {
-                            results_in_args[fullId] = {
-                                id: i,
-                                index: -1,
-                                dontValidate: true,
-                            };
-                        }
-                        if (returned === true) {
-                            results_returned[fullId] = {
-                                id: i,
-                                index: -1,
-                                dontValidate: true,
-                            };
-                        }
-                        if (is_module === true) {
-                            results[fullId] = {
-                                id: i,
-                                index: -1,
-                                dontValidate: true,
-                            };
-                        }
+                    if (signature.inputs !== null) {
+                        results_in_args[fullId] = makeResult(i, cost);
+                    } else {
+                        results[fullId] = makeResult(i, cost);
+                    }
+                    if (signature.output !== null) {
+                        results_returned[fullId] = makeResult(i, cost);
                     }
                 }
-                query.inputs = inputs.map(function(input) {
-                    return input.name;
+                query.inputs = val.split("->")[0].split(",").map(function(input) {
+                    return input.trim();
                 });
-                query.output = output.name;
+                query.output = val.split("->")[1].trim();
             } else {
                 query.inputs = [val];
                 query.output = val;
@@ -1627,7 +1864,8 @@ function getSearchElement() {
                 return ret;
             }
 
-            var queries = query.raw.split(",");
+            // Commas separate the arguments of type signature queries.
+            var queries = query.raw.indexOf("->") > -1 ? [query.raw] : query.raw.split(",");
             var results = {
                 "in_args": [],
                 "returned": [],
@@ -1661,7 +1899,7 @@ function getSearchElement() {
         function getFilterCrates() {
             var elem = document.getElementById("crate-search");
 
-            if (elem && elem.value !== "All crates" && rawSearchIndex.hasOwnProperty(elem.value)) {
+            if (elem && elem.value !== "All crates" && searchCrates.hasOwnProperty(elem.value)) {
                 return elem.value;
             }
             return undefined;
@@ -1699,9 +1937,49 @@ function getSearchElement() {
             }
 
             var filterCrates = getFilterCrates();
+            if (loadSearchIndexes(filterCrates) === false) {
+                var searchElem = getSearchElement();
+                searchElem.innerHTML = "<h1>Loading search results...</h1>";
+                showSearchResults(searchElem);
+                return;
+            }
             showResults(execSearch(query, index, filterCrates));
         }
 
+        // Starts loading the search indexes of the searched crates that weren't loaded yet. Returns
+        // `false` if some of them are still loading, in which case the search is run again once
+        // they are loaded.
+        function loadSearchIndexes(filterCrates) {
+            var crates = filterCrates !== undefined ? [filterCrates] : Object.keys(searchCrates);
+            for (var i = 0; i < crates.length; ++i) {
+                if (requestedCrates[crates[i]] === true) {
+                    continue;
+                }
+                requestedCrates[crates[i]] = true;
+                loadingCrates += 1;
+                var script = document.createElement("script");
+                script.src = rootPath + "search-index/" + crates[i] + resourcesSuffix + ".js";
+                // The crate is searched without its items if its search index can't be loaded.
+                script.onerror = searchIndexLoaded;
+                document.head.appendChild(script);
+            }
+            return loadingCrates === 0;
+        }
+
+        function searchIndexLoaded() {
+            loadingCrates -= 1;
+            if (loadingCrates === 0) {
+                index = buildIndex(rawSearchIndex);
+                search(undefined, true);
+            }
+        }
+
+        // Called by the search index file of each crate once it is loaded.
+        window.searchIndexShardLoaded = function(crate, shard) {
+            rawSearchIndex[crate] = shard;
+            searchIndexLoaded();
+        };
+
         function buildIndex(rawSearchIndex) {
             searchIndex = [];
             var searchWords = [];
@@ -1726,22 +2004,30 @@ function getSearchElement() {
 
                 // an array of [(Number) item type,
                 //              (String) name,
-                //              (String) full path or empty string for previous path,
+                //              (Number) the full path index to `modulePaths`,
                 //              (String) description,
                 //              (Number | null) the parent path index to `paths`]
-                //              (Object | null) the type of the function (if any)
+                //              (Number | null) the function type index to `types` (if any)
+                //              (Number) the signature index to `signatures` (if any)
                 var items = rawSearchIndex[crate].i;
                 // an array of [(Number) item type,
                 //              (String) name]
-                var paths = rawSearchIndex[crate].p;
+                var rawPaths = rawSearchIndex[crate].p;
+                // an array of (String) full paths
+                var modulePaths = rawSearchIndex[crate].m;
+                // an array of (Object | null) the types of the functions
+                var types = rawSearchIndex[crate].f;
+                // an array of (Array) the full signatures of the functions
+                var signatures = rawSearchIndex[crate].s;
                 // a array of [(String) alias name
                 //             [Number] index to items]
                 var aliases = rawSearchIndex[crate].a;
 
                 // convert `rawPaths` entries into object form
-                var len = paths.length;
+                var len = rawPaths.length;
+                var paths = [];
                 for (i = 0; i < len; ++i) {
-                    paths[i] = {ty: paths[i][0], name: paths[i][1]};
+                    paths.push({ty: rawPaths[i][0], name: rawPaths[i][1]});
                 }
 
                 // convert `items` into an object form, and construct word indices.
@@ -1752,20 +2038,17 @@ function getSearchElement() {
                 // all other search operations have access to this cached data for
                 // faster analysis operations
                 len = items.length;
-                var lastPath = "";
                 for (i = 0; i < len; ++i) {
                     var rawRow = items[i];
-                    if (!rawRow[2]) {
-                        rawRow[2] = lastPath;
-                    }
                     var row = {
                         crate: crate,
                         ty: rawRow[0],
                         name: rawRow[1],
-                        path: rawRow[2],
+                        path: modulePaths[rawRow[2]],
                         desc: rawRow[3],
                         parent: paths[rawRow[4]],
-                        type: rawRow[5],
+                        type: rawRow[5] === null ? null : types[rawRow[5]],
+                        sig: rawRow.length > 6 ? signatures[rawRow[6]] : undefined,
                     };
                     searchIndex.push(row);
                     if (typeof row.name === "string") {
@@ -1774,7 +2057,6 @@ function getSearchElement() {
                     } else {
                         searchWords.push("");
                     }
-                    lastPath = row.path;
                     crateSize += 1;
                 }
 
@@ -1892,8 +2174,8 @@ function getSearchElement() {
                 div.appendChild(ul);
 
                 var crates = [];
-                for (var crate in rawSearchIndex) {
-                    if (!rawSearchIndex.hasOwnProperty(crate)) {
+                for (var crate in searchCrates) {
+                    if (!searchCrates.hasOwnProperty(crate)) {
                         continue;
                     }
                     crates.push(crate);
@@ -1906,7 +2188,7 @@ function getSearchElement() {
                     }
                     var link = document.createElement("a");
                     link.href = rootPath + crates[i] + "/index.html";
-                    link.title = rawSearchIndex[crates[i]].doc;
+                    link.title = searchCrates[crates[i]];
                     link.className = klass;
                     link.textContent = crates[i];
 
@@ -2755,6 +3037,8 @@ function getSearchElement() {
              and <code>const</code>.",
             "Search functions by type signature (e.g., <code>vec -&gt; usize</code> or \
              <code>* -&gt; vec</code>)",
+            "Type signatures can use type parameters, which are single uppercase letters: \
+             <code>Vec&lt;T&gt;, usize -&gt; Option&lt;T&gt;</code>",
             "Search multiple things at once by splitting your query with comma (e.g., \
              <code>str,u8</code> or <code>String,struct:Vec,test</code>)",
             "You can look for items with an exact name by putting double quotes around \
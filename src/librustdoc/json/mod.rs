@@ -0,0 +1,307 @@
+//! Rustdoc's JSON backend
+//!
+//! This module contains the logic for rendering a crate as JSON rather than the normal static HTML
+//! output. The schema of the output is described by the types of the `types` module, and the
+//! conversions from the `clean` types to them live in `conversions`.
+
+mod conversions;
+mod types;
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use rustc_ast_pretty::pprust;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::def_id::DefId;
+use rustc_span::edition::Edition;
+
+use crate::clean;
+use crate::config::{OutputFormat, RenderOptions};
+use crate::docfs::PathError;
+use crate::formats::FormatRenderer;
+use crate::html::render::{Cache, Error, ExternalLocation, SearchIndex};
+
+use self::types::{Id, ItemEnum};
+
+/// Collects the items of a crate into the index of the JSON output, which is written as a single
+/// file, `<crate name>.json`, in the output directory.
+crate struct JsonRenderer {
+    dst: PathBuf,
+    cache: Arc<Cache>,
+    includes_private: bool,
+    /// The id of the root module of the crate.
+    root: Option<Id>,
+    /// All the items that are documented, by id. Items refer to each other through their id,
+    /// instead of nesting the items they contain.
+    index: BTreeMap<Id, types::Item>,
+    /// How many of the modules containing the current one were stripped from the
+    /// documentation. Their items aren't documented.
+    stripped_depth: usize,
+}
+
+impl FormatRenderer for JsonRenderer {
+    fn init(
+        krate: clean::Crate,
+        options: RenderOptions,
+        _output_format: OutputFormat,
+        _edition: Edition,
+        cache: Arc<Cache>,
+        _search_index: SearchIndex,
+        _diag: &rustc_errors::Handler,
+    ) -> Result<(Self, clean::Crate), Error> {
+        let renderer = JsonRenderer {
+            dst: options.output,
+            cache,
+            includes_private: options.document_private,
+            root: krate.module.as_ref().map(|module| module.def_id.into()),
+            index: BTreeMap::new(),
+            stripped_depth: 0,
+        };
+        Ok((renderer, krate))
+    }
+
+    fn item(&mut self, item: clean::Item) -> Result<(), Error> {
+        if self.stripped_depth == 0 {
+            self.insert(item);
+        }
+        Ok(())
+    }
+
+    fn mod_item_in(&mut self, item: &clean::Item, _item_name: &str) -> Result<(), Error> {
+        if item.is_stripped() || self.stripped_depth > 0 {
+            self.stripped_depth += 1;
+        } else {
+            self.insert(item.clone());
+        }
+        Ok(())
+    }
+
+    fn mod_item_out(&mut self, _item_name: &str) -> Result<(), Error> {
+        self.stripped_depth = self.stripped_depth.saturating_sub(1);
+        Ok(())
+    }
+
+    fn after_krate(
+        &mut self,
+        krate: &clean::Crate,
+        _diag: &rustc_errors::Handler,
+    ) -> Result<(), Error> {
+        // Implementations were moved out of the crate into the cache when it was built.
+        let cache = self.cache.clone();
+        let mut seen_impls = FxHashSet::default();
+        for impl_ in cache.impls.values().chain(cache.implementors.values()).flatten() {
+            if seen_impls.insert(impl_.impl_item.def_id) {
+                self.insert(impl_.impl_item.clone());
+            }
+        }
+
+        let paths = cache
+            .paths
+            .iter()
+            .chain(cache.external_paths.iter())
+            .map(|(&did, &(ref path, kind))| {
+                let summary = types::ItemSummary {
+                    crate_id: did.krate.as_u32(),
+                    path: path.clone(),
+                    kind: kind.into(),
+                };
+                (did.into(), summary)
+            })
+            .collect();
+
+        let external_crates = cache
+            .extern_locations
+            .iter()
+            .map(|(krate, &(ref name, _, ref location))| {
+                let html_root_url = match location {
+                    ExternalLocation::Remote(url) => Some(url.clone()),
+                    _ => None,
+                };
+                (krate.as_u32(), types::ExternalCrate { name: name.clone(), html_root_url })
+            })
+            .collect();
+
+        let root = match self.root.take() {
+            Some(root) => root,
+            None => return Ok(()),
+        };
+        let output_crate = types::Crate {
+            root,
+            crate_version: cache.crate_version.clone(),
+            includes_private: self.includes_private,
+            index: mem::take(&mut self.index),
+            paths,
+            external_crates,
+            format_version: types::FORMAT_VERSION,
+        };
+
+        let path = self.dst.join(format!("{}.json", krate.name));
+        write_crate(&output_crate, &path).map_err(|e| Error::new(e, &path))
+    }
+}
+
+fn write_crate(output_crate: &types::Crate, path: &std::path::Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut file, output_crate)?;
+    file.flush()
+}
+
+impl JsonRenderer {
+    /// Adds `item` and the items it contains to the index. Returns the id of the item, or `None`
+    /// if it was stripped from the documentation.
+    fn insert(&mut self, item: clean::Item) -> Option<Id> {
+        if item.is_stripped() {
+            return None;
+        }
+
+        let id = Id::from(item.def_id);
+        let kind = item.type_().into();
+        let deprecation = item.deprecation().cloned().map(Into::into);
+        let clean::Item { source, name, attrs, inner, visibility, def_id, .. } = item;
+        let inner = self.convert_inner(inner, def_id);
+
+        let json_item = types::Item {
+            id: id.clone(),
+            crate_id: def_id.krate.as_u32(),
+            name,
+            span: conversions::from_span(&source),
+            visibility: visibility.into(),
+            docs: attrs.collapsed_doc_value(),
+            links: attrs
+                .links
+                .iter()
+                .filter_map(|&(ref text, did, _)| did.map(|did| (text.clone(), did.into())))
+                .collect(),
+            attrs: attrs.other_attrs.iter().map(pprust::attribute_to_string).collect(),
+            deprecation,
+            kind,
+            inner,
+        };
+        self.index.insert(id.clone(), json_item);
+        Some(id)
+    }
+
+    /// Adds `items` to the index, and returns the ids of the ones that weren't stripped.
+    fn insert_all(&mut self, items: impl IntoIterator<Item = clean::Item>) -> Vec<Id> {
+        items.into_iter().filter_map(|item| self.insert(item)).collect()
+    }
+
+    fn impls_of(&self, did: DefId) -> Vec<Id> {
+        self.cache
+            .impls
+            .get(&did)
+            .map(|impls| impls.iter().map(|i| i.impl_item.def_id.into()).collect())
+            .unwrap_or_default()
+    }
+
+    fn implementors_of(&self, did: DefId) -> Vec<Id> {
+        self.cache
+            .implementors
+            .get(&did)
+            .map(|impls| impls.iter().map(|i| i.impl_item.def_id.into()).collect())
+            .unwrap_or_default()
+    }
+
+    fn convert_inner(&mut self, inner: clean::ItemEnum, def_id: DefId) -> ItemEnum {
+        use crate::clean::ItemEnum::*;
+        match inner {
+            ModuleItem(m) => {
+                // The named items of the module are given to the renderer on their own, only the
+                // imports have to be added here.
+                let items = m
+                    .items
+                    .into_iter()
+                    .filter_map(|item| {
+                        if item.name.is_none() {
+                            self.insert(item)
+                        } else if item.is_stripped() {
+                            None
+                        } else {
+                            Some(item.def_id.into())
+                        }
+                    })
+                    .collect();
+                ItemEnum::ModuleItem(types::Module { is_crate: m.is_crate, items })
+            }
+            ExternCrateItem(name, rename) => ItemEnum::ExternCrateItem { name, rename },
+            ImportItem(import) => ItemEnum::ImportItem(import.into()),
+            StructItem(s) => ItemEnum::StructItem(types::Struct {
+                struct_type: s.struct_type.into(),
+                generics: s.generics.into(),
+                fields_stripped: s.fields_stripped,
+                fields: self.insert_all(s.fields),
+                impls: self.impls_of(def_id),
+            }),
+            UnionItem(u) => ItemEnum::UnionItem(types::Union {
+                generics: u.generics.into(),
+                fields_stripped: u.fields_stripped,
+                fields: self.insert_all(u.fields),
+                impls: self.impls_of(def_id),
+            }),
+            EnumItem(e) => ItemEnum::EnumItem(types::Enum {
+                generics: e.generics.into(),
+                variants_stripped: e.variants_stripped,
+                variants: self.insert_all(e.variants),
+                impls: self.impls_of(def_id),
+            }),
+            VariantItem(v) => ItemEnum::VariantItem(match v.kind {
+                clean::VariantKind::CLike => types::Variant::Plain,
+                clean::VariantKind::Tuple(tys) => {
+                    types::Variant::Tuple(tys.into_iter().map(Into::into).collect())
+                }
+                clean::VariantKind::Struct(s) => types::Variant::Struct(self.insert_all(s.fields)),
+            }),
+            StructFieldItem(ty) => ItemEnum::StructFieldItem(ty.into()),
+            FunctionItem(f) | ForeignFunctionItem(f) => ItemEnum::FunctionItem(f.into()),
+            TraitItem(t) => ItemEnum::TraitItem(types::Trait {
+                is_auto: t.is_auto,
+                is_unsafe: t.unsafety == rustc_hir::Unsafety::Unsafe,
+                items: self.insert_all(t.items),
+                generics: t.generics.into(),
+                bounds: t.bounds.into_iter().map(Into::into).collect(),
+                implementors: self.implementors_of(def_id),
+            }),
+            TraitAliasItem(t) => ItemEnum::TraitAliasItem(t.into()),
+            MethodItem(m) => ItemEnum::MethodItem(m.into()),
+            TyMethodItem(m) => ItemEnum::MethodItem(m.into()),
+            ImplItem(i) => {
+                let mut provided_trait_methods: Vec<_> =
+                    i.provided_trait_methods.into_iter().collect();
+                provided_trait_methods.sort();
+                ItemEnum::ImplItem(types::Impl {
+                    is_unsafe: i.unsafety == rustc_hir::Unsafety::Unsafe,
+                    generics: i.generics.into(),
+                    provided_trait_methods,
+                    trait_: i.trait_.map(Into::into),
+                    for_: i.for_.into(),
+                    items: self.insert_all(i.items),
+                    negative: i.polarity == Some(clean::ImplPolarity::Negative),
+                    synthetic: i.synthetic,
+                    blanket_impl: i.blanket_impl.map(Into::into),
+                })
+            }
+            TypedefItem(t, _) => ItemEnum::TypedefItem(t.into()),
+            OpaqueTyItem(t, _) => ItemEnum::OpaqueTyItem(t.into()),
+            ConstantItem(c) => ItemEnum::ConstantItem(c.into()),
+            StaticItem(s) | ForeignStaticItem(s) => ItemEnum::StaticItem(s.into()),
+            ForeignTypeItem => ItemEnum::ForeignTypeItem,
+            MacroItem(m) => ItemEnum::MacroItem(m.source),
+            ProcMacroItem(m) => ItemEnum::ProcMacroItem(m.into()),
+            PrimitiveItem(p) => ItemEnum::PrimitiveItem(p.as_str().to_string()),
+            AssocConstItem(ty, default) => ItemEnum::AssocConstItem { type_: ty.into(), default },
+            AssocTypeItem(bounds, default) => ItemEnum::AssocTypeItem {
+                bounds: bounds.into_iter().map(Into::into).collect(),
+                default: default.map(Into::into),
+            },
+            KeywordItem(k) => ItemEnum::KeywordItem(k),
+            StrippedItem(_) => unreachable!("stripped items are not documented"),
+        }
+    }
+}
@@ -57,12 +57,15 @@ use rustc_session::{early_error, early_warn};
 #[macro_use]
 mod externalfiles;
 
+mod api_diff;
 mod clean;
 mod config;
 mod core;
 mod docfs;
 mod doctree;
 mod fold;
+mod formats;
+mod json;
 pub mod html {
     crate mod escape;
     crate mod format;
@@ -77,7 +80,9 @@ pub mod html {
 }
 mod markdown;
 mod passes;
+mod scrape_examples;
 mod test;
+mod text;
 mod theme;
 mod visit_ast;
 mod visit_lib;
@@ -143,7 +148,9 @@ fn opts() -> Vec<RustcOptGroup> {
         stable("r", |o| {
             o.optopt("r", "input-format", "the input type of the specified file", "[rust]")
         }),
-        stable("w", |o| o.optopt("w", "output-format", "the output type to write", "[html]")),
+        stable("w", |o| {
+            o.optopt("w", "output-format", "the output type to write", "[html|json|text|man]")
+        }),
         stable("o", |o| o.optopt("o", "output", "where to place the output", "PATH")),
         stable("crate-name", |o| {
             o.optopt("", "crate-name", "specify the name of this crate", "NAME")
@@ -391,6 +398,23 @@ fn opts() -> Vec<RustcOptGroup> {
                 "calculate percentage of public items with documentation",
             )
         }),
+        unstable("coverage-threshold", |o| {
+            o.optmulti(
+                "",
+                "coverage-threshold",
+                "fail --show-coverage if the percentage of documented items, or of items with \
+                 code examples with the `examples=` prefix, is below this",
+                "[docs=|examples=]PERCENT",
+            )
+        }),
+        unstable("coverage-details", |o| {
+            o.optflag(
+                "",
+                "coverage-details",
+                "with --show-coverage, also print the code example counts and the counts of each \
+                 module",
+            )
+        }),
         unstable("enable-per-target-ignores", |o| {
             o.optflag(
                 "",
@@ -421,6 +445,62 @@ fn opts() -> Vec<RustcOptGroup> {
                 "specified the rustc-like binary to use as the test builder",
             )
         }),
+        unstable("merge-doctests", |o| {
+            o.optflag(
+                "",
+                "merge-doctests",
+                "compile the doctests that allow it into a single binary when running them",
+            )
+        }),
+        unstable("scrape-examples-output-path", |o| {
+            o.optopt(
+                "",
+                "scrape-examples-output-path",
+                "collect the calls this crate makes to the target crates into a file, instead of \
+                 documenting it",
+                "PATH",
+            )
+        }),
+        unstable("scrape-examples-target-crate", |o| {
+            o.optmulti(
+                "",
+                "scrape-examples-target-crate",
+                "the crate to collect calls to, when using --scrape-examples-output-path",
+                "CRATE",
+            )
+        }),
+        unstable("with-examples", |o| {
+            o.optmulti(
+                "",
+                "with-examples",
+                "a file written by --scrape-examples-output-path, whose calls are shown as \
+                 examples of the functions they call",
+                "PATH",
+            )
+        }),
+        unstable("api-diff", |o| {
+            o.optopt(
+                "",
+                "api-diff",
+                "compare the public API of the compiled crate given as input with the one of an \
+                 older version of it, instead of documenting it",
+                "OLD",
+            )
+        }),
+        unstable("check-intra-doc-links", |o| {
+            o.optflag(
+                "",
+                "check-intra-doc-links",
+                "check the intra-doc links of the crate for errors, instead of documenting it",
+            )
+        }),
+        unstable("generate-link-to-definition", |o| {
+            o.optflag(
+                "",
+                "generate-link-to-definition",
+                "make identifiers in the source pages link to their definitions",
+            )
+        }),
     ]
 }
 
@@ -466,10 +546,15 @@ fn main_options(options: config::Options) -> i32 {
     // but we can't crates the Handler ahead of time because it's not Send
     let diag_opts = (options.error_format, options.edition, options.debugging_options.clone());
     let show_coverage = options.show_coverage;
+    let scrape_examples = options.scrape_examples_options.is_some();
+    let api_diff = options.api_diff_options.is_some();
+    let check_intra_doc_links = options.check_intra_doc_links;
+    let output_format = options.output_format;
     rust_input(options, move |out| {
-        if show_coverage {
-            // if we ran coverage, bail early, we don't need to also generate docs at this point
-            // (also we didn't load in any of the useful passes)
+        if show_coverage || scrape_examples || api_diff || check_intra_doc_links {
+            // if we ran coverage, scraped examples, compared two versions of a crate or checked
+            // intra-doc links, bail early, we don't need to also generate docs at this point (also
+            // we didn't load in any of the useful passes)
             return rustc_driver::EXIT_SUCCESS;
         }
 
@@ -477,7 +562,21 @@ fn main_options(options: config::Options) -> i32 {
         info!("going to format");
         let (error_format, edition, debugging_options) = diag_opts;
         let diag = core::new_handler(error_format, None, &debugging_options);
-        match html::render::run(krate, renderopts, renderinfo, &diag, edition) {
+        let format = output_format.unwrap_or(config::OutputFormat::Html);
+        let res = match format {
+            config::OutputFormat::Json => formats::run_format::<json::JsonRenderer>(
+                krate, renderopts, renderinfo, format, edition, &diag,
+            ),
+            config::OutputFormat::Text | config::OutputFormat::Man => {
+                formats::run_format::<text::TextRenderer>(
+                    krate, renderopts, renderinfo, format, edition, &diag,
+                )
+            }
+            config::OutputFormat::Html => formats::run_format::<html::render::Context>(
+                krate, renderopts, renderinfo, format, edition, &diag,
+            ),
+        };
+        match res {
             Ok(_) => rustc_driver::EXIT_SUCCESS,
             Err(e) => {
                 diag.struct_err(&format!("couldn't generate documentation: {}", e.error))
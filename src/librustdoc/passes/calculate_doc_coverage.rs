@@ -2,9 +2,10 @@ use crate::clean;
 use crate::config::OutputFormat;
 use crate::core::DocContext;
 use crate::fold::{self, DocFolder};
-use crate::passes::Pass;
+use crate::passes::{count_doc_tests, Pass};
 
 use rustc_ast::attr;
+use rustc_session::getopts;
 use rustc_span::symbol::sym;
 use rustc_span::FileName;
 use serde::Serialize;
@@ -18,11 +19,79 @@ pub const CALCULATE_DOC_COVERAGE: Pass = Pass {
     description: "counts the number of items with and without documentation",
 };
 
+/// The minimum percentages of documented items and of items with code examples a crate must
+/// reach, below which `--show-coverage` fails.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CoverageThresholds {
+    pub docs: Option<f64>,
+    pub examples: Option<f64>,
+}
+
+impl CoverageThresholds {
+    /// Parses the `--coverage-threshold` flags, each of which is either a percentage of documented
+    /// items, or a percentage prefixed by `docs=` or `examples=`.
+    crate fn new(
+        matches: &getopts::Matches,
+        diag: &rustc_errors::Handler,
+    ) -> Result<CoverageThresholds, i32> {
+        let mut thresholds = CoverageThresholds::default();
+        for threshold in matches.opt_strs("coverage-threshold") {
+            let (slot, percentage) = if threshold.starts_with("examples=") {
+                (&mut thresholds.examples, &threshold["examples=".len()..])
+            } else if threshold.starts_with("docs=") {
+                (&mut thresholds.docs, &threshold["docs=".len()..])
+            } else {
+                (&mut thresholds.docs, &threshold[..])
+            };
+            match percentage.parse::<f64>() {
+                Ok(percentage) if percentage >= 0.0 && percentage <= 100.0 => {
+                    *slot = Some(percentage)
+                }
+                _ => {
+                    diag.struct_err(&format!("invalid coverage threshold `{}`", threshold))
+                        .note(
+                            "expected a percentage between 0 and 100, optionally prefixed by \
+                             `docs=` or `examples=`",
+                        )
+                        .emit();
+                    return Err(1);
+                }
+            }
+        }
+        if (thresholds.docs.is_some() || thresholds.examples.is_some())
+            && !matches.opt_present("show-coverage")
+        {
+            diag.struct_err("must use --show-coverage with --coverage-threshold").emit();
+            return Err(1);
+        }
+        Ok(thresholds)
+    }
+}
+
 fn calculate_doc_coverage(krate: clean::Crate, ctx: &DocContext<'_>) -> clean::Crate {
     let mut calc = CoverageCalculator::new();
     let krate = calc.fold_crate(krate);
 
-    calc.print_results(ctx.renderinfo.borrow().output_format);
+    let renderinfo = ctx.renderinfo.borrow();
+    calc.print_results(renderinfo.output_format, renderinfo.coverage_details);
+
+    let total = calc.total();
+    let thresholds = renderinfo.coverage_thresholds;
+    let checks = [
+        ("documentation", total.total, total.percentage(), thresholds.docs),
+        ("code example", total.total_examples, total.examples_percentage(), thresholds.examples),
+    ];
+    for &(what, count, percentage, threshold) in checks.iter() {
+        // Without any item to count, there is nothing missing.
+        if let Some(threshold) = threshold.filter(|_| count > 0) {
+            if percentage < threshold {
+                ctx.sess().err(&format!(
+                    "{} coverage of {:.1}% is below the threshold of {}%",
+                    what, percentage, threshold
+                ));
+            }
+        }
+    }
 
     krate
 }
@@ -31,22 +100,40 @@ fn calculate_doc_coverage(krate: clean::Crate, ctx: &DocContext<'_>) -> clean::C
 struct ItemCount {
     total: u64,
     with_docs: u64,
+    /// The number of items that are expected to have code examples, which excludes fields,
+    /// variants, constants, statics and type aliases.
+    total_examples: u64,
+    with_examples: u64,
 }
 
 impl ItemCount {
-    fn count_item(&mut self, has_docs: bool) {
+    fn count_item(&mut self, has_docs: bool, should_have_example: bool, has_example: bool) {
         self.total += 1;
 
         if has_docs {
             self.with_docs += 1;
         }
+        if should_have_example {
+            self.total_examples += 1;
+
+            if has_example {
+                self.with_examples += 1;
+            }
+        }
     }
 
-    fn percentage(&self) -> Option<f64> {
-        if self.total > 0 {
-            Some((self.with_docs as f64 * 100.0) / self.total as f64)
+    /// The percentage of documented items, which is 0 when there are no items.
+    fn percentage(&self) -> f64 {
+        if self.total > 0 { (self.with_docs as f64 * 100.0) / self.total as f64 } else { 0.0 }
+    }
+
+    /// The percentage of items with code examples, out of the items expected to have some, which
+    /// is 0 when there are no such items.
+    fn examples_percentage(&self) -> f64 {
+        if self.total_examples > 0 {
+            (self.with_examples as f64 * 100.0) / self.total_examples as f64
         } else {
-            None
+            0.0
         }
     }
 }
@@ -55,7 +142,12 @@ impl ops::Sub for ItemCount {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self {
-        ItemCount { total: self.total - rhs.total, with_docs: self.with_docs - rhs.with_docs }
+        ItemCount {
+            total: self.total - rhs.total,
+            with_docs: self.with_docs - rhs.with_docs,
+            total_examples: self.total_examples - rhs.total_examples,
+            with_examples: self.with_examples - rhs.with_examples,
+        }
     }
 }
 
@@ -63,11 +155,17 @@ impl ops::AddAssign for ItemCount {
     fn add_assign(&mut self, rhs: Self) {
         self.total += rhs.total;
         self.with_docs += rhs.with_docs;
+        self.total_examples += rhs.total_examples;
+        self.with_examples += rhs.with_examples;
     }
 }
 
 struct CoverageCalculator {
     items: BTreeMap<FileName, ItemCount>,
+    /// The same counts as `items`, by module path instead of by file.
+    modules: BTreeMap<String, ItemCount>,
+    /// The path of the module the items being folded are in.
+    module_path: Vec<String>,
 }
 
 fn limit_filename_len(filename: String) -> String {
@@ -82,62 +180,130 @@ fn limit_filename_len(filename: String) -> String {
 
 impl CoverageCalculator {
     fn new() -> CoverageCalculator {
-        CoverageCalculator { items: Default::default() }
+        CoverageCalculator {
+            items: Default::default(),
+            modules: Default::default(),
+            module_path: Vec::new(),
+        }
     }
 
-    fn to_json(&self) -> String {
-        serde_json::to_string(
-            &self
-                .items
-                .iter()
-                .map(|(k, v)| (k.to_string(), v))
-                .collect::<BTreeMap<String, &ItemCount>>(),
-        )
-        .expect("failed to convert JSON data to string")
+    fn total(&self) -> ItemCount {
+        let mut total = ItemCount::default();
+        for &count in self.items.values() {
+            total += count;
+        }
+        total
     }
 
-    fn print_results(&self, output_format: Option<OutputFormat>) {
+    /// Returns the counts of each file as JSON. With `details`, the code example counts are
+    /// included, and the counts of each file and of each module are given under the `files` and
+    /// `modules` keys.
+    fn to_json(&self, details: bool) -> String {
+        #[derive(Serialize)]
+        struct FileCount {
+            total: u64,
+            with_docs: u64,
+        }
+
+        #[derive(Serialize)]
+        struct Coverage<'a> {
+            files: BTreeMap<String, &'a ItemCount>,
+            modules: &'a BTreeMap<String, ItemCount>,
+        }
+
+        let json = if details {
+            serde_json::to_string(&Coverage {
+                files: self.items.iter().map(|(k, v)| (k.to_string(), v)).collect(),
+                modules: &self.modules,
+            })
+        } else {
+            serde_json::to_string(
+                &self
+                    .items
+                    .iter()
+                    .map(|(k, v)| {
+                        (k.to_string(), FileCount { total: v.total, with_docs: v.with_docs })
+                    })
+                    .collect::<BTreeMap<String, FileCount>>(),
+            )
+        };
+        json.expect("failed to convert JSON data to string")
+    }
+
+    /// Prints the coverage as a table, or as JSON. The code examples and the coverage of each
+    /// module are only shown with `--coverage-details`.
+    fn print_results(&self, output_format: Option<OutputFormat>, details: bool) {
         if output_format.map(|o| o.is_json()).unwrap_or_else(|| false) {
-            println!("{}", self.to_json());
+            println!("{}", self.to_json(details));
             return;
         }
-        let mut total = ItemCount::default();
 
-        fn print_table_line() {
-            println!("+-{0:->35}-+-{0:->10}-+-{0:->10}-+-{0:->10}-+", "");
+        fn print_table_line(details: bool) {
+            print!("+-{0:->35}-+-{0:->10}-+-{0:->10}-+-{0:->10}-+", "");
+            if details {
+                print!("-{0:->10}-+-{0:->10}-+", "");
+            }
+            println!();
         }
 
-        fn print_table_record(name: &str, count: ItemCount, percentage: f64) {
-            println!(
+        fn print_table_record(name: &str, count: ItemCount, details: bool) {
+            print!(
                 "| {:<35} | {:>10} | {:>10} | {:>9.1}% |",
-                name, count.with_docs, count.total, percentage
+                name,
+                count.with_docs,
+                count.total,
+                count.percentage()
             );
+            if details {
+                let examples = format!("{}/{}", count.with_examples, count.total_examples);
+                print!(" {:>10} | {:>9.1}% |", examples, count.examples_percentage());
+            }
+            println!();
         }
 
-        print_table_line();
-        println!(
-            "| {:<35} | {:>10} | {:>10} | {:>10} |",
-            "File", "Documented", "Total", "Percentage"
-        );
-        print_table_line();
-
-        for (file, &count) in &self.items {
-            if let Some(percentage) = count.percentage() {
-                print_table_record(&limit_filename_len(file.to_string()), count, percentage);
+        fn print_table<'a>(
+            title: &str,
+            rows: impl Iterator<Item = (String, &'a ItemCount)>,
+            details: bool,
+        ) {
+            print_table_line(details);
+            print!(
+                "| {:<35} | {:>10} | {:>10} | {:>10} |",
+                title, "Documented", "Total", "Percentage"
+            );
+            if details {
+                print!(" {:>10} | {:>10} |", "Examples", "Percentage");
+            }
+            println!();
+            print_table_line(details);
 
-                total += count;
+            for (name, &count) in rows {
+                if count.total > 0 {
+                    print_table_record(&limit_filename_len(name), count, details);
+                }
             }
         }
 
-        print_table_line();
-        print_table_record("Total", total, total.percentage().unwrap_or(0.0));
-        print_table_line();
+        let files = self.items.iter().map(|(file, count)| (file.to_string(), count));
+        print_table("File", files, details);
+        print_table_line(details);
+        print_table_record("Total", self.total(), details);
+        print_table_line(details);
+
+        // A crate with a single module has nothing to break down.
+        if details && self.modules.len() > 1 {
+            println!();
+            let modules = self.modules.iter().map(|(path, count)| (path.clone(), count));
+            print_table("Module", modules, details);
+            print_table_line(details);
+        }
     }
 }
 
 impl fold::DocFolder for CoverageCalculator {
     fn fold_item(&mut self, i: clean::Item) -> Option<clean::Item> {
         let has_docs = !i.attrs.doc_strings.is_empty();
+        let mut is_module = false;
 
         match i.inner {
             _ if !i.def_id.is_local() => {
@@ -184,11 +350,48 @@ impl fold::DocFolder for CoverageCalculator {
                 }
             }
             _ => {
+                // modules are counted in their own row, as their docs are on their own page
+                if let clean::ModuleItem(..) = i.inner {
+                    self.module_path.push(i.name.clone().unwrap_or_default());
+                    is_module = true;
+                }
+
+                let should_have_example = match i.inner {
+                    clean::StructFieldItem(..)
+                    | clean::VariantItem(..)
+                    | clean::AssocConstItem(..)
+                    | clean::AssocTypeItem(..)
+                    | clean::TypedefItem(..)
+                    | clean::StaticItem(..)
+                    | clean::ConstantItem(..)
+                    | clean::PrimitiveItem(..)
+                    | clean::KeywordItem(..) => false,
+                    _ => true,
+                };
+                let has_example = has_docs
+                    && should_have_example
+                    && count_doc_tests(&i.attrs.collapsed_doc_value().unwrap_or_default()) > 0;
+
                 debug!("counting {:?} {:?} in {}", i.type_(), i.name, i.source.filename);
-                self.items.entry(i.source.filename.clone()).or_default().count_item(has_docs);
+                self.items.entry(i.source.filename.clone()).or_default().count_item(
+                    has_docs,
+                    should_have_example,
+                    has_example,
+                );
+                self.modules.entry(self.module_path.join("::")).or_default().count_item(
+                    has_docs,
+                    should_have_example,
+                    has_example,
+                );
             }
         }
 
-        self.fold_item_recur(i)
+        let ret = self.fold_item_recur(i);
+
+        if is_module {
+            self.module_path.pop();
+        }
+
+        ret
     }
 }
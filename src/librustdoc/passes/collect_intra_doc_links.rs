@@ -4,18 +4,21 @@ use rustc_expand::base::SyntaxExtensionKind;
 use rustc_feature::UnstableFeatures;
 use rustc_hir as hir;
 use rustc_hir::def::{
-    DefKind,
+    CtorKind, CtorOf, DefKind,
     Namespace::{self, *},
     PerNS, Res,
 };
-use rustc_hir::def_id::DefId;
+use rustc_hir::def_id::{DefId, CRATE_DEF_INDEX};
+use rustc_middle::lint::{struct_lint_level, LintDiagnosticBuilder, LintSource};
 use rustc_middle::ty;
 use rustc_resolve::ParentScope;
-use rustc_session::lint;
+use rustc_session::lint::{self, LintId};
+use rustc_span::hygiene::MacroKind;
 use rustc_span::symbol::Ident;
 use rustc_span::symbol::Symbol;
-use rustc_span::DUMMY_SP;
+use rustc_span::{Span, DUMMY_SP};
 
+use std::cmp;
 use std::ops::Range;
 
 use crate::clean::*;
@@ -47,6 +50,139 @@ enum ErrorKind {
     AnchorFailure(&'static str),
 }
 
+/// What a link says about the kind of item it points to, with a prefix such as `struct@` or a
+/// suffix such as `()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Disambiguator {
+    /// A specific kind of item, such as `fn@` or `derive@`.
+    Kind(DefKind),
+    /// Any item of a namespace, such as `type@` or `macro@`.
+    Namespace(Namespace),
+    /// A primitive type, with `prim@` or `primitive@`.
+    Primitive,
+}
+
+impl Disambiguator {
+    /// Splits the disambiguator of a link off of it, returning the disambiguator (if any) and the
+    /// path the link points to.
+    fn from_link(link: &str) -> (Option<Disambiguator>, &str) {
+        use Disambiguator::{Kind, Primitive};
+
+        const PREFIXES: &[(&str, Disambiguator)] = &[
+            ("struct@", Kind(DefKind::Struct)),
+            ("enum@", Kind(DefKind::Enum)),
+            ("trait@", Kind(DefKind::Trait)),
+            ("union@", Kind(DefKind::Union)),
+            ("mod@", Kind(DefKind::Mod)),
+            ("module@", Kind(DefKind::Mod)),
+            ("const@", Kind(DefKind::Const)),
+            ("constant@", Kind(DefKind::Const)),
+            ("static@", Kind(DefKind::Static)),
+            ("fn@", Kind(DefKind::Fn)),
+            ("function@", Kind(DefKind::Fn)),
+            ("method@", Kind(DefKind::AssocFn)),
+            ("derive@", Kind(DefKind::Macro(MacroKind::Derive))),
+            ("attr@", Kind(DefKind::Macro(MacroKind::Attr))),
+            ("type@", Disambiguator::Namespace(TypeNS)),
+            ("value@", Disambiguator::Namespace(ValueNS)),
+            ("macro@", Disambiguator::Namespace(MacroNS)),
+            ("prim@", Primitive),
+            ("primitive@", Primitive),
+        ];
+
+        if let Some(&(prefix, disambiguator)) =
+            PREFIXES.iter().find(|(prefix, _)| link.starts_with(prefix))
+        {
+            (Some(disambiguator), &link[prefix.len()..])
+        } else if link.ends_with("()") {
+            (Some(Kind(DefKind::Fn)), &link[..link.len() - 2])
+        } else if link.ends_with('!') {
+            (Some(Kind(DefKind::Macro(MacroKind::Bang))), &link[..link.len() - 1])
+        } else {
+            (None, link)
+        }
+    }
+
+    /// The namespace the path of the link is resolved in.
+    fn ns(self) -> Namespace {
+        match self {
+            Disambiguator::Kind(DefKind::Macro(_)) => MacroNS,
+            Disambiguator::Kind(
+                DefKind::Struct | DefKind::Enum | DefKind::Trait | DefKind::Union | DefKind::Mod,
+            ) => TypeNS,
+            Disambiguator::Kind(_) => ValueNS,
+            Disambiguator::Namespace(ns) => ns,
+            Disambiguator::Primitive => TypeNS,
+        }
+    }
+
+    /// Returns whether a link with this disambiguator can point to an item of the given kind, or
+    /// to a primitive type if `kind` is `None`. Functions and methods are interchangeable, as are
+    /// constants and associated constants.
+    fn matches(self, kind: Option<DefKind>) -> bool {
+        match (self, kind) {
+            (Disambiguator::Primitive, kind) => kind.is_none(),
+            (_, None) => self == Disambiguator::Namespace(TypeNS),
+            // Fields aren't namespaced, and are found in both namespaces.
+            (Disambiguator::Namespace(_), Some(DefKind::Field)) => true,
+            (Disambiguator::Namespace(ns), Some(kind)) => kind.matches_ns(ns),
+            (
+                Disambiguator::Kind(DefKind::Fn | DefKind::AssocFn),
+                Some(DefKind::Fn | DefKind::AssocFn | DefKind::Ctor(_, CtorKind::Fn)),
+            ) => true,
+            (Disambiguator::Kind(DefKind::Const), Some(DefKind::Const | DefKind::AssocConst)) => {
+                true
+            }
+            (Disambiguator::Kind(expected), Some(kind)) => expected == kind,
+        }
+    }
+
+    fn article(self) -> &'static str {
+        match self {
+            Disambiguator::Kind(DefKind::AssocFn) => "a",
+            Disambiguator::Kind(kind) => kind.article(),
+            Disambiguator::Namespace(_) | Disambiguator::Primitive => "a",
+        }
+    }
+
+    fn descr(self) -> &'static str {
+        match self {
+            Disambiguator::Kind(DefKind::Mod) => "module",
+            Disambiguator::Kind(DefKind::AssocFn) => "method",
+            Disambiguator::Kind(kind) => kind.descr(DefId::local(CRATE_DEF_INDEX)),
+            Disambiguator::Namespace(ns) => ns.descr(),
+            Disambiguator::Primitive => "builtin type",
+        }
+    }
+}
+
+/// Returns the kind of the item a link points to, or `None` for primitive types. `res` is the
+/// item whose page the link points to, and `fragment` the anchor of the link on that page, which
+/// tells apart the associated items, variants and fields of `res`.
+fn link_kind(res: Res, fragment: &Option<String>) -> Option<DefKind> {
+    let kind = match res {
+        Res::Def(kind, _) => Some(kind),
+        _ => None,
+    };
+    let anchor = match fragment {
+        Some(fragment) => fragment,
+        None => return kind,
+    };
+    if anchor.starts_with("method.") || anchor.starts_with("tymethod.") {
+        Some(DefKind::AssocFn)
+    } else if anchor.starts_with("associatedconstant.") {
+        Some(DefKind::AssocConst)
+    } else if anchor.starts_with("associatedtype.") {
+        Some(DefKind::AssocTy)
+    } else if anchor.starts_with("structfield.") || anchor.contains(".field.") {
+        Some(DefKind::Field)
+    } else if anchor.starts_with("variant.") || anchor.ends_with(".v") {
+        Some(DefKind::Variant)
+    } else {
+        kind
+    }
+}
+
 struct LinkCollector<'a, 'tcx> {
     cx: &'a DocContext<'tcx>,
     mod_ids: Vec<hir::HirId>,
@@ -269,7 +405,7 @@ impl<'a, 'tcx> LinkCollector<'a, 'tcx> {
                         let out = match item.kind {
                             ty::AssocKind::Fn if ns == ValueNS => "method",
                             ty::AssocKind::Const if ns == ValueNS => "associatedconstant",
-                            ty::AssocKind::Type if ns == ValueNS => "associatedtype",
+                            ty::AssocKind::Type if ns == TypeNS => "associatedtype",
                             _ => return self.variant_field(path_str, current_item, module_id),
                         };
                         if extra_fragment.is_some() {
@@ -457,42 +593,8 @@ impl<'a, 'tcx> DocFolder for LinkCollector<'a, 'tcx> {
                 (parts[0].to_owned(), None)
             };
             let (res, fragment) = {
-                let mut kind = None;
-                let path_str = if let Some(prefix) =
-                    ["struct@", "enum@", "type@", "trait@", "union@"]
-                        .iter()
-                        .find(|p| link.starts_with(**p))
-                {
-                    kind = Some(TypeNS);
-                    link.trim_start_matches(prefix)
-                } else if let Some(prefix) = [
-                    "const@",
-                    "static@",
-                    "value@",
-                    "function@",
-                    "mod@",
-                    "fn@",
-                    "module@",
-                    "method@",
-                ]
-                .iter()
-                .find(|p| link.starts_with(**p))
-                {
-                    kind = Some(ValueNS);
-                    link.trim_start_matches(prefix)
-                } else if link.ends_with("()") {
-                    kind = Some(ValueNS);
-                    link.trim_end_matches("()")
-                } else if link.starts_with("macro@") {
-                    kind = Some(MacroNS);
-                    link.trim_start_matches("macro@")
-                } else if link.ends_with('!') {
-                    kind = Some(MacroNS);
-                    link.trim_end_matches('!')
-                } else {
-                    &link[..]
-                }
-                .trim();
+                let (disambiguator, path_str) = Disambiguator::from_link(&link);
+                let path_str = path_str.trim();
 
                 if path_str.contains(|ch: char| !(ch.is_alphanumeric() || ch == ':' || ch == '_')) {
                     continue;
@@ -511,8 +613,10 @@ impl<'a, 'tcx> DocFolder for LinkCollector<'a, 'tcx> {
                 // parent_node first.
                 let base_node =
                     if item.is_mod() && item.attrs.inner_docs { None } else { parent_node };
+                let module_id = base_node.or(self.mod_ids.last().cloned());
+                let anchor_in_link = extra_fragment.is_some();
 
-                match kind {
+                let (res, fragment) = match disambiguator.map(Disambiguator::ns) {
                     Some(ns @ ValueNS) => {
                         match self.resolve(
                             path_str,
@@ -560,7 +664,7 @@ impl<'a, 'tcx> DocFolder for LinkCollector<'a, 'tcx> {
                     None => {
                         // Try everything!
                         let candidates = PerNS {
-                            macro_ns: macro_resolve(cx, path_str)
+                            macro_ns: macro_resolve(cx, path_str, module_id)
                                 .map(|res| (res, extra_fragment.clone())),
                             type_ns: match self.resolve(
                                 path_str,
@@ -629,14 +733,37 @@ impl<'a, 'tcx> DocFolder for LinkCollector<'a, 'tcx> {
                         }
                     }
                     Some(MacroNS) => {
-                        if let Some(res) = macro_resolve(cx, path_str) {
+                        if let Some(res) = macro_resolve(cx, path_str, module_id) {
                             (res, extra_fragment)
                         } else {
                             resolution_failure(cx, &item, path_str, &dox, link_range);
                             continue;
                         }
                     }
+                };
+
+                // When checking links, a link with a disambiguator has to point to an item of the
+                // kind it names, even when there was only one namespace to look in. An anchor
+                // written in the link itself doesn't say anything about the kind of the item.
+                if let (true, Some(disambiguator), Res::Def(..) | Res::PrimTy(_)) =
+                    (cx.check_intra_doc_links, disambiguator, res)
+                {
+                    let kind = link_kind(res, if anchor_in_link { &None } else { &fragment });
+                    if !disambiguator.matches(kind) {
+                        disambiguator_error(
+                            cx,
+                            &item,
+                            path_str,
+                            &dox,
+                            link_range,
+                            disambiguator,
+                            (res, kind),
+                        );
+                        continue;
+                    }
                 }
+
+                (res, fragment)
             };
 
             if let Res::PrimTy(_) = res {
@@ -673,7 +800,25 @@ impl<'a, 'tcx> DocFolder for LinkCollector<'a, 'tcx> {
 }
 
 /// Resolves a string as a macro.
-fn macro_resolve(cx: &DocContext<'_>, path_str: &str) -> Option<Res> {
+///
+/// Names are looked up from the crate root, and paths from the module `parent_id`, which finds the
+/// macros of dependencies through their path, like `dep::mac!`.
+fn macro_resolve(
+    cx: &DocContext<'_>,
+    path_str: &str,
+    parent_id: Option<hir::HirId>,
+) -> Option<Res> {
+    if path_str.contains("::") {
+        let module_id = cx.tcx.hir().hir_id_to_node_id(parent_id?);
+        return match cx.enter_resolver(|resolver| {
+            resolver.resolve_str_path_error(DUMMY_SP, path_str, MacroNS, module_id)
+        }) {
+            Ok((_, res @ Res::Def(DefKind::Macro(_), _))) => {
+                Some(res.map_id(|_| panic!("unexpected id")))
+            }
+            _ => None,
+        };
+    }
     let path = ast::Path::from_ident(Ident::from_str(path_str));
     cx.enter_resolver(|resolver| {
         if let Ok((Some(ext), res)) = resolver.resolve_macro_path(
@@ -694,6 +839,63 @@ fn macro_resolve(cx: &DocContext<'_>, path_str: &str) -> Option<Res> {
     })
 }
 
+/// Returns how to disambiguate a link to `path_str` so that it points to an item of the given
+/// kind in the namespace `ns`, or to a primitive type if `kind` is `None`, along with a
+/// description of the change.
+fn suggest_disambiguator(
+    kind: Option<DefKind>,
+    ns: Namespace,
+    path_str: &str,
+) -> (&'static str, String) {
+    match kind {
+        Some(DefKind::AssocFn | DefKind::Fn) => ("add parentheses", format!("{}()", path_str)),
+        Some(DefKind::Macro(MacroKind::Bang)) => {
+            ("add an exclamation mark", format!("{}!", path_str))
+        }
+        _ => {
+            let type_ = match (kind, ns) {
+                (Some(DefKind::Const | DefKind::AssocConst), _) => "const",
+                (Some(DefKind::Static), _) => "static",
+                (Some(DefKind::Struct | DefKind::Ctor(CtorOf::Struct, _)), _) => "struct",
+                (Some(DefKind::Enum), _) => "enum",
+                (Some(DefKind::Union), _) => "union",
+                (Some(DefKind::Trait), _) => "trait",
+                (Some(DefKind::Mod), _) => "module",
+                (Some(DefKind::Macro(MacroKind::Derive)), _) => "derive",
+                (Some(DefKind::Macro(MacroKind::Attr)), _) => "attr",
+                (None, _) => "prim",
+                (_, TypeNS) => "type",
+                (_, ValueNS) => "value",
+                (_, MacroNS) => "macro",
+            };
+
+            // FIXME: if this is an implied shortcut link, it's bad style to suggest `@`
+            ("prefix with the item type", format!("{}@{}", type_, path_str))
+        }
+    }
+}
+
+/// Emits `intra_doc_link_resolution_failure` for a link of the given item. Unless its level was set
+/// with an attribute or a lint flag, the lint is denied when running `--check-intra-doc-links`.
+fn report_link_failure(
+    cx: &DocContext<'_>,
+    hir_id: hir::HirId,
+    sp: Span,
+    decorate: impl for<'a> FnOnce(LintDiagnosticBuilder<'a>),
+) {
+    let sess = cx.sess();
+    let lint = lint::builtin::INTRA_DOC_LINK_RESOLUTION_FAILURE;
+    let (mut level, src) = cx.tcx.lint_level_at_node(lint, hir_id);
+    if cx.check_intra_doc_links && src == LintSource::Default {
+        // `--cap-lints` still applies to the default level of this mode.
+        level = cmp::min(lint::Deny, sess.opts.lint_cap.unwrap_or(lint::Forbid));
+        if let Some(&cap) = sess.driver_lint_caps.get(&LintId::of(lint)) {
+            level = cmp::min(level, cap);
+        }
+    }
+    struct_lint_level(sess, lint, level, src, Some(sp.into()), decorate);
+}
+
 fn build_diagnostic(
     cx: &DocContext<'_>,
     item: &Item,
@@ -714,43 +916,36 @@ fn build_diagnostic(
     let attrs = &item.attrs;
     let sp = span_of_attrs(attrs).unwrap_or(item.source.span());
 
-    cx.tcx.struct_span_lint_hir(
-        lint::builtin::INTRA_DOC_LINK_RESOLUTION_FAILURE,
-        hir_id,
-        sp,
-        |lint| {
-            let mut diag = lint.build(&format!("`[{}]` {}", path_str, err_msg));
-            if let Some(link_range) = link_range {
-                if let Some(sp) = super::source_span_for_markdown_range(cx, dox, &link_range, attrs)
-                {
-                    diag.set_span(sp);
-                    diag.span_label(sp, short_err_msg);
-                } else {
-                    // blah blah blah\nblah\nblah [blah] blah blah\nblah blah
-                    //                       ^     ~~~~
-                    //                       |     link_range
-                    //                       last_new_line_offset
-                    let last_new_line_offset =
-                        dox[..link_range.start].rfind('\n').map_or(0, |n| n + 1);
-                    let line = dox[last_new_line_offset..].lines().next().unwrap_or("");
-
-                    // Print the line containing the `link_range` and manually mark it with '^'s.
-                    diag.note(&format!(
-                        "the link appears in this line:\n\n{line}\n\
-                         {indicator: <before$}{indicator:^<found$}",
-                        line = line,
-                        indicator = "",
-                        before = link_range.start - last_new_line_offset,
-                        found = link_range.len(),
-                    ));
-                }
-            };
-            if let Some(help_msg) = help_msg {
-                diag.help(help_msg);
+    report_link_failure(cx, hir_id, sp, |lint| {
+        let mut diag = lint.build(&format!("`[{}]` {}", path_str, err_msg));
+        if let Some(link_range) = link_range {
+            if let Some(sp) = super::source_span_for_markdown_range(cx, dox, &link_range, attrs) {
+                diag.set_span(sp);
+                diag.span_label(sp, short_err_msg);
+            } else {
+                // blah blah blah\nblah\nblah [blah] blah blah\nblah blah
+                //                       ^     ~~~~
+                //                       |     link_range
+                //                       last_new_line_offset
+                let last_new_line_offset = dox[..link_range.start].rfind('\n').map_or(0, |n| n + 1);
+                let line = dox[last_new_line_offset..].lines().next().unwrap_or("");
+
+                // Print the line containing the `link_range` and manually mark it with '^'s.
+                diag.note(&format!(
+                    "the link appears in this line:\n\n{line}\n\
+                     {indicator: <before$}{indicator:^<found$}",
+                    line = line,
+                    indicator = "",
+                    before = link_range.start - last_new_line_offset,
+                    found = link_range.len(),
+                ));
             }
-            diag.emit();
-        },
-    );
+        };
+        if let Some(help_msg) = help_msg {
+            diag.help(help_msg);
+        }
+        diag.emit();
+    });
 }
 
 /// Reports a resolution failure diagnostic.
@@ -815,108 +1010,148 @@ fn ambiguity_error(
     let attrs = &item.attrs;
     let sp = span_of_attrs(attrs).unwrap_or(item.source.span());
 
-    cx.tcx.struct_span_lint_hir(
-        lint::builtin::INTRA_DOC_LINK_RESOLUTION_FAILURE,
-        hir_id,
-        sp,
-        |lint| {
-            let mut msg = format!("`{}` is ", path_str);
-
-            let candidates = [TypeNS, ValueNS, MacroNS]
-                .iter()
-                .filter_map(|&ns| candidates[ns].map(|res| (res, ns)))
-                .collect::<Vec<_>>();
-            match candidates.as_slice() {
-                [(first_def, _), (second_def, _)] => {
-                    msg += &format!(
-                        "both {} {} and {} {}",
-                        first_def.article(),
-                        first_def.descr(),
-                        second_def.article(),
-                        second_def.descr(),
-                    );
+    report_link_failure(cx, hir_id, sp, |lint| {
+        let mut msg = format!("`{}` is ", path_str);
+
+        let candidates = [TypeNS, ValueNS, MacroNS]
+            .iter()
+            .filter_map(|&ns| candidates[ns].map(|res| (res, ns)))
+            .collect::<Vec<_>>();
+        match candidates.as_slice() {
+            [(first_def, _), (second_def, _)] => {
+                msg += &format!(
+                    "both {} {} and {} {}",
+                    first_def.article(),
+                    first_def.descr(),
+                    second_def.article(),
+                    second_def.descr(),
+                );
+            }
+            _ => {
+                let mut candidates = candidates.iter().peekable();
+                while let Some((res, _)) = candidates.next() {
+                    if candidates.peek().is_some() {
+                        msg += &format!("{} {}, ", res.article(), res.descr());
+                    } else {
+                        msg += &format!("and {} {}", res.article(), res.descr());
+                    }
                 }
-                _ => {
-                    let mut candidates = candidates.iter().peekable();
-                    while let Some((res, _)) = candidates.next() {
-                        if candidates.peek().is_some() {
-                            msg += &format!("{} {}, ", res.article(), res.descr());
-                        } else {
-                            msg += &format!("and {} {}", res.article(), res.descr());
-                        }
+            }
+        }
+
+        let mut diag = lint.build(&msg);
+
+        if let Some(link_range) = link_range {
+            if let Some(sp) = super::source_span_for_markdown_range(cx, dox, &link_range, attrs) {
+                diag.set_span(sp);
+                diag.span_label(sp, "ambiguous link");
+
+                for (res, ns) in candidates {
+                    let kind = link_kind(res, &None);
+                    let (action, mut suggestion) = suggest_disambiguator(kind, ns, path_str);
+
+                    if dox.bytes().nth(link_range.start) == Some(b'`') {
+                        suggestion = format!("`{}`", suggestion);
                     }
+
+                    diag.span_suggestion(
+                        sp,
+                        &format!("to link to the {}, {}", res.descr(), action),
+                        suggestion,
+                        Applicability::MaybeIncorrect,
+                    );
                 }
+            } else {
+                // blah blah blah\nblah\nblah [blah] blah blah\nblah blah
+                //                       ^     ~~~~
+                //                       |     link_range
+                //                       last_new_line_offset
+                let last_new_line_offset = dox[..link_range.start].rfind('\n').map_or(0, |n| n + 1);
+                let line = dox[last_new_line_offset..].lines().next().unwrap_or("");
+
+                // Print the line containing the `link_range` and manually mark it with '^'s.
+                diag.note(&format!(
+                    "the link appears in this line:\n\n{line}\n\
+                     {indicator: <before$}{indicator:^<found$}",
+                    line = line,
+                    indicator = "",
+                    before = link_range.start - last_new_line_offset,
+                    found = link_range.len(),
+                ));
             }
+        }
+        diag.emit();
+    });
+}
 
-            let mut diag = lint.build(&msg);
+/// Reports a link whose disambiguator doesn't match the kind of item it resolved to, and suggests
+/// the disambiguator for that item instead.
+fn disambiguator_error(
+    cx: &DocContext<'_>,
+    item: &Item,
+    path_str: &str,
+    dox: &str,
+    link_range: Option<Range<usize>>,
+    disambiguator: Disambiguator,
+    (res, kind): (Res, Option<DefKind>),
+) {
+    // `kind` is only different from the kind of `res` for the items on the page of `res`.
+    let (found_article, found) = match (kind, res) {
+        (Some(DefKind::AssocFn), _) => ("a", "method"),
+        (Some(DefKind::Field), _) => ("a", "field"),
+        (Some(kind), Res::Def(res_kind, def_id)) if kind != res_kind => {
+            (kind.article(), kind.descr(def_id))
+        }
+        _ => (res.article(), res.descr()),
+    };
 
-            if let Some(link_range) = link_range {
-                if let Some(sp) = super::source_span_for_markdown_range(cx, dox, &link_range, attrs)
-                {
-                    diag.set_span(sp);
-                    diag.span_label(sp, "ambiguous link");
+    let hir_id = match cx.as_local_hir_id(item.def_id) {
+        Some(hir_id) => hir_id,
+        None => {
+            // If non-local, no need to check anything.
+            return;
+        }
+    };
+    let attrs = &item.attrs;
+    let sp = span_of_attrs(attrs).unwrap_or(item.source.span());
 
-                    for (res, ns) in candidates {
-                        let (action, mut suggestion) = match res {
-                            Res::Def(DefKind::AssocFn | DefKind::Fn, _) => {
-                                ("add parentheses", format!("{}()", path_str))
-                            }
-                            Res::Def(DefKind::Macro(..), _) => {
-                                ("add an exclamation mark", format!("{}!", path_str))
-                            }
-                            _ => {
-                                let type_ = match (res, ns) {
-                                    (Res::Def(DefKind::Const, _), _) => "const",
-                                    (Res::Def(DefKind::Static, _), _) => "static",
-                                    (Res::Def(DefKind::Struct, _), _) => "struct",
-                                    (Res::Def(DefKind::Enum, _), _) => "enum",
-                                    (Res::Def(DefKind::Union, _), _) => "union",
-                                    (Res::Def(DefKind::Trait, _), _) => "trait",
-                                    (Res::Def(DefKind::Mod, _), _) => "module",
-                                    (_, TypeNS) => "type",
-                                    (_, ValueNS) => "value",
-                                    (_, MacroNS) => "macro",
-                                };
-
-                                // FIXME: if this is an implied shortcut link, it's bad style to suggest `@`
-                                ("prefix with the item type", format!("{}@{}", type_, path_str))
-                            }
-                        };
+    report_link_failure(cx, hir_id, sp, |lint| {
+        let mut diag = lint.build(&format!("incompatible link kind for `{}`", path_str));
+        diag.note(&format!(
+            "this link resolved to {} {}, which is not {} {}",
+            found_article,
+            found,
+            disambiguator.article(),
+            disambiguator.descr(),
+        ));
 
-                        if dox.bytes().nth(link_range.start) == Some(b'`') {
-                            suggestion = format!("`{}`", suggestion);
-                        }
+        if let Some(link_range) = link_range {
+            if let Some(sp) = super::source_span_for_markdown_range(cx, dox, &link_range, attrs) {
+                diag.set_span(sp);
+                diag.span_label(sp, "this link resolved incorrectly");
 
-                        diag.span_suggestion(
-                            sp,
-                            &format!("to link to the {}, {}", res.descr(), action),
-                            suggestion,
-                            Applicability::MaybeIncorrect,
-                        );
-                    }
-                } else {
-                    // blah blah blah\nblah\nblah [blah] blah blah\nblah blah
-                    //                       ^     ~~~~
-                    //                       |     link_range
-                    //                       last_new_line_offset
-                    let last_new_line_offset =
-                        dox[..link_range.start].rfind('\n').map_or(0, |n| n + 1);
-                    let line = dox[last_new_line_offset..].lines().next().unwrap_or("");
-
-                    // Print the line containing the `link_range` and manually mark it with '^'s.
-                    diag.note(&format!(
-                        "the link appears in this line:\n\n{line}\n\
-                         {indicator: <before$}{indicator:^<found$}",
-                        line = line,
-                        indicator = "",
-                        before = link_range.start - last_new_line_offset,
-                        found = link_range.len(),
-                    ));
+                // Keep the anchor of the link, if any, and only replace its disambiguator.
+                let link = &dox[link_range.clone()];
+                let code = link.starts_with('`');
+                let link = link.trim_matches('`');
+                let anchor = link.find('#').map_or("", |i| &link[i..]);
+                let (action, mut suggestion) =
+                    suggest_disambiguator(kind, disambiguator.ns(), path_str);
+                suggestion.push_str(anchor);
+                if code {
+                    suggestion = format!("`{}`", suggestion);
                 }
+
+                diag.span_suggestion(
+                    sp,
+                    &format!("to link to the {}, {}", found, action),
+                    suggestion,
+                    Applicability::MachineApplicable,
+                );
             }
-            diag.emit();
-        },
-    );
+        }
+        diag.emit();
+    });
 }
 
 /// Given an enum variant's res, return the res of its enum and the associated fragment.
@@ -45,7 +45,7 @@ mod check_code_block_syntax;
 pub use self::check_code_block_syntax::CHECK_CODE_BLOCK_SYNTAX;
 
 mod calculate_doc_coverage;
-pub use self::calculate_doc_coverage::CALCULATE_DOC_COVERAGE;
+pub use self::calculate_doc_coverage::{CoverageThresholds, CALCULATE_DOC_COVERAGE};
 
 /// A single pass over the cleaned documentation.
 ///
@@ -113,6 +113,15 @@ pub const COVERAGE_PASSES: &[ConditionalPass] = &[
     ConditionalPass::always(CALCULATE_DOC_COVERAGE),
 ];
 
+/// The list of default passes run when `--check-intra-doc-links` is passed to rustdoc.
+pub const INTRA_DOC_LINK_PASSES: &[ConditionalPass] = &[
+    ConditionalPass::always(COLLAPSE_DOCS),
+    ConditionalPass::always(UNINDENT_COMMENTS),
+    ConditionalPass::new(STRIP_HIDDEN, WhenNotDocumentHidden),
+    ConditionalPass::new(STRIP_PRIVATE, WhenNotDocumentPrivate),
+    ConditionalPass::always(COLLECT_INTRA_DOC_LINKS),
+];
+
 impl ConditionalPass {
     pub const fn always(pass: Pass) -> Self {
         Self::new(pass, Always)
@@ -124,11 +133,12 @@ impl ConditionalPass {
 }
 
 /// A shorthand way to refer to which set of passes to use, based on the presence of
-/// `--no-defaults` and `--show-coverage`.
+/// `--no-defaults`, `--show-coverage` and `--check-intra-doc-links`.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum DefaultPassOption {
     Default,
     Coverage,
+    IntraDocLinks,
     None,
 }
 
@@ -137,6 +147,7 @@ pub fn defaults(default_set: DefaultPassOption) -> &'static [ConditionalPass] {
     match default_set {
         DefaultPassOption::Default => DEFAULT_PASSES,
         DefaultPassOption::Coverage => COVERAGE_PASSES,
+        DefaultPassOption::IntraDocLinks => INTRA_DOC_LINK_PASSES,
         DefaultPassOption::None => &[],
     }
 }
@@ -312,6 +323,25 @@ impl DocFolder for ImportStripper {
     }
 }
 
+/// Returns the number of code examples in the given documentation.
+crate fn count_doc_tests(dox: &str) -> usize {
+    struct Tests {
+        found_tests: usize,
+    }
+
+    impl crate::test::Tester for Tests {
+        fn add_test(&mut self, _: String, _: LangString, _: usize) {
+            self.found_tests += 1;
+        }
+    }
+
+    let mut tests = Tests { found_tests: 0 };
+
+    find_testable_code(dox, &mut tests, ErrorCodes::No, false, None);
+
+    tests.found_tests
+}
+
 pub fn look_for_tests<'tcx>(
     cx: &DocContext<'tcx>,
     dox: &str,
@@ -326,27 +356,15 @@ pub fn look_for_tests<'tcx>(
         }
     };
 
-    struct Tests {
-        found_tests: usize,
-    }
-
-    impl crate::test::Tester for Tests {
-        fn add_test(&mut self, _: String, _: LangString, _: usize) {
-            self.found_tests += 1;
-        }
-    }
-
-    let mut tests = Tests { found_tests: 0 };
-
-    find_testable_code(&dox, &mut tests, ErrorCodes::No, false, None);
+    let found_tests = count_doc_tests(dox);
 
-    if check_missing_code && tests.found_tests == 0 {
+    if check_missing_code && found_tests == 0 {
         let sp = span_of_attrs(&item.attrs).unwrap_or(item.source.span());
         cx.tcx.struct_span_lint_hir(lint::builtin::MISSING_DOC_CODE_EXAMPLES, hir_id, sp, |lint| {
             lint.build("missing code example in this documentation").emit()
         });
     } else if !check_missing_code
-        && tests.found_tests > 0
+        && found_tests > 0
         && !cx.renderinfo.borrow().access_levels.is_public(item.def_id)
     {
         cx.tcx.struct_span_lint_hir(
@@ -1,7 +1,10 @@
 use std::sync::Arc;
 
+use rustc_data_structures::fx::FxHashSet;
+use rustc_span::symbol::sym;
+
 use crate::clean::cfg::Cfg;
-use crate::clean::{Crate, Item};
+use crate::clean::{AttributesExt, Crate, Item};
 use crate::core::DocContext;
 use crate::fold::DocFolder;
 use crate::passes::Pass;
@@ -12,18 +15,69 @@ pub const PROPAGATE_DOC_CFG: Pass = Pass {
     description: "propagates `#[doc(cfg(...))]` to child items",
 };
 
-pub fn propagate_doc_cfg(cr: Crate, _: &DocContext<'_>) -> Crate {
-    CfgPropagator { parent_cfg: None }.fold_crate(cr)
+pub fn propagate_doc_cfg(cr: Crate, cx: &DocContext<'_>) -> Crate {
+    let auto_cfg = AutoCfg::new(&cr, cx);
+    CfgPropagator { parent_cfg: None, auto_cfg }.fold_crate(cr)
+}
+
+/// The settings of `#![doc(auto_cfg)]`, which uses the `#[cfg]` attributes of the items as if
+/// they were `#[doc(cfg)]` attributes.
+struct AutoCfg {
+    /// The configuration options left out of the `#[cfg]` attributes, given by
+    /// `#![doc(cfg_hide(...))]`. `doc` and `doctest` are always left out, since they are only
+    /// set by rustdoc itself.
+    hidden: FxHashSet<Cfg>,
+}
+
+impl AutoCfg {
+    fn new(cr: &Crate, cx: &DocContext<'_>) -> Option<AutoCfg> {
+        let attrs = &cr.module.as_ref()?.attrs;
+        if !attrs.has_doc_flag(sym::auto_cfg) {
+            return None;
+        }
+
+        let mut hidden: FxHashSet<Cfg> =
+            vec![Cfg::Cfg(sym::doc, None), Cfg::Cfg(sym::doctest, None)].into_iter().collect();
+        for hide in attrs.lists(sym::doc).filter(|attr| attr.check_name(sym::cfg_hide)) {
+            let cfg_mis = hide.meta_item_list().unwrap_or(&[]);
+            for cfg_mi in cfg_mis.iter().filter_map(|nested| nested.meta_item()) {
+                match Cfg::parse(cfg_mi) {
+                    Ok(cfg) => {
+                        hidden.insert(cfg);
+                    }
+                    Err(e) => cx.sess().span_err(e.span, e.msg),
+                }
+            }
+        }
+        Some(AutoCfg { hidden })
+    }
+
+    /// Returns the combined `#[cfg]` attributes of `item`, without the hidden options.
+    fn item_cfg(&self, item: &Item) -> Option<Cfg> {
+        let mut cfgs = item
+            .attrs
+            .lists(sym::cfg)
+            .filter_map(|cfg_mi| Cfg::parse(cfg_mi.meta_item()?).ok())
+            .filter_map(|cfg| cfg.strip_hidden(&self.hidden));
+        let first = cfgs.next()?;
+        Some(cfgs.fold(first, |x, y| x & y))
+    }
 }
 
 struct CfgPropagator {
     parent_cfg: Option<Arc<Cfg>>,
+    auto_cfg: Option<AutoCfg>,
 }
 
 impl DocFolder for CfgPropagator {
     fn fold_item(&mut self, mut item: Item) -> Option<Item> {
         let old_parent_cfg = self.parent_cfg.clone();
 
+        if let Some(cfg) = self.auto_cfg.as_ref().and_then(|auto_cfg| auto_cfg.item_cfg(&item)) {
+            let doc_cfg = item.attrs.cfg.take().map_or(Cfg::True, |rc| Cfg::clone(&rc));
+            item.attrs.cfg = Some(Arc::new(doc_cfg & cfg));
+        }
+
         let new_cfg = match (self.parent_cfg.take(), item.attrs.cfg.take()) {
             (None, None) => None,
             (Some(rc), None) | (None, Some(rc)) => Some(rc),
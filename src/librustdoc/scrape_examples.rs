@@ -0,0 +1,262 @@
+//! Finding the places where the functions of a crate are called from other crates, such as its
+//! examples and tests, so they can be shown as usage examples in its documentation.
+//!
+//! This is done in two steps. Rustdoc is first run on each of the crates the calls are taken
+//! from, with `--scrape-examples-output-path` and `--scrape-examples-target-crate`: instead of
+//! documenting the crate, it writes the calls it contains to functions of the target crates to a
+//! JSON file. Those files are then given back to rustdoc with `--with-examples` when documenting
+//! the target crate.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir as hir;
+use rustc_hir::def_id::{CrateNum, DefId, DefIndex, LocalDefId};
+use rustc_hir::intravisit::{self, NestedVisitorMap, Visitor};
+use rustc_middle::hir::map::Map;
+use rustc_middle::ty::{self, TyCtxt, TypeckTables};
+use rustc_span::{FileName, Span};
+use serde::{Deserialize, Serialize};
+
+/// Options for the scraping step.
+#[derive(Clone, Debug)]
+pub struct ScrapeExamplesOptions {
+    /// The file to write the calls to.
+    pub output_path: PathBuf,
+    /// The crates whose functions we are looking for calls to.
+    pub target_crates: Vec<String>,
+}
+
+impl ScrapeExamplesOptions {
+    crate fn new(
+        matches: &getopts::Matches,
+        diag: &rustc_errors::Handler,
+    ) -> Result<Option<Self>, i32> {
+        let output_path = matches.opt_str("scrape-examples-output-path");
+        let target_crates = matches.opt_strs("scrape-examples-target-crate");
+        match (output_path, target_crates.is_empty()) {
+            (Some(output_path), false) => Ok(Some(ScrapeExamplesOptions {
+                output_path: PathBuf::from(output_path),
+                target_crates,
+            })),
+            (Some(_), true) => {
+                diag.struct_err(
+                    "must use --scrape-examples-target-crate with --scrape-examples-output-path",
+                )
+                .emit();
+                Err(1)
+            }
+            (None, false) => {
+                diag.struct_err(
+                    "must use --scrape-examples-output-path with --scrape-examples-target-crate",
+                )
+                .emit();
+                Err(1)
+            }
+            (None, true) => Ok(None),
+        }
+    }
+}
+
+/// A range of a source file, as byte offsets from the start of the file and as 1-based,
+/// inclusive line numbers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SyntaxRange {
+    pub byte_span: (u32, u32),
+    pub line_span: (usize, usize),
+}
+
+impl SyntaxRange {
+    fn new(tcx: TyCtxt<'_>, span: Span) -> SyntaxRange {
+        let source_map = tcx.sess.source_map();
+        let lo = source_map.lookup_byte_offset(span.lo());
+        let hi = source_map.lookup_byte_offset(span.hi());
+        SyntaxRange {
+            byte_span: (lo.pos.0, hi.pos.0),
+            line_span: (
+                source_map.lookup_char_pos(span.lo()).line,
+                source_map.lookup_char_pos(span.hi()).line,
+            ),
+        }
+    }
+}
+
+/// A call to a function, and the item it was found in.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CallLocation {
+    pub call_expr: SyntaxRange,
+    pub enclosing_item: SyntaxRange,
+}
+
+/// The calls to a function found in a single file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CallData {
+    /// The path of the file, as it is shown to the reader.
+    pub display_name: String,
+    /// The contents of the file, which the ranges of `locations` refer to.
+    pub contents: String,
+    pub locations: Vec<CallLocation>,
+}
+
+/// The calls to a function, by the path of the file they were found in.
+pub type FnCallLocations = BTreeMap<String, CallData>;
+
+/// The calls to all the functions, by the hash of the path of the function. Unlike `DefId`s,
+/// these hashes are the same in the crate a function is defined in and in the crates using it.
+pub type AllCallLocations = BTreeMap<String, FnCallLocations>;
+
+fn def_path_hash(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    tcx.def_path_hash(def_id).0.to_hex()
+}
+
+/// Visits the bodies of a crate, and records the calls they make to the functions of the target
+/// crates.
+struct FindCalls<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    target_crates: &'a [CrateNum],
+    /// The type-check results of the body being visited.
+    tables: Option<&'tcx TypeckTables<'tcx>>,
+    calls: &'a mut AllCallLocations,
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for FindCalls<'a, 'tcx> {
+    type Map = Map<'tcx>;
+
+    fn nested_visit_map(&mut self) -> NestedVisitorMap<Self::Map> {
+        NestedVisitorMap::OnlyBodies(self.tcx.hir())
+    }
+
+    fn visit_nested_body(&mut self, id: hir::BodyId) {
+        let old_tables = self.tables.replace(self.tcx.body_tables(id));
+        let body = self.tcx.hir().body(id);
+        self.visit_body(body);
+        self.tables = old_tables;
+    }
+
+    fn visit_expr(&mut self, ex: &'tcx hir::Expr<'tcx>) {
+        intravisit::walk_expr(self, ex);
+
+        // Calls written by macros would show the macro invocation instead, which isn't helpful.
+        if ex.span.from_expansion() {
+            return;
+        }
+        let tables = match self.tables {
+            Some(tables) => tables,
+            None => return,
+        };
+        let def_id = match ex.kind {
+            hir::ExprKind::Call(f, _) => match tables.node_type(f.hir_id).kind {
+                ty::FnDef(def_id, _) => def_id,
+                _ => return,
+            },
+            hir::ExprKind::MethodCall(..) => match tables.type_dependent_def_id(ex.hir_id) {
+                Some(def_id) => def_id,
+                None => return,
+            },
+            _ => return,
+        };
+        if !self.target_crates.contains(&def_id.krate) {
+            return;
+        }
+
+        let tcx = self.tcx;
+        let file = tcx.sess.source_map().lookup_byte_offset(ex.span.lo()).sf;
+        let (path, contents) = match (&file.name, &file.src) {
+            (FileName::Real(path), Some(src)) => (path, src),
+            _ => return,
+        };
+        let enclosing_item = tcx.hir().span(tcx.hir().get_parent_item(ex.hir_id));
+        let location = CallLocation {
+            call_expr: SyntaxRange::new(tcx, ex.span),
+            enclosing_item: SyntaxRange::new(tcx, enclosing_item),
+        };
+
+        let fn_calls = self.calls.entry(def_path_hash(tcx, def_id)).or_default();
+        fn_calls
+            .entry(path.display().to_string())
+            .or_insert_with(|| CallData {
+                display_name: path.display().to_string(),
+                contents: contents.to_string(),
+                locations: Vec::new(),
+            })
+            .locations
+            .push(location);
+    }
+}
+
+/// Finds the calls of the local crate to the functions of the target crates, and writes them to
+/// the output file.
+crate fn run(tcx: TyCtxt<'_>, options: &ScrapeExamplesOptions) {
+    let target_crates: Vec<CrateNum> = tcx
+        .crates()
+        .iter()
+        .copied()
+        .filter(|&cnum| {
+            let name = tcx.crate_name(cnum);
+            options.target_crates.iter().any(|target| *target == *name.as_str())
+        })
+        .collect();
+
+    let mut calls = AllCallLocations::new();
+    let mut finder =
+        FindCalls { tcx, target_crates: &target_crates, tables: None, calls: &mut calls };
+    tcx.hir().krate().visit_all_item_likes(&mut finder.as_deep_visitor());
+
+    let res = serde_json::to_string(&calls)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(&options.output_path, json).map_err(|e| e.to_string()));
+    if let Err(e) = res {
+        tcx.sess
+            .struct_err(&format!(
+                "failed to write the call locations to {}: {}",
+                options.output_path.display(),
+                e
+            ))
+            .emit();
+    }
+}
+
+/// Loads and merges the call locations written to `paths` by the scraping step.
+crate fn load_call_locations(
+    paths: Vec<String>,
+    diag: &rustc_errors::Handler,
+) -> Result<AllCallLocations, i32> {
+    let mut all_calls = AllCallLocations::new();
+    for path in paths {
+        let res = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| serde_json::from_str(&contents).map_err(|e| e.to_string()));
+        let calls: AllCallLocations = match res {
+            Ok(calls) => calls,
+            Err(e) => {
+                diag.struct_err(&format!("failed to load examples from {}: {}", path, e)).emit();
+                return Err(1);
+            }
+        };
+        for (function, fn_calls) in calls {
+            all_calls.entry(function).or_default().extend(fn_calls);
+        }
+    }
+    Ok(all_calls)
+}
+
+/// Finds the local functions the calls were made to, dropping the calls to functions of other
+/// crates.
+crate fn resolve_call_locations(
+    tcx: TyCtxt<'_>,
+    mut all_calls: AllCallLocations,
+) -> FxHashMap<DefId, FnCallLocations> {
+    if all_calls.is_empty() {
+        return FxHashMap::default();
+    }
+    // Required trait methods and foreign functions have no body, so all the definitions of the
+    // crate are looked at, and not only the body owners.
+    (0..tcx.hir().definitions().def_index_count())
+        .filter_map(|index| {
+            let def_id = LocalDefId { local_def_index: DefIndex::from_usize(index) }.to_def_id();
+            all_calls.remove(&def_path_hash(tcx, def_id)).map(|calls| (def_id, calls))
+        })
+        .collect()
+}
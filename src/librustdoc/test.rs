@@ -20,11 +20,13 @@ use tempfile::Builder as TempFileBuilder;
 
 use std::collections::HashMap;
 use std::env;
+use std::fs;
 use std::io::{self, Write};
 use std::panic;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{self, Command, Stdio};
 use std::str;
+use std::sync::{Arc, Mutex};
 
 use crate::clean::Attributes;
 use crate::config::Options;
@@ -215,6 +217,9 @@ enum TestFailure {
     ExecutionFailure(process::Output),
     /// The test is marked `should_panic` but the test binary executed successfully.
     UnexpectedRunPass,
+    /// The test binary panicked (as expected) but its output did not contain the expected panic
+    /// message.
+    MissingPanicMessage(String, process::Output),
 }
 
 enum DirState {
@@ -237,6 +242,9 @@ fn run_test(
     line: usize,
     options: Options,
     should_panic: bool,
+    panic_message: Option<&str>,
+    env: &[(String, String)],
+    cfgs: &[String],
     no_run: bool,
     as_test_harness: bool,
     runtool: Option<String>,
@@ -253,47 +261,21 @@ fn run_test(
 
     let output_file = outdir.path().join("rust_out");
 
-    let rustc_binary = options
-        .test_builder
-        .as_deref()
-        .unwrap_or_else(|| rustc_interface::util::rustc_path().expect("found rustc"));
-    let mut compiler = Command::new(&rustc_binary);
-    compiler.arg("--crate-type").arg("bin");
-    for cfg in &options.cfgs {
-        compiler.arg("--cfg").arg(&cfg);
-    }
-    if let Some(sysroot) = options.maybe_sysroot {
-        compiler.arg("--sysroot").arg(sysroot);
+    let mut compiler = rustc_command(&options, edition, target);
+    for cfg in cfgs {
+        compiler.arg("--cfg").arg(cfg);
     }
-    compiler.arg("--edition").arg(&edition.to_string());
+    // The variables are also set at compile time, for `env!` and `option_env!`.
+    compiler.envs(env.iter().map(|&(ref key, ref value)| (key, value)));
     compiler.env("UNSTABLE_RUSTDOC_TEST_PATH", path);
     compiler.env("UNSTABLE_RUSTDOC_TEST_LINE", format!("{}", line as isize - line_offset as isize));
     compiler.arg("-o").arg(&output_file);
     if as_test_harness {
         compiler.arg("--test");
     }
-    for lib_str in &options.lib_strs {
-        compiler.arg("-L").arg(&lib_str);
-    }
-    for extern_str in &options.extern_strs {
-        compiler.arg("--extern").arg(&extern_str);
-    }
-    compiler.arg("-Ccodegen-units=1");
-    for codegen_options_str in &options.codegen_options_strs {
-        compiler.arg("-C").arg(&codegen_options_str);
-    }
-    for debugging_option_str in &options.debugging_options_strs {
-        compiler.arg("-Z").arg(&debugging_option_str);
-    }
     if no_run && !compile_fail {
         compiler.arg("--emit=metadata");
     }
-    compiler.arg("--target").arg(match target {
-        TargetTriple::TargetTriple(s) => s,
-        TargetTriple::TargetPath(path) => {
-            path.to_str().expect("target path must be valid unicode").to_string()
-        }
-    });
 
     compiler.arg("-");
     compiler.stdin(Stdio::piped());
@@ -337,16 +319,69 @@ fn run_test(
         return Ok(());
     }
 
-    // Run the code!
+    run_binary(&output_file, &[], env, runtool, runtool_args, should_panic, panic_message)
+}
+
+/// Creates a command compiling a doctest into an executable, with the options every doctest is
+/// compiled with.
+fn rustc_command(options: &Options, edition: Edition, target: TargetTriple) -> Command {
+    let rustc_binary = options
+        .test_builder
+        .as_deref()
+        .unwrap_or_else(|| rustc_interface::util::rustc_path().expect("found rustc"));
+    let mut compiler = Command::new(&rustc_binary);
+    compiler.arg("--crate-type").arg("bin");
+    for cfg in &options.cfgs {
+        compiler.arg("--cfg").arg(&cfg);
+    }
+    if let Some(ref sysroot) = options.maybe_sysroot {
+        compiler.arg("--sysroot").arg(sysroot);
+    }
+    compiler.arg("--edition").arg(&edition.to_string());
+    for lib_str in &options.lib_strs {
+        compiler.arg("-L").arg(&lib_str);
+    }
+    for extern_str in &options.extern_strs {
+        compiler.arg("--extern").arg(&extern_str);
+    }
+    compiler.arg("-Ccodegen-units=1");
+    for codegen_options_str in &options.codegen_options_strs {
+        compiler.arg("-C").arg(&codegen_options_str);
+    }
+    for debugging_option_str in &options.debugging_options_strs {
+        compiler.arg("-Z").arg(&debugging_option_str);
+    }
+    compiler.arg("--target").arg(match target {
+        TargetTriple::TargetTriple(s) => s,
+        TargetTriple::TargetPath(path) => {
+            path.to_str().expect("target path must be valid unicode").to_string()
+        }
+    });
+    compiler
+}
+
+/// Runs a compiled doctest, and checks that it succeeded, or that it panicked with the expected
+/// message if it was expected to.
+fn run_binary(
+    binary: &Path,
+    args: &[String],
+    env: &[(String, String)],
+    runtool: Option<String>,
+    runtool_args: Vec<String>,
+    should_panic: bool,
+    panic_message: Option<&str>,
+) -> Result<(), TestFailure> {
     let mut cmd;
 
     if let Some(tool) = runtool {
         cmd = Command::new(tool);
         cmd.args(runtool_args);
-        cmd.arg(output_file);
+        cmd.arg(binary);
     } else {
-        cmd = Command::new(output_file);
+        cmd = Command::new(binary);
     }
+    cmd.args(args);
+    cmd.envs(env.iter().map(|&(ref key, ref value)| (key, value)));
 
     match cmd.output() {
         Err(e) => return Err(TestFailure::ExecutionError(e)),
@@ -355,6 +390,10 @@ fn run_test(
                 return Err(TestFailure::UnexpectedRunPass);
             } else if !should_panic && !out.status.success() {
                 return Err(TestFailure::ExecutionFailure(out));
+            } else if let Some(message) = panic_message {
+                if !String::from_utf8_lossy(&out.stderr).contains(message) {
+                    return Err(TestFailure::MissingPanicMessage(message.to_owned(), out));
+                }
             }
         }
     }
@@ -396,6 +435,57 @@ pub fn make_test(
     prog.push_str(&crate_attrs);
     prog.push_str(&crates);
 
+    let (already_has_main, already_has_extern_crate) =
+        match check_source(&(crates + everything_else), cratename, edition) {
+            Ok(result) => result,
+            Err(ErrorReported) => {
+                // If the parser panicked due to a fatal error, pass the test code through
+                // unchanged. The error will be reported during compilation.
+                return (s.to_owned(), 0);
+            }
+        };
+
+    // Don't inject `extern crate std` because it's already injected by the
+    // compiler.
+    if !already_has_extern_crate && !opts.no_crate_inject && cratename != Some("std") {
+        if let Some(cratename) = cratename {
+            // Make sure its actually used if not included.
+            if s.contains(cratename) {
+                prog.push_str(&format!("extern crate {};\n", cratename));
+                line_offset += 1;
+            }
+        }
+    }
+
+    // FIXME: This code cannot yet handle no_std test cases yet
+    if dont_insert_main || already_has_main || prog.contains("![no_std]") {
+        prog.push_str(everything_else);
+    } else {
+        let returns_result = everything_else.trim_end().ends_with("(())");
+        let (main_pre, main_post) = if returns_result {
+            (
+                "fn main() { fn _inner() -> Result<(), impl core::fmt::Debug> {",
+                "}\n_inner().unwrap() }",
+            )
+        } else {
+            ("fn main() {\n", "\n}")
+        };
+        prog.extend([main_pre, everything_else, main_post].iter().cloned());
+        line_offset += 1;
+    }
+
+    debug!("final doctest:\n{}", prog);
+
+    (prog, line_offset)
+}
+
+/// Parses the code of a doctest to find whether it has a `main` function, and whether it already
+/// has an `extern crate` item for `cratename`.
+fn check_source(
+    source: &str,
+    cratename: Option<&str>,
+    edition: Edition,
+) -> Result<(bool, bool), ErrorReported> {
     // Uses librustc_ast to parse the doctest and find if there's a main fn and the extern
     // crate already is included.
     let result = rustc_driver::catch_fatal_errors(|| {
@@ -406,8 +496,8 @@ pub fn make_test(
             use rustc_session::parse::ParseSess;
             use rustc_span::source_map::FilePathMapping;
 
-            let filename = FileName::anon_source_code(s);
-            let source = crates + everything_else;
+            let filename = FileName::anon_source_code(source);
+            let source = source.to_owned();
 
             // Any errors in parsing should also appear when the doctest is compiled for real, so just
             // send all the errors that librustc_ast emits directly into a `Sink` instead of stderr.
@@ -478,21 +568,15 @@ pub fn make_test(
             (found_main, found_extern_crate, found_macro)
         })
     });
-    let (already_has_main, already_has_extern_crate, found_macro) = match result {
-        Ok(result) => result,
-        Err(ErrorReported) => {
-            // If the parser panicked due to a fatal error, pass the test code through unchanged.
-            // The error will be reported during compilation.
-            return (s.to_owned(), 0);
-        }
-    };
+    let (already_has_main, already_has_extern_crate, found_macro) = result?;
 
     // If a doctest's `fn main` is being masked by a wrapper macro, the parsing loop above won't
     // see it. In that case, run the old text-based scan to see if they at least have a main
     // function written inside a macro invocation. See
     // https://github.com/rust-lang/rust/issues/56898
     let already_has_main = if found_macro && !already_has_main {
-        s.lines()
+        source
+            .lines()
             .map(|line| {
                 let comment = line.find("//");
                 if let Some(comment_begins) = comment { &line[0..comment_begins] } else { line }
@@ -502,38 +586,108 @@ pub fn make_test(
         already_has_main
     };
 
-    // Don't inject `extern crate std` because it's already injected by the
-    // compiler.
-    if !already_has_extern_crate && !opts.no_crate_inject && cratename != Some("std") {
-        if let Some(cratename) = cratename {
-            // Make sure its actually used if not included.
-            if s.contains(cratename) {
-                prog.push_str(&format!("extern crate {};\n", cratename));
-                line_offset += 1;
-            }
-        }
+    Ok((already_has_main, already_has_extern_crate))
+}
+
+/// Transforms a doctest into a module of a merged doctest binary, or returns `None` if it can't
+/// be merged with other doctests. The code of the doctest is put on the lines following `line`,
+/// as it is in the file it was taken from, so that panics point to the right line.
+///
+/// Also returns whether the doctest needs `extern crate cratename;` to be inserted. It is put in
+/// the root of the binary, where the paths of `use` items start in the 2015 edition.
+fn make_merged_module(
+    s: &str,
+    cratename: Option<&str>,
+    line: usize,
+    opts: &TestOptions,
+    edition: Edition,
+) -> Option<(String, bool)> {
+    let (crate_attrs, _, crates) = partition_source(s);
+    // Crate attributes apply to the whole binary, and `#[macro_use]` can only be used on the
+    // `extern crate` items of the crate root.
+    if crate_attrs.contains("#![") || crates.contains("#[macro_use]") {
+        return None;
     }
+    let (already_has_main, already_has_extern_crate) = check_source(s, cratename, edition).ok()?;
 
-    // FIXME: This code cannot yet handle no_std test cases yet
-    if dont_insert_main || already_has_main || prog.contains("![no_std]") {
-        prog.push_str(everything_else);
+    let needs_crate = !already_has_extern_crate
+        && !opts.no_crate_inject
+        && cratename.map_or(false, |cratename| cratename != "std" && s.contains(cratename));
+
+    let mut module = "\n".repeat(line.saturating_sub(1));
+    if already_has_main {
+        module.push('\n');
+        module.push_str(s);
     } else {
-        let returns_result = everything_else.trim_end().ends_with("(())");
+        let returns_result = s.trim_end().ends_with("(())");
         let (main_pre, main_post) = if returns_result {
             (
                 "fn main() { fn _inner() -> Result<(), impl core::fmt::Debug> {",
                 "}\n_inner().unwrap() }",
             )
         } else {
-            ("fn main() {\n", "\n}")
+            ("fn main() {", "\n}")
         };
-        prog.extend([main_pre, everything_else, main_post].iter().cloned());
-        line_offset += 1;
+        module.extend([main_pre, "\n", s, main_post].iter().cloned());
     }
+    module.push_str("\n#[test]\nfn __doctest() {\n");
+    module.push_str("    crate::__DoctestResult::check(main())\n}\n");
 
-    debug!("final doctest:\n{}", prog);
+    debug!("merged doctest module:\n{}", module);
 
-    (prog, line_offset)
+    Some((module, needs_crate))
+}
+
+/// Generates the root of a merged doctest binary, whose modules are in the files at
+/// `module_paths`. The binary is compiled with `--test`, and each module has a `__doctest` test
+/// which runs its doctest.
+fn make_merged_main(
+    module_paths: &[String],
+    cratename: Option<&str>,
+    opts: &TestOptions,
+) -> String {
+    let mut prog = String::new();
+
+    // The same crate attributes as in `make_test`, since they can't be set in the modules.
+    if opts.attrs.is_empty() && !opts.display_warnings {
+        prog.push_str("#![allow(unused)]\n");
+    }
+    for attr in &opts.attrs {
+        prog.push_str(&format!("#![{}]\n", attr));
+    }
+
+    if let Some(cratename) = cratename {
+        prog.push_str(&format!("extern crate {};\n", cratename));
+    }
+
+    for (i, path) in module_paths.iter().enumerate() {
+        prog.push_str(&format!("#[path = {:?}]\nmod __doctest_{};\n", path, i));
+    }
+
+    // The `main` function of a doctest may return a `Result`, whose error makes the doctest fail
+    // with the same exit code as a standalone doctest.
+    prog.push_str(
+        "
+trait __DoctestResult {
+    fn check(self);
+}
+
+impl __DoctestResult for () {
+    fn check(self) {}
+}
+
+impl<E: std::fmt::Debug> __DoctestResult for Result<(), E> {
+    fn check(self) {
+        if let Err(e) = self {
+            eprintln!(\"Error: {:?}\", e);
+            std::process::exit(1);
+        }
+    }
+}
+",
+    );
+
+    prog
 }
 
 // FIXME(aburka): use a real parser to deal with multiline attributes
@@ -606,6 +760,117 @@ fn partition_source(s: &str) -> (String, String, String) {
     (before, after, crates)
 }
 
+/// Doctests compiled together into a single binary with `--merge-doctests`, which is much faster
+/// than compiling and linking an executable for each of them.
+///
+/// The binary is a test harness with a `#[test]` for each doctest. It is run once per doctest,
+/// with a filter selecting its test, so that a doctest which exits the process or changes its
+/// global state doesn't affect the others, as for standalone doctests.
+///
+/// The binary is built the first time one of the doctests is run, once all of them were
+/// collected. If it fails to compile, each doctest is compiled on its own instead, which reports
+/// the errors of the doctests that caused it.
+struct MergedDoctests {
+    cratename: String,
+    options: Options,
+    opts: TestOptions,
+    outdir: DirState,
+    modules: Mutex<Vec<MergedModule>>,
+    /// The path to the binary once it was built, or `None` if it failed to compile.
+    binary: Mutex<Option<Option<PathBuf>>>,
+}
+
+struct MergedModule {
+    /// The path of the file the doctest was taken from.
+    path: PathBuf,
+    source: String,
+    /// Whether the doctest uses the crate without an `extern crate` item.
+    needs_crate: bool,
+}
+
+impl MergedDoctests {
+    /// Adds a module to the binary, and returns the index of its doctest.
+    fn add(&self, path: PathBuf, source: String, needs_crate: bool) -> usize {
+        let mut modules = self.modules.lock().unwrap();
+        modules.push(MergedModule { path, source, needs_crate });
+        modules.len() - 1
+    }
+
+    /// Returns the path to the binary, building it if it wasn't yet.
+    fn binary(&self) -> Option<PathBuf> {
+        let mut binary = self.binary.lock().unwrap();
+        binary
+            .get_or_insert_with(|| {
+                self.build()
+                    .map_err(|err| {
+                        // The output of the doctests is captured, but not what is written to
+                        // `io::stderr` directly.
+                        let _ = writeln!(
+                            io::stderr(),
+                            "warning: couldn't build the merged doctests ({}), compiling each \
+                             doctest on its own instead",
+                            err
+                        );
+                    })
+                    .ok()
+            })
+            .clone()
+    }
+
+    fn build(&self) -> io::Result<PathBuf> {
+        let modules = self.modules.lock().unwrap();
+        let dir = self.outdir.path();
+
+        let mut module_paths = Vec::with_capacity(modules.len());
+        let mut args = String::new();
+        for (i, module) in modules.iter().enumerate() {
+            let module_dir = dir.join(i.to_string());
+            let file_name = module.path.file_name().unwrap_or_else(|| "doctest.rs".as_ref());
+            fs::create_dir_all(&module_dir)?;
+            fs::write(module_dir.join(file_name), &module.source)?;
+            module_paths.push(format!("{}/{}", i, file_name.to_string_lossy()));
+
+            // Make the paths of the module point to the file the doctest was taken from.
+            let parent = module.path.parent().unwrap_or_else(|| Path::new(""));
+            args.push_str(&format!(
+                "--remap-path-prefix={}={}\n",
+                module_dir.display(),
+                parent.display()
+            ));
+        }
+
+        let main = dir.join("main.rs");
+        let cratename = if modules.iter().any(|module| module.needs_crate) {
+            Some(self.cratename.as_str())
+        } else {
+            None
+        };
+        fs::write(&main, make_merged_main(&module_paths, cratename, &self.opts))?;
+        // There is an argument per doctest, which could exceed the maximum length of a command
+        // line, so they are passed in a file.
+        let args_file = dir.join("args");
+        fs::write(&args_file, args)?;
+
+        let output_file = dir.join("rust_out");
+        let mut compiler =
+            rustc_command(&self.options, self.options.edition, self.options.target.clone());
+        compiler.arg(format!("@{}", args_file.display()));
+        compiler.arg("--test");
+        compiler.arg("-o").arg(&output_file);
+        compiler.arg(&main);
+        // The errors are reported when the doctests are compiled on their own instead.
+        compiler.stdout(Stdio::null());
+        compiler.stderr(Stdio::null());
+
+        let status = compiler.status()?;
+        if status.success() {
+            Ok(output_file)
+        } else {
+            Err(io::Error::new(io::ErrorKind::Other, format!("rustc failed with {}", status)))
+        }
+    }
+}
+
 pub trait Tester {
     fn add_test(&mut self, test: String, config: LangString, line: usize);
     fn get_line(&self) -> usize {
@@ -648,6 +913,7 @@ pub struct Collector {
     source_map: Option<Lrc<SourceMap>>,
     filename: Option<PathBuf>,
     visited_tests: HashMap<(String, usize), usize>,
+    merged: Option<Arc<MergedDoctests>>,
 }
 
 impl Collector {
@@ -660,6 +926,32 @@ impl Collector {
         filename: Option<PathBuf>,
         enable_per_target_ignores: bool,
     ) -> Collector {
+        let merged = if options.merge_doctests {
+            let outdir = if let Some(path) = options.persist_doctests.as_ref() {
+                let path = path.join("merged");
+                std::fs::create_dir_all(&path)
+                    .expect("Couldn't create directory for doctest executables");
+                DirState::Perm(path)
+            } else {
+                DirState::Temp(
+                    TempFileBuilder::new()
+                        .prefix("rustdoctest")
+                        .tempdir()
+                        .expect("rustdoc needs a tempdir"),
+                )
+            };
+            Some(Arc::new(MergedDoctests {
+                cratename: cratename.clone(),
+                options: options.clone(),
+                opts: opts.clone(),
+                outdir,
+                modules: Mutex::new(Vec::new()),
+                binary: Mutex::new(None),
+            }))
+        } else {
+            None
+        };
+
         Collector {
             tests: Vec::new(),
             names: Vec::new(),
@@ -672,6 +964,7 @@ impl Collector {
             source_map,
             filename,
             visited_tests: HashMap::new(),
+            merged,
         }
     }
 
@@ -757,6 +1050,27 @@ impl Tester for Collector {
             )
         };
 
+        // Doctests that need to be compiled in a specific way can't be merged with the others.
+        let merged = match self.merged {
+            Some(ref merged)
+                if config.ignore == Ignore::None
+                    && !config.compile_fail
+                    && !config.no_run
+                    && !config.test_harness
+                    && config.env.is_empty()
+                    && config.cfgs.is_empty()
+                    && edition == self.options.edition =>
+            {
+                make_merged_module(&test, Some(cratename.as_str()), line, &opts, edition).map(
+                    |(module, needs_crate)| {
+                        let index = merged.add(path.clone(), module, needs_crate);
+                        (Arc::clone(merged), index)
+                    },
+                )
+            }
+            _ => None,
+        };
+
         debug!("creating test {}: {}", name, test);
         self.tests.push(testing::TestDescAndFn {
             desc: testing::TestDesc {
@@ -769,27 +1083,49 @@ impl Tester for Collector {
                 // compiler failures are test failures
                 should_panic: testing::ShouldPanic::No,
                 allow_fail: config.allow_fail,
+                timeout: None,
+                retries: None,
                 test_type: testing::TestType::DocTest,
             },
             testfn: testing::DynTestFn(box move || {
-                let res = run_test(
-                    &test,
-                    &cratename,
-                    line,
-                    options,
-                    config.should_panic,
-                    config.no_run,
-                    config.test_harness,
-                    runtool,
-                    runtool_args,
-                    target,
-                    config.compile_fail,
-                    config.error_codes,
-                    &opts,
-                    edition,
-                    outdir,
-                    path,
-                );
+                let merged_binary = merged.and_then(|(merged, index)| {
+                    let filter = format!("__doctest_{}::__doctest", index);
+                    let args = vec![filter, "--exact".to_owned(), "--nocapture".to_owned()];
+                    Some((merged.binary()?, args))
+                });
+                let res = if let Some((binary, args)) = merged_binary {
+                    run_binary(
+                        &binary,
+                        &args,
+                        &config.env,
+                        runtool,
+                        runtool_args,
+                        config.should_panic,
+                        config.should_panic_message.as_deref(),
+                    )
+                } else {
+                    run_test(
+                        &test,
+                        &cratename,
+                        line,
+                        options,
+                        config.should_panic,
+                        config.should_panic_message.as_deref(),
+                        &config.env,
+                        &config.cfgs,
+                        config.no_run,
+                        config.test_harness,
+                        runtool,
+                        runtool_args,
+                        target,
+                        config.compile_fail,
+                        config.error_codes,
+                        &opts,
+                        edition,
+                        outdir,
+                        path,
+                    )
+                };
 
                 if let Err(err) = res {
                     match err {
@@ -802,6 +1138,17 @@ impl Tester for Collector {
                         TestFailure::UnexpectedRunPass => {
                             eprint!("Test executable succeeded, but it's marked `should_panic`.");
                         }
+                        TestFailure::MissingPanicMessage(message, out) => {
+                            eprintln!(
+                                "Test executable panicked, but its output didn't contain the \
+                                 expected panic message `{}`.",
+                                message
+                            );
+                            let stderr = str::from_utf8(&out.stderr).unwrap_or_default();
+                            if !stderr.is_empty() {
+                                eprintln!("\nstderr:\n{}", stderr);
+                            }
+                        }
                         TestFailure::MissingErrorCodes(codes) => {
                             eprint!("Some expected error codes were not found: {:?}", codes);
                         }
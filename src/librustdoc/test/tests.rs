@@ -1,4 +1,4 @@
-use super::{make_test, TestOptions};
+use super::{make_merged_main, make_merged_module, make_test, TestOptions};
 use rustc_span::edition::DEFAULT_EDITION;
 
 #[test]
@@ -277,3 +277,76 @@ test_wrapper! {
     let output = make_test(input, Some("my_crate"), false, &opts, DEFAULT_EDITION);
     assert_eq!(output, (expected, 1));
 }
+
+#[test]
+fn make_merged_module_basic() {
+    // The code of the doctest is put on the line following the one it was given, and is wrapped
+    // with a `fn main` that the `__doctest` test calls.
+    let opts = TestOptions::default();
+    let input = "assert_eq!(2+2, 4);";
+    let expected = "
+
+fn main() {
+assert_eq!(2+2, 4);
+}
+#[test]
+fn __doctest() {
+    crate::__DoctestResult::check(main())
+}
+"
+    .to_string();
+    let output = make_merged_module(input, None, 3, &opts, DEFAULT_EDITION);
+    assert_eq!(output, Some((expected, false)));
+}
+
+#[test]
+fn make_merged_module_crate_name_and_main() {
+    // The `extern crate` statement is left to the root of the binary, and an existing `fn main`
+    // is kept.
+    let opts = TestOptions::default();
+    let input = "use asdf::qwop;
+fn main() {}";
+    let expected = "
+use asdf::qwop;
+fn main() {}
+#[test]
+fn __doctest() {
+    crate::__DoctestResult::check(main())
+}
+"
+    .to_string();
+    let output = make_merged_module(input, Some("asdf"), 1, &opts, DEFAULT_EDITION);
+    assert_eq!(output, Some((expected, true)));
+}
+
+#[test]
+fn make_merged_module_crate_attrs() {
+    // Doctests with crate attributes can't be merged with other doctests.
+    let opts = TestOptions::default();
+    let input = "#![feature(asdfasdf)]
+assert_eq!(2+2, 4);";
+    let output = make_merged_module(input, None, 1, &opts, DEFAULT_EDITION);
+    assert_eq!(output, None);
+
+    let input = "#[macro_use] extern crate asdf;
+assert_eq!(2+2, 4);";
+    let output = make_merged_module(input, Some("asdf"), 1, &opts, DEFAULT_EDITION);
+    assert_eq!(output, None);
+}
+
+#[test]
+fn make_merged_main_basic() {
+    let opts = TestOptions::default();
+    let modules = ["0/lib.rs".to_string(), "1/lib.rs".to_string()];
+    let output = make_merged_main(&modules, Some("asdf"), &opts);
+    assert!(output.starts_with(
+        "#![allow(unused)]
+extern crate asdf;
+#[path = \"0/lib.rs\"]
+mod __doctest_0;
+#[path = \"1/lib.rs\"]
+mod __doctest_1;
+"
+    ));
+    assert!(!output.contains("fn main"));
+}
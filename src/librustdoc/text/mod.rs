@@ -0,0 +1,503 @@
+//! Rustdoc's plain text and man page backend
+//!
+//! This module renders every module and item of a crate as a page of its own, either as plain
+//! text (`--output-format text`) or as a man page (`--output-format man`). The documentation is
+//! converted from markdown by `html::markdown::MarkdownText`, and the declarations are printed
+//! by the alternate (plain) mode of the printers of `html::format`.
+
+mod page;
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use rustc_span::edition::Edition;
+use rustc_span::hygiene::MacroKind;
+
+use crate::clean;
+use crate::config::{OutputFormat, RenderOptions};
+use crate::docfs::PathError;
+use crate::doctree;
+use crate::formats::FormatRenderer;
+use crate::html::format::{
+    print_abi_with_space, print_generic_bounds, Function, PrintWithSpace, WhereClause,
+};
+use crate::html::item_type::ItemType;
+use crate::html::markdown::{plain_summary_line, MarkdownText, TextBlock};
+use crate::html::render::{item_ty_to_strs, Cache, Error, SearchIndex};
+
+use self::page::{Content, Page, Section};
+
+/// How the pages are written.
+#[derive(Clone, Copy, PartialEq)]
+enum Style {
+    /// `.txt` files, laid out like the HTML documentation.
+    Plain,
+    /// Section 3 man pages, in a `man3` directory, named after the full paths and the types of
+    /// the items.
+    Man,
+}
+
+crate struct TextRenderer {
+    style: Style,
+    dst: PathBuf,
+    cache: Arc<Cache>,
+    /// The path of the current module.
+    current: Vec<String>,
+    /// How many of the modules containing the current one were stripped from the
+    /// documentation. Their items don't get pages.
+    stripped_depth: usize,
+    /// The last line of the man pages, which says which crate they document.
+    source: String,
+}
+
+impl FormatRenderer for TextRenderer {
+    fn init(
+        krate: clean::Crate,
+        options: RenderOptions,
+        output_format: OutputFormat,
+        _edition: Edition,
+        cache: Arc<Cache>,
+        _search_index: SearchIndex,
+        _diag: &rustc_errors::Handler,
+    ) -> Result<(Self, clean::Crate), Error> {
+        let style = if output_format == OutputFormat::Man { Style::Man } else { Style::Plain };
+        let source = match cache.crate_version {
+            Some(ref version) => format!("{} {}", krate.name, version),
+            None => krate.name.clone(),
+        };
+        let renderer = TextRenderer {
+            style,
+            dst: options.output,
+            cache,
+            current: Vec::new(),
+            stripped_depth: 0,
+            source,
+        };
+        Ok((renderer, krate))
+    }
+
+    fn item(&mut self, item: clean::Item) -> Result<(), Error> {
+        match item.type_() {
+            ItemType::ExternCrate | ItemType::Import => return Ok(()),
+            _ if item.is_stripped() || self.stripped_depth > 0 => return Ok(()),
+            _ => {}
+        }
+
+        let name = item.name.as_ref().unwrap();
+        let page = Page {
+            title: self.full_path(name),
+            summary: summary(&item),
+            sections: self.item_sections(&item),
+        };
+        let file = match self.style {
+            Style::Plain => self.module_dir().join(format!("{}.{}.txt", item.type_(), name)),
+            Style::Man => self.man_page(&page.title, &item),
+        };
+        self.write(file, &page)
+    }
+
+    fn mod_item_in(&mut self, item: &clean::Item, item_name: &str) -> Result<(), Error> {
+        self.current.push(item_name.to_owned());
+        if item.is_stripped() || self.stripped_depth > 0 {
+            self.stripped_depth += 1;
+            return Ok(());
+        }
+
+        let module = match item.inner {
+            clean::ModuleItem(ref m) => m,
+            _ => unreachable!(),
+        };
+        let mut sections = vec![];
+        let docs = docs(item);
+        if !docs.is_empty() {
+            sections.push(Section::new("Description", vec![Content::Docs(docs)]));
+        }
+        sections.extend(module_sections(&module.items));
+
+        let page = Page { title: self.current.join("::"), summary: summary(item), sections };
+        let file = match self.style {
+            Style::Plain => self.module_dir().join("index.txt"),
+            Style::Man => self.man_page(&page.title, item),
+        };
+        self.write(file, &page)
+    }
+
+    fn mod_item_out(&mut self, _item_name: &str) -> Result<(), Error> {
+        self.current.pop();
+        self.stripped_depth = self.stripped_depth.saturating_sub(1);
+        Ok(())
+    }
+
+    fn after_krate(
+        &mut self,
+        _krate: &clean::Crate,
+        _diag: &rustc_errors::Handler,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl TextRenderer {
+    fn full_path(&self, name: &str) -> String {
+        format!("{}::{}", self.current.join("::"), name)
+    }
+
+    /// Returns the file of the man page of `item`, whose path is `path`. The `::` separators of
+    /// the path are replaced with dots, since colons can't appear in file names on every platform,
+    /// and the type of the item is added, since items of different namespaces can have the same
+    /// path. The page of the crate is only named after it.
+    fn man_page(&self, path: &str, item: &clean::Item) -> PathBuf {
+        let path = path.replace("::", ".");
+        let name = if item.is_crate() {
+            format!("{}.3", path)
+        } else {
+            format!("{}.{}.3", path, item.type_())
+        };
+        self.dst.join("man3").join(name)
+    }
+
+    fn module_dir(&self) -> PathBuf {
+        self.current.iter().fold(self.dst.clone(), |dir, name| dir.join(name))
+    }
+
+    fn write(&self, file: PathBuf, page: &Page) -> Result<(), Error> {
+        let contents = match self.style {
+            Style::Plain => page.print_plain(),
+            Style::Man => page.print_man(&self.source),
+        };
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir).map_err(|e| Error::new(e, dir))?;
+        }
+        fs::write(&file, contents).map_err(|e| Error::new(e, &file))
+    }
+
+    /// The sections of the page of an item that isn't a module.
+    fn item_sections(&self, item: &clean::Item) -> Vec<Section> {
+        let mut sections = vec![];
+        if let Some(decl) = declaration(item) {
+            sections.push(Section::new("Synopsis", vec![Content::Code(decl)]));
+        }
+        let docs = docs(item);
+        if !docs.is_empty() {
+            sections.push(Section::new("Description", vec![Content::Docs(docs)]));
+        }
+
+        match item.inner {
+            clean::StructItem(clean::Struct { ref fields, .. })
+            | clean::UnionItem(clean::Union { ref fields, .. }) => {
+                push_entries(&mut sections, "Fields", fields.iter());
+            }
+            clean::EnumItem(ref e) => push_entries(&mut sections, "Variants", e.variants.iter()),
+            clean::TraitItem(ref t) => {
+                let of_type = move |ty: ItemType| t.items.iter().filter(move |it| it.type_() == ty);
+                push_entries(&mut sections, "Associated Types", of_type(ItemType::AssocType));
+                push_entries(&mut sections, "Associated Constants", of_type(ItemType::AssocConst));
+                push_entries(&mut sections, "Required Methods", of_type(ItemType::TyMethod));
+                push_entries(&mut sections, "Provided Methods", of_type(ItemType::Method));
+
+                let implementors = self.cache.implementors.get(&item.def_id);
+                let implementors = implementors.into_iter().flatten();
+                let implementors = implementors.map(|i| impl_entry(i.inner_impl())).collect();
+                push_section(&mut sections, "Implementors", implementors);
+            }
+            _ => {}
+        }
+
+        // The implementations of a type, which were moved into the cache.
+        let impls = self.cache.impls.get(&item.def_id).map(|impls| &impls[..]).unwrap_or(&[]);
+        let (inherent, traits): (Vec<_>, Vec<_>) =
+            impls.iter().map(|i| i.inner_impl()).partition(|i| i.trait_.is_none());
+        push_entries(&mut sections, "Methods", inherent.iter().flat_map(|i| i.items.iter()));
+        let traits = traits.iter().filter(|i| !i.synthetic && i.blanket_impl.is_none());
+        let traits = traits.map(|i| impl_entry(i)).collect();
+        push_section(&mut sections, "Trait Implementations", traits);
+
+        sections
+    }
+}
+
+/// The sections listing the items of a module, by kind, with their summary lines.
+fn module_sections(items: &[clean::Item]) -> Vec<Section> {
+    let mut items: Vec<_> = items.iter().filter(|it| !it.is_stripped()).collect();
+    items.sort_by(|a, b| (a.type_(), &a.name).cmp(&(b.type_(), &b.name)));
+
+    let mut sections: Vec<Section> = vec![];
+    for item in items {
+        let term = match item.inner {
+            clean::ExternCrateItem(..) | clean::ImportItem(..) => declaration(item),
+            _ => item.name.clone(),
+        };
+        let term = match term {
+            Some(term) => term,
+            None => continue,
+        };
+        let summary = summary(item);
+        let docs = if summary.is_empty() { vec![] } else { vec![TextBlock::Paragraph(summary)] };
+
+        let heading = item_ty_to_strs(&item.type_()).1;
+        match sections.last_mut() {
+            Some(section) if section.heading == heading => {}
+            _ => sections.push(Section::new(heading, vec![])),
+        }
+        sections.last_mut().unwrap().contents.push(Content::Entry { term, docs });
+    }
+    sections
+}
+
+/// Adds a section with an entry for each item of `items` that wasn't stripped from the
+/// documentation, if there are any.
+fn push_entries<'a>(
+    sections: &mut Vec<Section>,
+    heading: &str,
+    items: impl Iterator<Item = &'a clean::Item>,
+) {
+    let entries = items
+        .filter(|it| !it.is_stripped())
+        .filter_map(|it| Some(Content::Entry { term: declaration(it)?, docs: docs(it) }))
+        .collect();
+    push_section(sections, heading, entries);
+}
+
+fn impl_entry(impl_: &clean::Impl) -> Content {
+    Content::Entry { term: format!("{:#}", impl_.print()), docs: vec![] }
+}
+
+fn push_section(sections: &mut Vec<Section>, heading: &str, contents: Vec<Content>) {
+    if !contents.is_empty() {
+        sections.push(Section::new(heading, contents));
+    }
+}
+
+fn docs(item: &clean::Item) -> Vec<TextBlock> {
+    match item.collapsed_doc_value() {
+        Some(doc) => MarkdownText(&doc, &item.links()).into_blocks(),
+        None => vec![],
+    }
+}
+
+fn summary(item: &clean::Item) -> String {
+    item.doc_value().map(plain_summary_line).unwrap_or_default()
+}
+
+/// Prints the declaration of `item`, without its body. Items without a declaration, such as
+/// primitive types, return `None`.
+fn declaration(item: &clean::Item) -> Option<String> {
+    // Glob imports are the only items without a name.
+    if let clean::ImportItem(ref import) = item.inner {
+        return Some(format!("{}{:#}", item.visibility.print_with_space(), import.print()));
+    }
+    let name = item.name.as_ref()?;
+    let vis = item.visibility.print_with_space();
+    let decl = match item.inner {
+        clean::FunctionItem(ref f) | clean::ForeignFunctionItem(ref f) => {
+            function(item, &f.header, &f.generics, &f.decl)
+        }
+        clean::MethodItem(ref m) => function(item, &m.header, &m.generics, &m.decl),
+        clean::TyMethodItem(ref m) => {
+            format!("{};", function(item, &m.header, &m.generics, &m.decl))
+        }
+        clean::StructItem(ref s) => {
+            let head = format!("{}struct {}{:#}", vis, name, s.generics.print());
+            fields(head, &s.generics, s.struct_type, &s.fields, s.fields_stripped)
+        }
+        clean::UnionItem(ref u) => {
+            let head = format!("{}union {}{:#}", vis, name, u.generics.print());
+            fields(head, &u.generics, u.struct_type, &u.fields, u.fields_stripped)
+        }
+        clean::EnumItem(ref e) => {
+            let mut decl = format!(
+                "{}enum {}{:#}{:#} {{",
+                vis,
+                name,
+                e.generics.print(),
+                WhereClause { gens: &e.generics, indent: 0, end_newline: true }
+            );
+            for variant in e.variants.iter().filter(|v| !v.is_stripped()) {
+                decl.push_str(&format!("\n    {},", declaration(variant)?));
+            }
+            if e.variants_stripped {
+                decl.push_str("\n    // some variants omitted");
+            }
+            decl + "\n}"
+        }
+        clean::VariantItem(ref v) => match v.kind {
+            clean::VariantKind::CLike => name.clone(),
+            clean::VariantKind::Tuple(ref tys) => {
+                let tys: Vec<_> = tys.iter().map(|ty| format!("{:#}", ty.print())).collect();
+                format!("{}({})", name, tys.join(", "))
+            }
+            clean::VariantKind::Struct(ref s) => {
+                let fields: Vec<_> = s.fields.iter().filter_map(declaration).collect();
+                format!("{} {{ {} }}", name, fields.join(", "))
+            }
+        },
+        clean::StructFieldItem(ref ty) => format!("{}{}: {:#}", vis, name, ty.print()),
+        clean::TypedefItem(ref t, _) => format!(
+            "{}type {}{:#}{:#} = {:#};",
+            vis,
+            name,
+            t.generics.print(),
+            WhereClause { gens: &t.generics, indent: 0, end_newline: false },
+            t.type_.print()
+        ),
+        clean::OpaqueTyItem(ref t, _) => format!(
+            "{}type {}{:#}{:#} = impl {:#};",
+            vis,
+            name,
+            t.generics.print(),
+            WhereClause { gens: &t.generics, indent: 0, end_newline: false },
+            print_generic_bounds(&t.bounds)
+        ),
+        clean::StaticItem(ref s) | clean::ForeignStaticItem(ref s) => format!(
+            "{}static {}{}: {:#};",
+            vis,
+            s.mutability.print_with_space(),
+            name,
+            s.type_.print()
+        ),
+        clean::ConstantItem(ref c) => {
+            let value = if c.value.is_some() || c.is_literal {
+                format!(" = {}", c.expr)
+            } else {
+                String::new()
+            };
+            format!("{}const {}: {:#}{};", vis, name, c.type_.print(), value)
+        }
+        clean::AssocConstItem(ref ty, ref default) => {
+            let default = default.as_ref().map(|d| format!(" = {}", d)).unwrap_or_default();
+            format!("{}const {}: {:#}{};", vis, name, ty.print(), default)
+        }
+        clean::AssocTypeItem(ref bounds, ref default) => {
+            let mut decl = format!("type {}", name);
+            if !bounds.is_empty() {
+                decl.push_str(&format!(": {:#}", print_generic_bounds(bounds)));
+            }
+            if let Some(default) = default {
+                decl.push_str(&format!(" = {:#}", default.print()));
+            }
+            decl + ";"
+        }
+        clean::TraitItem(ref t) => {
+            let mut decl = format!(
+                "{}{}{}trait {}{:#}",
+                vis,
+                t.unsafety.print_with_space(),
+                if t.is_auto { "auto " } else { "" },
+                name,
+                t.generics.print()
+            );
+            if !t.bounds.is_empty() {
+                let bounds = print_generic_bounds(&t.bounds);
+                decl.push_str(&format!(": {:#}", bounds));
+            }
+            decl.push_str(&format!(
+                "{:#} {{",
+                WhereClause { gens: &t.generics, indent: 0, end_newline: true }
+            ));
+            for it in t.items.iter().filter(|it| !it.is_stripped()) {
+                if let Some(item_decl) = declaration(it) {
+                    let item_decl = match it.inner {
+                        clean::MethodItem(_) => format!("{} {{ ... }}", item_decl),
+                        _ => item_decl,
+                    };
+                    decl.push_str(&format!("\n    {}", item_decl));
+                }
+            }
+            decl + "\n}"
+        }
+        clean::TraitAliasItem(ref t) => format!(
+            "trait {}{:#} = {:#};",
+            name,
+            t.generics.print(),
+            print_generic_bounds(&t.bounds)
+        ),
+        clean::MacroItem(ref m) => m.source.trim_end().to_owned(),
+        clean::ProcMacroItem(ref m) => match m.kind {
+            MacroKind::Bang => format!("{}!() {{ /* proc-macro */ }}", name),
+            MacroKind::Attr => format!("#[{}]", name),
+            MacroKind::Derive => format!("#[derive({})]", name),
+        },
+        clean::ExternCrateItem(ref src, ref rename) => match rename {
+            Some(rename) => format!("{}extern crate {} as {};", vis, src, rename),
+            None => format!("{}extern crate {};", vis, src),
+        },
+        clean::ForeignTypeItem => format!("{}type {};", vis, name),
+        _ => return None,
+    };
+    Some(decl)
+}
+
+/// Prints the declaration of a function or a method.
+fn function(
+    item: &clean::Item,
+    header: &rustc_hir::FnHeader,
+    generics: &clean::Generics,
+    decl: &clean::FnDecl,
+) -> String {
+    let head = format!(
+        "{}{}{}{}{:#}fn {}{:#}",
+        item.visibility.print_with_space(),
+        header.constness.print_with_space(),
+        header.asyncness.print_with_space(),
+        header.unsafety.print_with_space(),
+        print_abi_with_space(header.abi),
+        item.name.as_ref().unwrap(),
+        generics.print()
+    );
+    let decl = Function { decl, header_len: head.len(), indent: 0, asyncness: header.asyncness };
+    format!(
+        "{}{:#}{:#}",
+        head,
+        decl.print(),
+        WhereClause { gens: generics, indent: 0, end_newline: true }
+    )
+}
+
+/// Prints the fields of a struct or a union after its `head`, which is the declaration up to
+/// its generics.
+fn fields(
+    head: String,
+    generics: &clean::Generics,
+    struct_type: doctree::StructType,
+    fields: &[clean::Item],
+    fields_stripped: bool,
+) -> String {
+    let visible = fields.iter().filter(|f| !f.is_stripped()).filter_map(declaration);
+    match struct_type {
+        doctree::Plain => {
+            let mut decl = format!(
+                "{}{:#} {{",
+                head,
+                WhereClause { gens: generics, indent: 0, end_newline: true }
+            );
+            for field in visible {
+                decl.push_str(&format!("\n    {},", field));
+            }
+            if fields_stripped {
+                decl.push_str("\n    // some fields omitted");
+            }
+            decl + "\n}"
+        }
+        doctree::Tuple => {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|f| match f.inner {
+                    clean::StructFieldItem(ref ty) => {
+                        format!("{}{:#}", f.visibility.print_with_space(), ty.print())
+                    }
+                    _ => "_".to_owned(),
+                })
+                .collect();
+            format!(
+                "{}({}){:#};",
+                head,
+                fields.join(", "),
+                WhereClause { gens: generics, indent: 0, end_newline: false }
+            )
+        }
+        doctree::Unit => {
+            format!("{}{:#};", head, WhereClause { gens: generics, indent: 0, end_newline: false })
+        }
+    }
+}
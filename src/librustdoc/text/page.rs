@@ -0,0 +1,281 @@
+//! The pages of the text backend, and how they are printed as plain text and as man pages.
+
+use std::fmt::Write;
+
+use crate::html::markdown::TextBlock;
+
+#[cfg(test)]
+mod tests;
+
+/// The width the paragraphs of plain text pages are wrapped at.
+const WIDTH: usize = 80;
+/// The indentation of the contents of the sections of plain text pages.
+const INDENT: usize = 4;
+
+/// The documentation of a module or an item.
+crate struct Page {
+    /// The full path of the item, such as `std::vec::Vec`.
+    crate title: String,
+    /// The first line of the documentation of the item.
+    crate summary: String,
+    crate sections: Vec<Section>,
+}
+
+crate struct Section {
+    crate heading: String,
+    crate contents: Vec<Content>,
+}
+
+crate enum Content {
+    /// The documentation of the item the page is about.
+    Docs(Vec<TextBlock>),
+    /// Code, such as the declaration of the item.
+    Code(String),
+    /// An item shown on the page of another one, such as a field or a method, with its
+    /// declaration and its documentation.
+    Entry { term: String, docs: Vec<TextBlock> },
+}
+
+impl Section {
+    crate fn new(heading: &str, contents: Vec<Content>) -> Section {
+        Section { heading: heading.to_owned(), contents }
+    }
+}
+
+impl Page {
+    /// Prints the page as plain text, with the contents of the sections indented below their
+    /// uppercase headings.
+    crate fn print_plain(&self) -> String {
+        let mut out = String::new();
+        let indent = " ".repeat(INDENT);
+        out.push_str("NAME\n");
+        let name = if self.summary.is_empty() {
+            self.title.clone()
+        } else {
+            format!("{} - {}", self.title, self.summary)
+        };
+        for line in wrap(&name, WIDTH - INDENT) {
+            writeln!(out, "{}{}", indent, line).unwrap();
+        }
+
+        for section in &self.sections {
+            writeln!(out, "\n{}", section.heading.to_uppercase()).unwrap();
+            let mut lines = Vec::new();
+            for content in &section.contents {
+                if !lines.is_empty() {
+                    lines.push(String::new());
+                }
+                match content {
+                    Content::Docs(blocks) => lines.extend(plain_blocks(blocks, WIDTH - INDENT)),
+                    Content::Code(code) => lines.extend(code.lines().map(str::to_owned)),
+                    Content::Entry { term, docs } => {
+                        lines.extend(term.lines().map(str::to_owned));
+                        let docs = plain_blocks(docs, WIDTH - 2 * INDENT);
+                        lines.extend(docs.into_iter().map(|l| indent_line(&indent, l)));
+                    }
+                }
+            }
+            for line in lines {
+                writeln!(out, "{}", indent_line(&indent, line)).unwrap();
+            }
+        }
+        out
+    }
+
+    /// Prints the page as a section 3 man page, whose footer says which version of the crate
+    /// it documents.
+    crate fn print_man(&self, source: &str) -> String {
+        let mut out = String::new();
+        writeln!(
+            out,
+            ".TH \"{}\" 3 \"\" \"{}\" \"Rust documentation\"",
+            man_quoted(&self.title),
+            man_quoted(source)
+        )
+        .unwrap();
+        out.push_str(".SH NAME\n");
+        if self.summary.is_empty() {
+            man_text(&mut out, &self.title);
+        } else {
+            writeln!(out, "{} \\- {}", man_escape(&self.title), man_escape(&self.summary)).unwrap();
+        }
+
+        for section in &self.sections {
+            writeln!(out, ".SH \"{}\"", man_quoted(&section.heading.to_uppercase())).unwrap();
+            for (i, content) in section.contents.iter().enumerate() {
+                match content {
+                    Content::Docs(blocks) => man_blocks(&mut out, blocks, false),
+                    Content::Code(code) => {
+                        if i > 0 {
+                            out.push_str(".PP\n");
+                        }
+                        man_code(&mut out, code);
+                    }
+                    Content::Entry { term, docs } => {
+                        out.push_str(".PP\n");
+                        for line in term.lines() {
+                            writeln!(out, "\\fB{}\\fR\n.br", man_escape(line)).unwrap();
+                        }
+                        if !docs.is_empty() {
+                            out.push_str(".RS 4\n");
+                            man_blocks(&mut out, docs, true);
+                            out.push_str(".RE\n");
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Prints `blocks` as lines of at most `width` characters, except for code, which isn't wrapped.
+/// Blocks are separated by an empty line, except for the consecutive items of a list.
+fn plain_blocks(blocks: &[TextBlock], width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut previous_item = false;
+    for block in blocks {
+        let is_item = matches!(block, TextBlock::Item(..));
+        let continues_list = is_item && previous_item;
+        if !lines.is_empty() && !continues_list {
+            lines.push(String::new());
+        }
+        previous_item = is_item;
+
+        match block {
+            TextBlock::Heading(level, text) => {
+                let underline = if *level == 1 { "=" } else { "-" };
+                lines.push(text.clone());
+                lines.push(underline.repeat(text.chars().count()));
+            }
+            TextBlock::Paragraph(text) => {
+                lines.extend(text.lines().flat_map(|line| wrap(line, width)));
+            }
+            TextBlock::Code(code) => {
+                lines.extend(code.lines().map(|line| indent_line("    ", line.to_owned())));
+            }
+            TextBlock::Item(marker, blocks) => {
+                let indent = " ".repeat(marker.chars().count() + 1);
+                let inner = plain_blocks(blocks, width.saturating_sub(indent.len()));
+                for (i, line) in inner.into_iter().enumerate() {
+                    if i == 0 {
+                        lines.push(format!("{} {}", marker, line).trim_end().to_owned());
+                    } else {
+                        lines.push(indent_line(&indent, line));
+                    }
+                }
+            }
+            TextBlock::Quote(blocks) => {
+                let inner = plain_blocks(blocks, width.saturating_sub(2));
+                lines.extend(inner.into_iter().map(|line| format!("> {}", line).trim_end().into()));
+            }
+            TextBlock::Rule => lines.push("-".repeat(width)),
+        }
+    }
+    lines
+}
+
+fn indent_line(indent: &str, line: String) -> String {
+    if line.is_empty() { line } else { format!("{}{}", indent, line) }
+}
+
+/// Splits `text` into lines of at most `width` characters at its whitespace. Words longer than
+/// `width` get a line of their own.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Prints `blocks` as man page requests. Headings of nested documentation, such as the
+/// documentation of a field, can't be subsections, and are only printed in bold.
+fn man_blocks(out: &mut String, blocks: &[TextBlock], nested: bool) {
+    for block in blocks {
+        match block {
+            TextBlock::Heading(_, text) if !nested => {
+                writeln!(out, ".SS \"{}\"", man_quoted(text)).unwrap();
+            }
+            TextBlock::Heading(_, text) => {
+                writeln!(out, ".PP\n\\fB{}\\fR", man_escape(text)).unwrap();
+            }
+            TextBlock::Paragraph(text) => {
+                out.push_str(".PP\n");
+                man_paragraph(out, text);
+            }
+            TextBlock::Code(code) => {
+                out.push_str(".PP\n.RS 4\n");
+                man_code(out, code);
+                out.push_str(".RE\n");
+            }
+            TextBlock::Item(marker, blocks) => {
+                writeln!(out, ".IP \"{}\" 4", man_quoted(marker)).unwrap();
+                let blocks = match blocks.split_first() {
+                    Some((TextBlock::Paragraph(text), rest)) => {
+                        man_paragraph(out, text);
+                        rest
+                    }
+                    _ => blocks,
+                };
+                if !blocks.is_empty() {
+                    out.push_str(".RS\n");
+                    man_blocks(out, blocks, true);
+                    out.push_str(".RE\n");
+                }
+            }
+            TextBlock::Quote(blocks) => {
+                out.push_str(".RS 4\n");
+                man_blocks(out, blocks, true);
+                out.push_str(".RE\n");
+            }
+            TextBlock::Rule => out.push_str(".PP\n* * *\n"),
+        }
+    }
+}
+
+/// Prints a paragraph, keeping its hard line breaks.
+fn man_paragraph(out: &mut String, text: &str) {
+    for (i, line) in text.lines().enumerate() {
+        if i > 0 {
+            out.push_str(".br\n");
+        }
+        man_text(out, line.trim());
+    }
+}
+
+fn man_code(out: &mut String, code: &str) {
+    out.push_str(".nf\n");
+    for line in code.lines() {
+        man_text(out, line);
+    }
+    out.push_str(".fi\n");
+}
+
+/// Prints a line of text, escaped so that it isn't taken for a request or an escape sequence.
+fn man_text(out: &mut String, line: &str) {
+    if line.starts_with('.') || line.starts_with('\'') {
+        out.push_str("\\&");
+    }
+    out.push_str(&man_escape(line));
+    out.push('\n');
+}
+
+fn man_escape(text: &str) -> String {
+    text.replace('\\', "\\e")
+}
+
+/// Escapes the argument of a request, which is written between double quotes.
+fn man_quoted(arg: &str) -> String {
+    man_escape(arg).replace('"', "\\(dq")
+}
@@ -0,0 +1,106 @@
+use super::{wrap, Content, Page, Section};
+use crate::html::markdown::TextBlock;
+
+fn page() -> Page {
+    let synopsis = "pub struct Bar {\n    pub x: u8,\n}".to_owned();
+    Page {
+        title: "foo::Bar".to_owned(),
+        summary: "A struct.".to_owned(),
+        sections: vec![
+            Section::new("Synopsis", vec![Content::Code(synopsis)]),
+            Section::new(
+                "Description",
+                vec![Content::Docs(vec![
+                    TextBlock::Paragraph("A struct.".into()),
+                    TextBlock::Heading(1, "Examples".into()),
+                    TextBlock::Code(".x\n\\n".into()),
+                    TextBlock::Item("-".into(), vec![TextBlock::Paragraph("one".into())]),
+                    TextBlock::Item("-".into(), vec![TextBlock::Paragraph("two".into())]),
+                ])],
+            ),
+            Section::new(
+                "Fields",
+                vec![Content::Entry {
+                    term: "pub x: u8".into(),
+                    docs: vec![TextBlock::Paragraph("The \"x\".".into())],
+                }],
+            ),
+        ],
+    }
+}
+
+#[test]
+fn test_wrap() {
+    assert_eq!(wrap("a bb  ccc\ndddd", 6), vec!["a bb", "ccc", "dddd"]);
+    assert_eq!(wrap("abcdefgh ij", 4), vec!["abcdefgh", "ij"]);
+    assert!(wrap("   ", 4).is_empty());
+}
+
+#[test]
+fn test_print_plain() {
+    let expected = "\
+NAME
+    foo::Bar - A struct.
+
+SYNOPSIS
+    pub struct Bar {
+        pub x: u8,
+    }
+
+DESCRIPTION
+    A struct.
+
+    Examples
+    ========
+
+        .x
+        \\n
+
+    - one
+    - two
+
+FIELDS
+    pub x: u8
+        The \"x\".
+";
+    assert_eq!(page().print_plain(), expected);
+}
+
+#[test]
+fn test_print_man() {
+    let expected = "\
+.TH \"foo::Bar\" 3 \"\" \"foo 1.0\" \"Rust documentation\"
+.SH NAME
+foo::Bar \\- A struct.
+.SH \"SYNOPSIS\"
+.nf
+pub struct Bar {
+    pub x: u8,
+}
+.fi
+.SH \"DESCRIPTION\"
+.PP
+A struct.
+.SS \"Examples\"
+.PP
+.RS 4
+.nf
+\\&.x
+\\en
+.fi
+.RE
+.IP \"-\" 4
+one
+.IP \"-\" 4
+two
+.SH \"FIELDS\"
+.PP
+\\fBpub x: u8\\fR
+.br
+.RS 4
+.PP
+The \"x\".
+.RE
+";
+    assert_eq!(page().print_man("foo 1.0"), expected);
+}
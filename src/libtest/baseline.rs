@@ -0,0 +1,198 @@
+//! Saving the results of benchmarks under a name, so that later runs can be compared to them.
+//!
+//! A baseline is a text file with a header line, followed by a line per benchmark holding
+//! its name, the fields of its `stats::Summary` and the raw samples they were computed from,
+//! separated by tabs.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::bench::{BenchComparison, BenchSamples};
+use crate::stats::Summary;
+
+const HEADER: &str = "# libtest benchmark baseline, version 1";
+
+/// Number of `f64` values a `Summary` is stored as.
+const SUMMARY_FIELDS: usize = 14;
+
+#[derive(Debug, Clone, PartialEq)]
+struct SavedBench {
+    summary: Summary,
+    samples: Vec<f64>,
+}
+
+/// The results of the benchmarks saved under one name.
+#[derive(Debug, Default, PartialEq)]
+pub struct Baseline {
+    benches: BTreeMap<String, SavedBench>,
+}
+
+impl Baseline {
+    /// Loads the baseline previously saved under `name`.
+    pub fn load(name: &str) -> io::Result<Baseline> {
+        let path = baseline_path(name)?;
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            let msg = format!(
+                "couldn't read the benchmark baseline `{}` from {}: {}",
+                name,
+                path.display(),
+                e
+            );
+            io::Error::new(e.kind(), msg)
+        })?;
+        Baseline::parse(&contents).map_err(|msg| {
+            let msg = format!("malformed benchmark baseline {}: {}", path.display(), msg);
+            io::Error::new(io::ErrorKind::InvalidData, msg)
+        })
+    }
+
+    /// Loads the baseline saved under `name`, or returns an empty one if there is none yet.
+    pub fn load_or_default(name: &str) -> io::Result<Baseline> {
+        match Baseline::load(name) {
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(Baseline::default()),
+            res => res,
+        }
+    }
+
+    /// Saves the baseline under `name`, replacing any baseline previously saved under it.
+    pub fn save(&self, name: &str) -> io::Result<()> {
+        let path = baseline_path(name)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.serialize())
+    }
+
+    /// Records the result of a benchmark, replacing its previous result.
+    pub fn insert(&mut self, bench_name: &str, bs: &BenchSamples) {
+        let saved = SavedBench { summary: bs.ns_iter_summ, samples: bs.samples.clone() };
+        self.benches.insert(bench_name.to_owned(), saved);
+    }
+
+    /// Compares the result of a benchmark to its result in this baseline, if it has one.
+    pub fn compare(&self, bench_name: &str, bs: &BenchSamples) -> Option<BenchComparison> {
+        self.benches
+            .get(bench_name)
+            .map(|saved| BenchComparison::new(&saved.summary, &saved.samples, bs))
+    }
+
+    pub(crate) fn parse(contents: &str) -> Result<Baseline, String> {
+        let mut lines = contents.lines();
+        if lines.next() != Some(HEADER) {
+            return Err("unsupported format".to_owned());
+        }
+
+        let mut benches = BTreeMap::new();
+        for (i, line) in lines.enumerate() {
+            let malformed = || format!("line {} is malformed", i + 2);
+            let mut parts = line.split('\t');
+            let (name, summary, samples) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(name), Some(summary), Some(samples)) => (name, summary, samples),
+                _ => return Err(malformed()),
+            };
+            let summary = parse_f64s(summary).ok_or_else(malformed)?;
+            let samples = parse_f64s(samples).ok_or_else(malformed)?;
+            if parts.next().is_some() || summary.len() != SUMMARY_FIELDS {
+                return Err(malformed());
+            }
+
+            let summary = Summary {
+                sum: summary[0],
+                min: summary[1],
+                max: summary[2],
+                mean: summary[3],
+                median: summary[4],
+                var: summary[5],
+                std_dev: summary[6],
+                std_dev_pct: summary[7],
+                median_abs_dev: summary[8],
+                median_abs_dev_pct: summary[9],
+                quartiles: (summary[10], summary[11], summary[12]),
+                iqr: summary[13],
+            };
+            benches.insert(unescape_name(name), SavedBench { summary, samples });
+        }
+
+        Ok(Baseline { benches })
+    }
+
+    pub(crate) fn serialize(&self) -> String {
+        let mut out = String::new();
+        out.push_str(HEADER);
+        out.push('\n');
+        for (name, saved) in &self.benches {
+            let s = &saved.summary;
+            let summary = [
+                s.sum,
+                s.min,
+                s.max,
+                s.mean,
+                s.median,
+                s.var,
+                s.std_dev,
+                s.std_dev_pct,
+                s.median_abs_dev,
+                s.median_abs_dev_pct,
+                s.quartiles.0,
+                s.quartiles.1,
+                s.quartiles.2,
+                s.iqr,
+            ];
+            out.push_str(&escape_name(name));
+            out.push('\t');
+            out.push_str(&join_f64s(&summary));
+            out.push('\t');
+            out.push_str(&join_f64s(&saved.samples));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Returns whether `name` can be used to name a baseline.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Baselines are stored next to the test executable, and are specific to it.
+fn baseline_path(name: &str) -> io::Result<PathBuf> {
+    let exe = env::current_exe()?;
+    let exe_name = exe.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let dir = exe.parent().map(|dir| dir.join("bench-baselines")).unwrap_or_default();
+    Ok(dir.join(format!("{}-{}.txt", exe_name, name)))
+}
+
+// The `Display` implementation of `f64` prints the shortest string that parses back to the
+// same value, so the samples are stored losslessly.
+fn join_f64s(values: &[f64]) -> String {
+    values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+fn parse_f64s(s: &str) -> Option<Vec<f64>> {
+    s.split(' ').filter(|v| !v.is_empty()).map(|v| v.parse().ok()).collect()
+}
+
+fn escape_name(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+fn unescape_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('t')) => out.push('\t'),
+            ('\\', Some('n')) => out.push('\n'),
+            ('\\', Some('\\')) => out.push('\\'),
+            _ => {
+                out.push(c);
+                continue;
+            }
+        }
+        chars.next();
+    }
+    out
+}
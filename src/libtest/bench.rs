@@ -22,6 +22,7 @@ use std::time::{Duration, Instant};
 pub struct Bencher {
     mode: BenchMode,
     summary: Option<stats::Summary>,
+    samples: Vec<f64>,
     pub bytes: u64,
 }
 
@@ -36,7 +37,9 @@ impl Bencher {
             return;
         }
 
-        self.summary = Some(iter(&mut inner));
+        let (summary, samples) = iter_with_samples(&mut inner);
+        self.summary = Some(summary);
+        self.samples = samples;
     }
 
     pub fn bench<F>(&mut self, mut f: F) -> Option<stats::Summary>
@@ -51,7 +54,53 @@ impl Bencher {
 #[derive(Debug, Clone, PartialEq)]
 pub struct BenchSamples {
     pub ns_iter_summ: stats::Summary,
+    /// The nanoseconds per iteration `ns_iter_summ` was computed from, before the outliers were
+    /// winsorized.
+    pub samples: Vec<f64>,
     pub mb_s: usize,
+    /// Comparison with the same benchmark in the baseline passed to `--baseline`.
+    pub baseline: Option<BenchComparison>,
+}
+
+/// Benchmarks whose p-value is below this are considered to have changed.
+pub const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// How a benchmark performed compared to a previous run of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchComparison {
+    /// Summary of the previous run.
+    pub baseline: stats::Summary,
+    /// Change of the median time per iteration, in percent of the previous median.
+    pub change_pct: f64,
+    /// p-value of a Mann-Whitney U test between the samples of both runs.
+    pub p_value: f64,
+}
+
+impl BenchComparison {
+    pub fn new(baseline: &stats::Summary, baseline_samples: &[f64], bs: &BenchSamples) -> Self {
+        let change_pct = if baseline.median == 0.0 {
+            0.0
+        } else {
+            (bs.ns_iter_summ.median - baseline.median) / baseline.median * 100.0
+        };
+        let p_value = stats::mann_whitney_u(baseline_samples, &bs.samples);
+        BenchComparison { baseline: *baseline, change_pct, p_value }
+    }
+
+    pub fn is_significant(&self) -> bool {
+        self.p_value < SIGNIFICANCE_LEVEL
+    }
+
+    /// Describes the change in a word, e.g. "regressed".
+    pub fn verdict(&self) -> &'static str {
+        if !self.is_significant() {
+            "no change"
+        } else if self.change_pct > 0.0 {
+            "regressed"
+        } else {
+            "improved"
+        }
+    }
 }
 
 pub fn fmt_bench_samples(bs: &BenchSamples) -> String {
@@ -71,6 +120,16 @@ pub fn fmt_bench_samples(bs: &BenchSamples) -> String {
     if bs.mb_s != 0 {
         output.write_fmt(format_args!(" = {} MB/s", bs.mb_s)).unwrap();
     }
+    if let Some(ref comparison) = bs.baseline {
+        output
+            .write_fmt(format_args!(
+                " ({:+.2}%, p = {:.3}: {})",
+                comparison.change_pct,
+                comparison.p_value,
+                comparison.verdict()
+            ))
+            .unwrap();
+    }
     output
 }
 
@@ -114,6 +173,14 @@ where
 }
 
 pub fn iter<T, F>(inner: &mut F) -> stats::Summary
+where
+    F: FnMut() -> T,
+{
+    iter_with_samples(inner).0
+}
+
+/// Like `iter`, but also returns the samples the summary was computed from, as they were measured.
+fn iter_with_samples<T, F>(inner: &mut F) -> (stats::Summary, Vec<f64>)
 where
     F: FnMut() -> T,
 {
@@ -149,6 +216,9 @@ where
             *p = ns as f64 / (5 * n) as f64;
         }
 
+        // The summary leaves the outliers out, but the samples are returned as they were
+        // measured, so that they can be compared with other runs.
+        let samples5 = samples.to_vec();
         stats::winsorize(samples, 5.0);
         let summ5 = stats::Summary::new(samples);
 
@@ -160,13 +230,13 @@ where
             && summ.median_abs_dev_pct < 1.0
             && summ.median - summ5.median < summ5.median_abs_dev
         {
-            return summ5;
+            return (summ5, samples5);
         }
 
         total_run = total_run + loop_run;
         // Longest we ever run for is 3s.
         if total_run > Duration::from_secs(3) {
-            return summ5;
+            return (summ5, samples5);
         }
 
         // If we overflow here just return the results so far. We check a
@@ -176,7 +246,7 @@ where
         n = match n.checked_mul(10) {
             Some(_) => n * 2,
             None => {
-                return summ5;
+                return (summ5, samples5);
             }
         };
     }
@@ -186,7 +256,7 @@ pub fn benchmark<F>(desc: TestDesc, monitor_ch: Sender<CompletedTest>, nocapture
 where
     F: FnMut(&mut Bencher),
 {
-    let mut bs = Bencher { mode: BenchMode::Auto, summary: None, bytes: 0 };
+    let mut bs = Bencher { mode: BenchMode::Auto, summary: None, samples: Vec::new(), bytes: 0 };
 
     let data = Arc::new(Mutex::new(Vec::new()));
     let oldio = if !nocapture {
@@ -211,14 +281,24 @@ where
             let ns_iter = cmp::max(ns_iter_summ.median as u64, 1);
             let mb_s = bs.bytes * 1000 / ns_iter;
 
-            let bs = BenchSamples { ns_iter_summ, mb_s: mb_s as usize };
+            let bs = BenchSamples {
+                ns_iter_summ,
+                samples: bs.samples,
+                mb_s: mb_s as usize,
+                baseline: None,
+            };
             TestResult::TrBench(bs)
         }
         Ok(None) => {
             // iter not called, so no data.
             // FIXME: error in this case?
             let samples: &mut [f64] = &mut [0.0_f64; 1];
-            let bs = BenchSamples { ns_iter_summ: stats::Summary::new(samples), mb_s: 0 };
+            let bs = BenchSamples {
+                ns_iter_summ: stats::Summary::new(samples),
+                samples: samples.to_vec(),
+                mb_s: 0,
+                baseline: None,
+            };
             TestResult::TrBench(bs)
         }
         Err(_) => TestResult::TrFailed,
@@ -233,6 +313,6 @@ pub fn run_once<F>(f: F)
 where
     F: FnMut(&mut Bencher),
 {
-    let mut bs = Bencher { mode: BenchMode::Single, summary: None, bytes: 0 };
+    let mut bs = Bencher { mode: BenchMode::Single, summary: None, samples: Vec::new(), bytes: 0 };
     bs.bench(f);
 }
@@ -2,9 +2,11 @@
 
 use std::env;
 use std::path::PathBuf;
+use std::time::Duration;
 
+use super::baseline;
 use super::helpers::isatty;
-use super::options::{ColorConfig, Options, OutputFormat, RunIgnored};
+use super::options::{ColorConfig, Options, OutputFormat, RunIgnored, TestShard};
 use super::time::TestTimeOptions;
 
 #[derive(Debug)]
@@ -13,6 +15,7 @@ pub struct TestOpts {
     pub filter: Option<String>,
     pub filter_exact: bool,
     pub force_run_in_process: bool,
+    pub isolate: bool,
     pub exclude_should_panic: bool,
     pub run_ignored: RunIgnored,
     pub run_tests: bool,
@@ -24,6 +27,13 @@ pub struct TestOpts {
     pub test_threads: Option<usize>,
     pub skip: Vec<String>,
     pub time_options: Option<TestTimeOptions>,
+    pub test_timeout: Option<Duration>,
+    pub shuffle: bool,
+    pub shuffle_seed: Option<u64>,
+    pub save_baseline: Option<String>,
+    pub baseline: Option<String>,
+    pub shard: Option<TestShard>,
+    pub retries: usize,
     pub options: Options,
 }
 
@@ -47,6 +57,12 @@ fn optgroups() -> getopts::Options {
     opts.optflag("", "include-ignored", "Run ignored and not ignored tests")
         .optflag("", "ignored", "Run only ignored tests")
         .optflag("", "force-run-in-process", "Forces tests to run in-process when panic=abort")
+        .optflag(
+            "",
+            "isolate",
+            "Run each test in its own subprocess, even when panic=unwind.
+            The number of tests running at once is set with --test-threads",
+        )
         .optflag("", "exclude-should-panic", "Excludes tests marked as should_panic")
         .optflag("", "test", "Run tests and not benchmarks")
         .optflag("", "bench", "Run benchmarks instead of tests")
@@ -101,8 +117,9 @@ fn optgroups() -> getopts::Options {
             "Configure formatting of output:
             pretty = Print verbose output;
             terse  = Display one character per test;
-            json   = Output a json document",
-            "pretty|terse|json",
+            json   = Output a json document
+            junit  = Output a JUnit document",
+            "pretty|terse|json|junit",
         )
         .optflag("", "show-output", "Show captured stdout of successful tests")
         .optopt(
@@ -143,6 +160,51 @@ fn optgroups() -> getopts::Options {
 
             `CRITICAL_TIME` here means the limit that should not be exceeded by test.
             ",
+        )
+        .optopt(
+            "",
+            "test-timeout",
+            "Fail tests which take longer than SECS seconds to run.
+
+            Tests running in a subprocess (e.g. with panic=abort) are killed
+            once they time out. Tests running in the test runner process can't
+            be stopped, so the first one to time out aborts the whole run.
+
+            The timeout of a single test can be overridden with the
+            `#[test_timeout(SECS)]` attribute.",
+            "SECS",
+        )
+        .optflag("", "shuffle", "Run tests in random order")
+        .optopt(
+            "",
+            "shuffle-seed",
+            "Run tests in random order; seed the random number generator with SEED",
+            "SEED",
+        )
+        .optopt("", "save-baseline", "Save the results of benchmarks under NAME", "NAME")
+        .optopt(
+            "",
+            "baseline",
+            "Compare the results of benchmarks to the ones saved under NAME",
+            "NAME",
+        )
+        .optopt(
+            "",
+            "shard-index",
+            "Only run the tests of the shard with index I (starting at 0), \
+             out of the ones given by --shard-count",
+            "I",
+        )
+        .optopt("", "shard-count", "Split the tests into N disjoint shards", "N")
+        .optopt(
+            "",
+            "retries",
+            "Rerun failing tests up to N times, and report the ones that
+            eventually pass as flaky.
+
+            The number of retries of a single test can be overridden with the
+            `#[test_retries(N)]` attribute.",
+            "N",
         );
     opts
 }
@@ -163,6 +225,32 @@ All tests have their standard output and standard error captured by default.
 This can be overridden with the --nocapture flag or setting RUST_TEST_NOCAPTURE
 environment variable to a value other than "0". Logging is not captured by default.
 
+With -Z unstable-options, the tests can be run in a random order with the
+--shuffle flag or by setting the RUST_TEST_SHUFFLE environment variable to a
+value other than "0". The seed used for the run is printed in its header, and
+can be passed back with --shuffle-seed or RUST_TEST_SHUFFLE_SEED to replay the
+exact same order.
+
+With -Z unstable-options, the results of benchmarks can be saved with
+--save-baseline NAME, and later runs compared to them with --baseline NAME.
+The change of the median time per iteration is then reported along with the
+p-value of a Mann-Whitney U test on the samples of both runs; changes with a
+p-value below 0.05 are reported as regressions or improvements.
+
+With -Z unstable-options, the tests can be split into disjoint shards, e.g. to
+spread them over several machines, by passing --shard-count N along with
+--shard-index I to run the I-th shard. The shard of a test only depends on its
+name. Combined with --list, the tests of the shard are listed.
+
+Tests share the process of the test runner, and so any global state like
+environment variables or the current directory. With -Z unstable-options, the
+--isolate flag runs each test in a fresh child process instead, while still
+capturing its output and reporting it like any other test.
+
+With -Z unstable-options, failing tests can be rerun with --retries N. A test
+that passes on a later attempt is reported as flaky, and doesn't fail the run.
+Only tests defined with the `#[test]` attribute can be rerun.
+
 Test Attributes:
 
     `#[test]`        - Indicates a function is a test to be run. This function
@@ -176,7 +264,13 @@ Test Attributes:
     `#[ignore]`       - When applied to a function which is already attributed as a
                         test, then the test runner will ignore these tests during
                         normal test runs. Running with --ignored or --include-ignored will run
-                        these tests."#,
+                        these tests.
+    `#[test_timeout(SECS)]` - This function (also labeled with `#[test]`) will fail
+                        if it runs for longer than SECS seconds, regardless of
+                        the --test-timeout option.
+    `#[test_retries(N)]` - This function (also labeled with `#[test]`) will be
+                        rerun up to N times if it fails, regardless of the
+                        --retries option."#,
         usage = options.usage(&message)
     );
 }
@@ -229,9 +323,17 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
 
     // Unstable flags
     let force_run_in_process = unstable_optflag!(matches, allow_unstable, "force-run-in-process");
+    let isolate = get_isolate(&matches, allow_unstable, force_run_in_process)?;
     let exclude_should_panic = unstable_optflag!(matches, allow_unstable, "exclude-should-panic");
     let include_ignored = unstable_optflag!(matches, allow_unstable, "include-ignored");
     let time_options = get_time_options(&matches, allow_unstable)?;
+    let test_timeout = get_test_timeout(&matches, allow_unstable)?;
+    let shuffle = get_shuffle(&matches, allow_unstable)?;
+    let shuffle_seed = get_shuffle_seed(&matches, allow_unstable)?;
+    let save_baseline = get_baseline_name(&matches, allow_unstable, "save-baseline")?;
+    let baseline = get_baseline_name(&matches, allow_unstable, "baseline")?;
+    let shard = get_shard(&matches, allow_unstable)?;
+    let retries = get_retries(&matches, allow_unstable)?;
 
     let quiet = matches.opt_present("quiet");
     let exact = matches.opt_present("exact");
@@ -256,6 +358,7 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         filter,
         filter_exact: exact,
         force_run_in_process,
+        isolate,
         exclude_should_panic,
         run_ignored,
         run_tests,
@@ -267,6 +370,13 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         test_threads,
         skip,
         time_options,
+        test_timeout,
+        shuffle,
+        shuffle_seed,
+        save_baseline,
+        baseline,
+        shard,
+        retries,
         options,
     };
 
@@ -307,6 +417,162 @@ fn get_time_options(
     Ok(options)
 }
 
+fn get_test_timeout(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<Duration>> {
+    let test_timeout = match matches.opt_str("test-timeout") {
+        Some(_) if !allow_unstable => {
+            return Err("The \"test-timeout\" flag is only accepted on the nightly compiler \
+                        with -Z unstable-options"
+                .into());
+        }
+        Some(n_str) => match n_str.parse::<u64>() {
+            Ok(0) => return Err("argument for --test-timeout must not be 0".to_string()),
+            Ok(n) => Some(Duration::from_secs(n)),
+            Err(e) => {
+                return Err(format!(
+                    "argument for --test-timeout must be a number > 0 \
+                     (error: {})",
+                    e
+                ));
+            }
+        },
+        None => None,
+    };
+
+    Ok(test_timeout)
+}
+
+fn get_isolate(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+    force_run_in_process: bool,
+) -> OptPartRes<bool> {
+    let isolate = unstable_optflag!(matches, allow_unstable, "isolate");
+    if isolate && force_run_in_process {
+        return Err(
+            "The \"isolate\" and \"force-run-in-process\" flags are mutually exclusive".to_string()
+        );
+    }
+
+    Ok(isolate)
+}
+
+fn get_baseline_name(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+    option_name: &str,
+) -> OptPartRes<Option<String>> {
+    let name = match matches.opt_str(option_name) {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    if !allow_unstable {
+        return Err(format!(
+            "The \"{}\" flag is only accepted on the nightly compiler with -Z unstable-options",
+            option_name
+        ));
+    }
+    if !baseline::is_valid_name(&name) {
+        return Err(format!(
+            "argument for --{} must only contain ASCII letters, digits, '-' and '_' (got {})",
+            option_name, name
+        ));
+    }
+
+    Ok(Some(name))
+}
+
+fn get_shard(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<Option<TestShard>> {
+    let parse = |option_name: &str| -> OptPartRes<Option<usize>> {
+        match matches.opt_str(option_name) {
+            Some(_) if !allow_unstable => Err(format!(
+                "The \"{}\" flag is only accepted on the nightly compiler with -Z unstable-options",
+                option_name
+            )),
+            Some(n_str) => n_str.parse::<usize>().map(Some).map_err(|e| {
+                format!("argument for --{} must be a number (error: {})", option_name, e)
+            }),
+            None => Ok(None),
+        }
+    };
+
+    match (parse("shard-index")?, parse("shard-count")?) {
+        (None, None) => Ok(None),
+        (Some(_), Some(0)) => Err("argument for --shard-count must be at least 1".to_string()),
+        (Some(index), Some(count)) if index >= count => {
+            Err(format!("argument for --shard-index must be less than the shard count ({})", count))
+        }
+        (Some(index), Some(count)) => Ok(Some(TestShard { index, count })),
+        _ => Err("--shard-index and --shard-count must be passed together".to_string()),
+    }
+}
+
+fn get_retries(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<usize> {
+    let retries = match matches.opt_str("retries") {
+        Some(_) if !allow_unstable => {
+            return Err("The \"retries\" flag is only accepted on the nightly compiler \
+                        with -Z unstable-options"
+                .into());
+        }
+        Some(n_str) => match n_str.parse::<usize>() {
+            Ok(n) => n,
+            Err(e) => {
+                return Err(format!("argument for --retries must be a number (error: {})", e));
+            }
+        },
+        None => 0,
+    };
+
+    Ok(retries)
+}
+
+fn get_shuffle(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<bool> {
+    let mut shuffle = unstable_optflag!(matches, allow_unstable, "shuffle");
+    if !shuffle && allow_unstable {
+        shuffle = match env::var("RUST_TEST_SHUFFLE") {
+            Ok(val) => &val != "0",
+            Err(_) => false,
+        };
+    }
+
+    Ok(shuffle)
+}
+
+fn get_shuffle_seed(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<Option<u64>> {
+    let mut shuffle_seed = match matches.opt_str("shuffle-seed") {
+        Some(n_str) => match n_str.parse::<u64>() {
+            Ok(_) if !allow_unstable => {
+                return Err("The \"shuffle-seed\" flag is only accepted on the nightly compiler \
+                            with -Z unstable-options"
+                    .into());
+            }
+            Ok(n) => Some(n),
+            Err(e) => {
+                return Err(format!(
+                    "argument for --shuffle-seed must be a number \
+                     (error: {})",
+                    e
+                ));
+            }
+        },
+        None => None,
+    };
+
+    if shuffle_seed.is_none() && allow_unstable {
+        shuffle_seed = match env::var("RUST_TEST_SHUFFLE_SEED") {
+            Ok(val) => match val.parse::<u64>() {
+                Ok(n) => Some(n),
+                Err(_) => panic!("RUST_TEST_SHUFFLE_SEED is `{}`, should be a number.", val),
+            },
+            Err(_) => None,
+        };
+    }
+
+    Ok(shuffle_seed)
+}
+
 fn get_test_threads(matches: &getopts::Matches) -> OptPartRes<Option<usize>> {
     let test_threads = match matches.opt_str("test-threads") {
         Some(n_str) => match n_str.parse::<usize>() {
@@ -341,10 +607,16 @@ fn get_format(
             }
             OutputFormat::Json
         }
+        Some("junit") => {
+            if !allow_unstable {
+                return Err("The \"junit\" format is only accepted on the nightly compiler".into());
+            }
+            OutputFormat::Junit
+        }
 
         Some(v) => {
             return Err(format!(
-                "argument for --format must be pretty, terse, or json (was \
+                "argument for --format must be pretty, terse, json or junit (was \
                  {})",
                 v
             ));
@@ -5,11 +5,12 @@ use std::io;
 use std::io::prelude::Write;
 
 use super::{
+    baseline::Baseline,
     bench::fmt_bench_samples,
     cli::TestOpts,
     event::{CompletedTest, TestEvent},
     filter_tests,
-    formatters::{JsonFormatter, OutputFormatter, PrettyFormatter, TerseFormatter},
+    formatters::{JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TerseFormatter},
     helpers::{concurrency::get_concurrency, metrics::MetricMap},
     options::{Options, OutputFormat},
     run_tests,
@@ -45,14 +46,17 @@ pub struct ConsoleTestState {
     pub total: usize,
     pub passed: usize,
     pub failed: usize,
+    pub flaky: usize,
     pub ignored: usize,
     pub allowed_fail: usize,
     pub filtered_out: usize,
     pub measured: usize,
+    pub not_run: usize,
     pub metrics: MetricMap,
     pub failures: Vec<(TestDesc, Vec<u8>)>,
     pub not_failures: Vec<(TestDesc, Vec<u8>)>,
     pub time_failures: Vec<(TestDesc, Vec<u8>)>,
+    pub flakes: Vec<(TestDesc, Vec<u8>)>,
     pub options: Options,
 }
 
@@ -68,14 +72,17 @@ impl ConsoleTestState {
             total: 0,
             passed: 0,
             failed: 0,
+            flaky: 0,
             ignored: 0,
             allowed_fail: 0,
             filtered_out: 0,
             measured: 0,
+            not_run: 0,
             metrics: MetricMap::new(),
             failures: Vec::new(),
             not_failures: Vec::new(),
             time_failures: Vec::new(),
+            flakes: Vec::new(),
             options: opts.options,
         })
     }
@@ -112,6 +119,8 @@ impl ConsoleTestState {
                     TestResult::TrAllowedFail => "failed (allowed)".to_owned(),
                     TestResult::TrBench(ref bs) => fmt_bench_samples(bs),
                     TestResult::TrTimedFail => "failed (time limit exceeded)".to_owned(),
+                    TestResult::TrTimedOut => "failed (timed out)".to_owned(),
+                    TestResult::TrFlaky(failures) => format!("flaky (failed {} times)", failures),
                 },
                 test.name,
             )
@@ -123,7 +132,13 @@ impl ConsoleTestState {
     }
 
     fn current_test_count(&self) -> usize {
-        self.passed + self.failed + self.ignored + self.measured + self.allowed_fail
+        self.passed
+            + self.failed
+            + self.flaky
+            + self.ignored
+            + self.measured
+            + self.allowed_fail
+            + self.not_run
     }
 }
 
@@ -211,6 +226,16 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
             st.failed += 1;
             st.time_failures.push((test, stdout));
         }
+        TestResult::TrTimedOut => {
+            st.failed += 1;
+            let mut stdout = stdout;
+            stdout.extend_from_slice(b"note: test did not finish within its timeout");
+            st.failures.push((test, stdout));
+        }
+        TestResult::TrFlaky(_) => {
+            st.flaky += 1;
+            st.flakes.push((test, stdout));
+        }
     }
 }
 
@@ -222,13 +247,16 @@ fn on_test_event(
     out: &mut dyn OutputFormatter,
 ) -> io::Result<()> {
     match (*event).clone() {
-        TestEvent::TeFiltered(ref filtered_tests) => {
+        TestEvent::TeFiltered(ref filtered_tests, shuffle_seed) => {
             st.total = filtered_tests.len();
-            out.write_run_start(filtered_tests.len())?;
+            out.write_run_start(filtered_tests.len(), shuffle_seed)?;
         }
         TestEvent::TeFilteredOut(filtered_out) => {
             st.filtered_out = filtered_out;
         }
+        TestEvent::TeAborted(not_run) => {
+            st.not_run = not_run;
+        }
         TestEvent::TeWait(ref test) => out.write_test_start(test)?,
         TestEvent::TeTimeout(ref test) => out.write_timeout(test)?,
         TestEvent::TeResult(completed_test) => {
@@ -236,9 +264,10 @@ fn on_test_event(
             let result = &completed_test.result;
             let exec_time = &completed_test.exec_time;
             let stdout = &completed_test.stdout;
+            let streams = completed_test.streams.as_ref();
 
             st.write_log_result(test, result, exec_time.as_ref())?;
-            out.write_result(test, result, exec_time.as_ref(), &*stdout, st)?;
+            out.write_result(test, result, exec_time.as_ref(), &*stdout, streams, st)?;
             handle_test_result(st, completed_test);
         }
     }
@@ -274,13 +303,42 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
             Box::new(TerseFormatter::new(output, opts.use_color(), max_name_len, is_multithreaded))
         }
         OutputFormat::Json => Box::new(JsonFormatter::new(output)),
+        OutputFormat::Junit => Box::new(JunitFormatter::new(output)),
     };
     let mut st = ConsoleTestState::new(opts)?;
 
-    run_tests(opts, tests, |x| on_test_event(&x, &mut st, &mut *out))?;
+    let baseline = match opts.baseline {
+        Some(ref name) => Some(Baseline::load(name)?),
+        None => None,
+    };
+    // Results are merged into the saved baseline, so that it also keeps
+    // the benchmarks which were filtered out of this run.
+    let mut saved_baseline = match opts.save_baseline {
+        Some(ref name) => Some(Baseline::load_or_default(name)?),
+        None => None,
+    };
+
+    run_tests(opts, tests, |mut event| {
+        if let TestEvent::TeResult(ref mut completed_test) = event {
+            if let TestResult::TrBench(ref mut bs) = completed_test.result {
+                let name = completed_test.desc.name.as_slice();
+                if let Some(ref baseline) = baseline {
+                    bs.baseline = baseline.compare(name, bs);
+                }
+                if let Some(ref mut saved_baseline) = saved_baseline {
+                    saved_baseline.insert(name, bs);
+                }
+            }
+        }
+        on_test_event(&event, &mut st, &mut *out)
+    })?;
 
     assert!(st.current_test_count() == st.total);
 
+    if let (Some(name), Some(saved_baseline)) = (&opts.save_baseline, &saved_baseline) {
+        saved_baseline.save(name)?;
+    }
+
     out.write_run_finish(&st)
 }
 
@@ -5,12 +5,22 @@ use super::test_result::TestResult;
 use super::time::TestExecTime;
 use super::types::TestDesc;
 
+/// The captured output of a test, split by the stream it was written to.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CapturedStreams {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
 #[derive(Debug, Clone)]
 pub struct CompletedTest {
     pub desc: TestDesc,
     pub result: TestResult,
     pub exec_time: Option<TestExecTime>,
+    /// All the captured output of the test, in the order it was written.
     pub stdout: Vec<u8>,
+    /// The same output split by stream, if it was captured separately.
+    pub streams: Option<CapturedStreams>,
 }
 
 impl CompletedTest {
@@ -20,7 +30,11 @@ impl CompletedTest {
         exec_time: Option<TestExecTime>,
         stdout: Vec<u8>,
     ) -> Self {
-        Self { desc, result, exec_time, stdout }
+        Self { desc, result, exec_time, stdout, streams: None }
+    }
+
+    pub fn with_streams(self, streams: CapturedStreams) -> Self {
+        Self { streams: Some(streams), ..self }
     }
 }
 
@@ -28,9 +42,10 @@ unsafe impl Send for CompletedTest {}
 
 #[derive(Debug, Clone)]
 pub enum TestEvent {
-    TeFiltered(Vec<TestDesc>),
+    TeFiltered(Vec<TestDesc>, Option<u64>),
     TeWait(TestDesc),
     TeResult(CompletedTest),
     TeTimeout(TestDesc),
     TeFilteredOut(usize),
+    TeAborted(usize),
 }
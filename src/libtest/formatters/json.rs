@@ -1,22 +1,68 @@
-use std::{borrow::Cow, io, io::prelude::Write};
+//! Machine-readable output, with one JSON object per line for every event of the run.
+//!
+//! The format of the objects is versioned by the `schema_version` field of the first
+//! event, which is bumped whenever an existing field is changed or removed. New fields
+//! may be added without bumping it. In the current version:
+//!
+//! * `exec_time` is the execution time of a test, as a number of seconds.
+//! * `stdout` and `stderr` hold what a test printed to each stream, when it was
+//!   captured and is shown (i.e. the test failed or `--show-output` was passed).
+//! * `median` and `deviation` of benchmarks are numbers of nanoseconds per iteration,
+//!   and `mib_per_second` is `null` unless the benchmark set `Bencher::bytes`.
+//! * A test that failed and then passed when it was rerun has the `flaky` event, with
+//!   the number of its `failed_attempts`, and the output of the last one.
+
+use std::{borrow::Cow, fmt, io, io::prelude::Write};
 
 use super::OutputFormatter;
 use crate::{
     console::{ConsoleTestState, OutputLocation},
+    event::CapturedStreams,
+    stats,
     test_result::TestResult,
     time,
     types::TestDesc,
 };
 
+/// Version of the format of the emitted events, see the module documentation.
+pub(crate) const SCHEMA_VERSION: u32 = 1;
+
 pub(crate) struct JsonFormatter<T> {
     out: OutputLocation<T>,
 }
 
+/// The captured output of a test, as it is reported.
+#[derive(Default)]
+struct TestOutput<'a> {
+    stdout: Option<Cow<'a, str>>,
+    stderr: Option<Cow<'a, str>>,
+}
+
+impl<'a> TestOutput<'a> {
+    fn new(stdout: &'a [u8], streams: Option<&'a CapturedStreams>) -> Self {
+        let lossy = |output: &'a [u8]| {
+            if output.is_empty() { None } else { Some(String::from_utf8_lossy(output)) }
+        };
+        match streams {
+            Some(streams) => {
+                TestOutput { stdout: lossy(&streams.stdout), stderr: lossy(&streams.stderr) }
+            }
+            // The streams weren't captured separately, so report everything as stdout.
+            None => TestOutput { stdout: lossy(stdout), stderr: None },
+        }
+    }
+}
+
 impl<T: Write> JsonFormatter<T> {
     pub fn new(out: OutputLocation<T>) -> Self {
         Self { out }
     }
 
+    #[cfg(test)]
+    pub fn output_location(&self) -> &OutputLocation<T> {
+        &self.out
+    }
+
     fn writeln_message(&mut self, s: &str) -> io::Result<()> {
         assert!(!s.contains('\n'));
 
@@ -36,19 +82,27 @@ impl<T: Write> JsonFormatter<T> {
         name: &str,
         evt: &str,
         exec_time: Option<&time::TestExecTime>,
-        stdout: Option<Cow<'_, str>>,
+        output: &TestOutput<'_>,
         extra: Option<&str>,
     ) -> io::Result<()> {
         self.write_message(&*format!(
             r#"{{ "type": "{}", "name": "{}", "event": "{}""#,
-            ty, name, evt
+            ty,
+            EscapedString(name),
+            evt
         ))?;
         if let Some(exec_time) = exec_time {
-            self.write_message(&*format!(r#", "exec_time": "{}""#, exec_time))?;
+            self.write_message(&*format!(
+                r#", "exec_time": {}"#,
+                JsonF64(exec_time.0.as_secs_f64())
+            ))?;
         }
-        if let Some(stdout) = stdout {
+        if let Some(ref stdout) = output.stdout {
             self.write_message(&*format!(r#", "stdout": "{}""#, EscapedString(stdout)))?;
         }
+        if let Some(ref stderr) = output.stderr {
+            self.write_message(&*format!(r#", "stderr": "{}""#, EscapedString(stderr)))?;
+        }
         if let Some(extra) = extra {
             self.write_message(&*format!(r#", {}"#, extra))?;
         }
@@ -57,17 +111,25 @@ impl<T: Write> JsonFormatter<T> {
 }
 
 impl<T: Write> OutputFormatter for JsonFormatter<T> {
-    fn write_run_start(&mut self, test_count: usize) -> io::Result<()> {
+    fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
+        let shuffle_seed_json = if let Some(shuffle_seed) = shuffle_seed {
+            format!(r#", "shuffle_seed": {}"#, shuffle_seed)
+        } else {
+            String::new()
+        };
         self.writeln_message(&*format!(
-            r#"{{ "type": "suite", "event": "started", "test_count": {} }}"#,
-            test_count
+            "{{ \"type\": \"suite\", \
+             \"event\": \"started\", \
+             \"schema_version\": {}, \
+             \"test_count\": {}{} }}",
+            SCHEMA_VERSION, test_count, shuffle_seed_json
         ))
     }
 
     fn write_test_start(&mut self, desc: &TestDesc) -> io::Result<()> {
         self.writeln_message(&*format!(
             r#"{{ "type": "test", "event": "started", "name": "{}" }}"#,
-            desc.name
+            EscapedString(desc.name.as_slice())
         ))
     }
 
@@ -77,21 +139,19 @@ impl<T: Write> OutputFormatter for JsonFormatter<T> {
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
         stdout: &[u8],
+        streams: Option<&CapturedStreams>,
         state: &ConsoleTestState,
     ) -> io::Result<()> {
-        let display_stdout = state.options.display_output || *result != TestResult::TrOk;
-        let stdout = if display_stdout && !stdout.is_empty() {
-            Some(String::from_utf8_lossy(stdout))
-        } else {
-            None
-        };
+        let display_output = state.options.display_output || *result != TestResult::TrOk;
+        let output =
+            if display_output { TestOutput::new(stdout, streams) } else { TestOutput::default() };
         match *result {
             TestResult::TrOk => {
-                self.write_event("test", desc.name.as_slice(), "ok", exec_time, stdout, None)
+                self.write_event("test", desc.name.as_slice(), "ok", exec_time, &output, None)
             }
 
             TestResult::TrFailed => {
-                self.write_event("test", desc.name.as_slice(), "failed", exec_time, stdout, None)
+                self.write_event("test", desc.name.as_slice(), "failed", exec_time, &output, None)
             }
 
             TestResult::TrTimedFail => self.write_event(
@@ -99,21 +159,39 @@ impl<T: Write> OutputFormatter for JsonFormatter<T> {
                 desc.name.as_slice(),
                 "failed",
                 exec_time,
-                stdout,
+                &output,
                 Some(r#""reason": "time limit exceeded""#),
             ),
 
+            TestResult::TrTimedOut => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "failed",
+                exec_time,
+                &output,
+                Some(r#""reason": "timeout""#),
+            ),
+
             TestResult::TrFailedMsg(ref m) => self.write_event(
                 "test",
                 desc.name.as_slice(),
                 "failed",
                 exec_time,
-                stdout,
+                &output,
                 Some(&*format!(r#""message": "{}""#, EscapedString(m))),
             ),
 
+            TestResult::TrFlaky(failures) => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "flaky",
+                exec_time,
+                &output,
+                Some(&*format!(r#""failed_attempts": {}"#, failures)),
+            ),
+
             TestResult::TrIgnored => {
-                self.write_event("test", desc.name.as_slice(), "ignored", exec_time, stdout, None)
+                self.write_event("test", desc.name.as_slice(), "ignored", exec_time, &output, None)
             }
 
             TestResult::TrAllowedFail => self.write_event(
@@ -121,26 +199,41 @@ impl<T: Write> OutputFormatter for JsonFormatter<T> {
                 desc.name.as_slice(),
                 "allowed_failure",
                 exec_time,
-                stdout,
+                &output,
                 None,
             ),
 
             TestResult::TrBench(ref bs) => {
-                let median = bs.ns_iter_summ.median as usize;
-                let deviation = (bs.ns_iter_summ.max - bs.ns_iter_summ.min) as usize;
+                let median = bs.ns_iter_summ.median;
+                let deviation = bs.ns_iter_summ.max - bs.ns_iter_summ.min;
+
+                let mbps = if bs.mb_s == 0 { "null".to_owned() } else { bs.mb_s.to_string() };
 
-                let mbps = if bs.mb_s == 0 {
-                    String::new()
-                } else {
-                    format!(r#", "mib_per_second": {}"#, bs.mb_s)
+                let baseline = match bs.baseline {
+                    Some(ref comparison) => format!(
+                        ", \"baseline\": {{ \"summary\": {}, \"change_pct\": {}, \
+                         \"p_value\": {}, \"verdict\": \"{}\" }}",
+                        JsonSummary(&comparison.baseline),
+                        JsonF64(comparison.change_pct),
+                        JsonF64(comparison.p_value),
+                        comparison.verdict()
+                    ),
+                    None => String::new(),
                 };
 
                 let line = format!(
                     "{{ \"type\": \"bench\", \
                      \"name\": \"{}\", \
                      \"median\": {}, \
-                     \"deviation\": {}{} }}",
-                    desc.name, median, deviation, mbps
+                     \"deviation\": {}, \
+                     \"mib_per_second\": {}, \
+                     \"summary\": {}{} }}",
+                    EscapedString(desc.name.as_slice()),
+                    JsonF64(median),
+                    JsonF64(deviation),
+                    mbps,
+                    JsonSummary(&bs.ns_iter_summ),
+                    baseline
                 );
 
                 self.writeln_message(&*line)
@@ -151,33 +244,80 @@ impl<T: Write> OutputFormatter for JsonFormatter<T> {
     fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
         self.writeln_message(&*format!(
             r#"{{ "type": "test", "event": "timeout", "name": "{}" }}"#,
-            desc.name
+            EscapedString(desc.name.as_slice())
         ))
     }
 
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        let not_run = if state.not_run > 0 {
+            format!(r#", "not_run": {}"#, state.not_run)
+        } else {
+            String::new()
+        };
         self.writeln_message(&*format!(
             "{{ \"type\": \"suite\", \
              \"event\": \"{}\", \
              \"passed\": {}, \
              \"failed\": {}, \
+             \"flaky\": {}, \
              \"allowed_fail\": {}, \
              \"ignored\": {}, \
              \"measured\": {}, \
-             \"filtered_out\": {} }}",
+             \"filtered_out\": {}{} }}",
             if state.failed == 0 { "ok" } else { "failed" },
             state.passed,
             state.failed + state.allowed_fail,
+            state.flaky,
             state.allowed_fail,
             state.ignored,
             state.measured,
-            state.filtered_out
+            state.filtered_out,
+            not_run
         ))?;
 
         Ok(state.failed == 0)
     }
 }
 
+/// A formatting utility used to print a `f64` as a JSON number, or `null`
+/// when it has no JSON representation (i.e. it is infinite or NaN).
+struct JsonF64(f64);
+
+impl fmt::Display for JsonF64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_finite() { write!(f, "{}", self.0) } else { f.write_str("null") }
+    }
+}
+
+/// A formatting utility used to print all the statistics of a benchmark as a JSON object.
+struct JsonSummary<'a>(&'a stats::Summary);
+
+impl fmt::Display for JsonSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = self.0;
+        write!(
+            f,
+            "{{ \"sum\": {}, \"min\": {}, \"max\": {}, \"mean\": {}, \"median\": {}, \
+             \"var\": {}, \"std_dev\": {}, \"std_dev_pct\": {}, \"median_abs_dev\": {}, \
+             \"median_abs_dev_pct\": {}, \"quartiles\": [{}, {}, {}], \"iqr\": {} }}",
+            JsonF64(s.sum),
+            JsonF64(s.min),
+            JsonF64(s.max),
+            JsonF64(s.mean),
+            JsonF64(s.median),
+            JsonF64(s.var),
+            JsonF64(s.std_dev),
+            JsonF64(s.std_dev_pct),
+            JsonF64(s.median_abs_dev),
+            JsonF64(s.median_abs_dev_pct),
+            JsonF64(s.quartiles.0),
+            JsonF64(s.quartiles.1),
+            JsonF64(s.quartiles.2),
+            JsonF64(s.iqr)
+        )
+    }
+}
+
 /// A formatting utility used to print strings with characters in need of escaping.
 /// Base code taken form `libserialize::json::escape_str`
 struct EscapedString<S: AsRef<str>>(S);
@@ -0,0 +1,213 @@
+use std::{fmt, io, io::prelude::Write, time::Duration};
+
+use super::OutputFormatter;
+use crate::{
+    console::{ConsoleTestState, OutputLocation},
+    event::CapturedStreams,
+    test_result::TestResult,
+    time,
+    types::TestDesc,
+};
+
+pub(crate) struct JunitFormatter<T> {
+    out: OutputLocation<T>,
+    results: Vec<(TestDesc, TestResult, Duration, Vec<u8>)>,
+    shuffle_seed: Option<u64>,
+}
+
+impl<T: Write> JunitFormatter<T> {
+    pub fn new(out: OutputLocation<T>) -> Self {
+        Self { out, results: Vec::new(), shuffle_seed: None }
+    }
+
+    #[cfg(test)]
+    pub fn output_location(&self) -> &OutputLocation<T> {
+        &self.out
+    }
+
+    fn write_message(&mut self, s: &str) -> io::Result<()> {
+        self.out.write_all(s.as_ref())
+    }
+
+    fn write_testcase(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        duration: Duration,
+        stdout: &[u8],
+    ) -> io::Result<()> {
+        let (class_name, test_name) = parse_class_name(desc);
+        self.write_message(&*format!(
+            r#"<testcase classname="{}" name="{}" time="{:.3}">"#,
+            EscapedXml(class_name),
+            EscapedXml(test_name),
+            duration.as_secs_f64()
+        ))?;
+
+        match *result {
+            TestResult::TrOk | TestResult::TrBench(_) => {}
+            TestResult::TrIgnored => self.write_message("<skipped/>")?,
+            TestResult::TrAllowedFail => {
+                self.write_message(r#"<skipped message="failed (allowed)"/>"#)?
+            }
+            TestResult::TrFailed => self.write_message(r#"<failure type="assert"/>"#)?,
+            TestResult::TrFailedMsg(ref m) => self.write_message(&*format!(
+                r#"<failure type="assert" message="{}"/>"#,
+                EscapedXml(m)
+            ))?,
+            TestResult::TrTimedFail => {
+                self.write_message(r#"<failure type="timeout" message="time limit exceeded"/>"#)?
+            }
+            TestResult::TrTimedOut => {
+                self.write_message(r#"<failure type="timeout" message="timed out"/>"#)?
+            }
+            // The test passed in the end, so its failed attempts are reported the way
+            // Maven Surefire reports reruns, which CI systems understand.
+            TestResult::TrFlaky(failures) => self.write_message(&*format!(
+                r#"<flakyFailure type="assert" message="failed {} times before passing"/>"#,
+                failures
+            ))?,
+        }
+
+        if !stdout.is_empty() {
+            self.write_message(&*format!(
+                "<system-out>{}</system-out>",
+                EscapedXml(String::from_utf8_lossy(stdout))
+            ))?;
+        }
+
+        self.write_message("</testcase>\n")
+    }
+}
+
+impl<T: Write> OutputFormatter for JunitFormatter<T> {
+    fn write_run_start(&mut self, _test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
+        // The seed is reported with the other properties of the test suite,
+        // which is only written when the run is finished.
+        self.shuffle_seed = shuffle_seed;
+
+        // We write xml header on run start
+        self.write_message(r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        self.write_message("\n")
+    }
+
+    fn write_test_start(&mut self, _desc: &TestDesc) -> io::Result<()> {
+        // We do not output anything on test start.
+        Ok(())
+    }
+
+    fn write_timeout(&mut self, _desc: &TestDesc) -> io::Result<()> {
+        // We do not output anything on test timeout.
+        Ok(())
+    }
+
+    fn write_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        exec_time: Option<&time::TestExecTime>,
+        stdout: &[u8],
+        _: Option<&CapturedStreams>,
+        state: &ConsoleTestState,
+    ) -> io::Result<()> {
+        // Results are buffered, since the `testsuite` element has to carry
+        // the totals of the run as its attributes.
+        let duration = exec_time.map(|t| t.0).unwrap_or_default();
+        let display_stdout = state.options.display_output || *result != TestResult::TrOk;
+        let stdout = if display_stdout { stdout.to_vec() } else { Vec::new() };
+        self.results.push((desc.clone(), result.clone(), duration, stdout));
+        Ok(())
+    }
+
+    fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        let total_time: Duration = self.results.iter().map(|(_, _, duration, _)| *duration).sum();
+        // The totals only count the tests that get a `testcase` element: the tests which were
+        // filtered out, or not run after the run was aborted, aren't reported.
+        let skipped = self
+            .results
+            .iter()
+            .filter(|(_, result, ..)| match result {
+                TestResult::TrIgnored | TestResult::TrAllowedFail => true,
+                _ => false,
+            })
+            .count();
+
+        self.write_message("<testsuites>\n")?;
+        self.write_message(&*format!(
+            "<testsuite name=\"test\" package=\"test\" id=\"0\" errors=\"0\" \
+             failures=\"{}\" tests=\"{}\" skipped=\"{}\" time=\"{:.3}\">",
+            state.failed,
+            self.results.len(),
+            skipped,
+            total_time.as_secs_f64()
+        ))?;
+        self.write_message("\n")?;
+
+        if let Some(shuffle_seed) = self.shuffle_seed {
+            self.write_message(&*format!(
+                "<properties><property name=\"shuffle_seed\" value=\"{}\"/></properties>\n",
+                shuffle_seed
+            ))?;
+        }
+
+        for (desc, result, duration, stdout) in std::mem::take(&mut self.results) {
+            self.write_testcase(&desc, &result, duration, &stdout)?;
+        }
+
+        self.write_message("</testsuite>\n")?;
+        self.write_message("</testsuites>\n")?;
+
+        Ok(state.failed == 0)
+    }
+}
+
+/// Splits a test name into the module path, which is reported as the
+/// `classname` of the test case, and the name of the test function itself.
+fn parse_class_name(desc: &TestDesc) -> (&str, &str) {
+    let name = desc.name.as_slice();
+    match name.rfind("::") {
+        Some(pos) => (&name[..pos], &name[pos + 2..]),
+        None => ("", name),
+    }
+}
+
+/// A formatting utility used to print strings with characters that are not
+/// allowed in XML attribute values and character data.
+struct EscapedXml<S: AsRef<str>>(S);
+
+impl<S: AsRef<str>> fmt::Display for EscapedXml<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = self.0.as_ref();
+        let mut start = 0;
+
+        for (i, c) in s.char_indices() {
+            let escaped = match c {
+                '<' => "&lt;",
+                '>' => "&gt;",
+                '&' => "&amp;",
+                '"' => "&quot;",
+                '\'' => "&apos;",
+                '\n' => "&#10;",
+                '\r' => "&#13;",
+                '\t' => "&#9;",
+                // Other control characters are not representable in XML 1.0 at all.
+                c if (c as u32) < 0x20 => "\u{FFFD}",
+                _ => continue,
+            };
+
+            if start < i {
+                f.write_str(&s[start..i])?;
+            }
+
+            f.write_str(escaped)?;
+
+            start = i + c.len_utf8();
+        }
+
+        if start != s.len() {
+            f.write_str(&s[start..])?;
+        }
+
+        Ok(())
+    }
+}
@@ -4,6 +4,7 @@ use super::OutputFormatter;
 use crate::{
     bench::fmt_bench_samples,
     console::{ConsoleTestState, OutputLocation},
+    event::CapturedStreams,
     test_result::TestResult,
     time,
     types::TestDesc,
@@ -56,6 +57,14 @@ impl<T: Write> PrettyFormatter<T> {
         self.write_short_result("FAILED (time limit exceeded)", term::color::RED)
     }
 
+    pub fn write_timed_out(&mut self) -> io::Result<()> {
+        self.write_short_result("FAILED (timed out)", term::color::RED)
+    }
+
+    pub fn write_flaky(&mut self) -> io::Result<()> {
+        self.write_short_result("flaky", term::color::YELLOW)
+    }
+
     pub fn write_bench(&mut self) -> io::Result<()> {
         self.write_pretty("bench", term::color::CYAN)
     }
@@ -167,6 +176,10 @@ impl<T: Write> PrettyFormatter<T> {
         self.write_results(&state.time_failures, "failures (time limit exceeded)")
     }
 
+    pub fn write_flakes(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_results(&state.flakes, "flaky")
+    }
+
     fn write_test_name(&mut self, desc: &TestDesc) -> io::Result<()> {
         let name = desc.padded_name(self.max_name_len, desc.name.padding());
         self.write_plain(&format!("test {} ... ", name))?;
@@ -176,9 +189,14 @@ impl<T: Write> PrettyFormatter<T> {
 }
 
 impl<T: Write> OutputFormatter for PrettyFormatter<T> {
-    fn write_run_start(&mut self, test_count: usize) -> io::Result<()> {
+    fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
         let noun = if test_count != 1 { "tests" } else { "test" };
-        self.write_plain(&format!("\nrunning {} {}\n", test_count, noun))
+        let shuffle_seed_msg = if let Some(shuffle_seed) = shuffle_seed {
+            format!(" (shuffle seed: {})", shuffle_seed)
+        } else {
+            String::new()
+        };
+        self.write_plain(&format!("\nrunning {} {}{}\n", test_count, noun, shuffle_seed_msg))
     }
 
     fn write_test_start(&mut self, desc: &TestDesc) -> io::Result<()> {
@@ -199,6 +217,7 @@ impl<T: Write> OutputFormatter for PrettyFormatter<T> {
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
         _: &[u8],
+        _: Option<&CapturedStreams>,
         _: &ConsoleTestState,
     ) -> io::Result<()> {
         if self.is_multithreaded {
@@ -215,6 +234,8 @@ impl<T: Write> OutputFormatter for PrettyFormatter<T> {
                 self.write_plain(&format!(": {}", fmt_bench_samples(bs)))?;
             }
             TestResult::TrTimedFail => self.write_time_failed()?,
+            TestResult::TrTimedOut => self.write_timed_out()?,
+            TestResult::TrFlaky(_) => self.write_flaky()?,
         }
 
         self.write_time(desc, exec_time)?;
@@ -237,6 +258,10 @@ impl<T: Write> OutputFormatter for PrettyFormatter<T> {
         if state.options.display_output {
             self.write_successes(state)?;
         }
+        // The output of flaky tests is the one of their last failed attempt.
+        if !state.flakes.is_empty() {
+            self.write_flakes(state)?;
+        }
         let success = state.failed == 0;
         if !success {
             if !state.failures.is_empty() {
@@ -248,6 +273,15 @@ impl<T: Write> OutputFormatter for PrettyFormatter<T> {
             }
         }
 
+        if state.not_run > 0 {
+            self.write_plain(&format!(
+                "\nthe test run was aborted because a test timed out and could not be stopped; \
+                 {} {} not run\n",
+                state.not_run,
+                if state.not_run != 1 { "tests were" } else { "test was" }
+            ))?;
+        }
+
         self.write_plain("\ntest result: ")?;
 
         if success {
@@ -257,20 +291,29 @@ impl<T: Write> OutputFormatter for PrettyFormatter<T> {
             self.write_pretty("FAILED", term::color::RED)?;
         }
 
+        let flaky =
+            if state.flaky > 0 { format!("; {} flaky", state.flaky) } else { String::new() };
         let s = if state.allowed_fail > 0 {
             format!(
-                ". {} passed; {} failed ({} allowed); {} ignored; {} measured; {} filtered out\n\n",
+                ". {} passed; {} failed ({} allowed){}; {} ignored; {} measured; \
+                 {} filtered out\n\n",
                 state.passed,
                 state.failed + state.allowed_fail,
                 state.allowed_fail,
+                flaky,
                 state.ignored,
                 state.measured,
                 state.filtered_out
             )
         } else {
             format!(
-                ". {} passed; {} failed; {} ignored; {} measured; {} filtered out\n\n",
-                state.passed, state.failed, state.ignored, state.measured, state.filtered_out
+                ". {} passed; {} failed{}; {} ignored; {} measured; {} filtered out\n\n",
+                state.passed,
+                state.failed,
+                flaky,
+                state.ignored,
+                state.measured,
+                state.filtered_out
             )
         };
 
@@ -4,6 +4,7 @@ use super::OutputFormatter;
 use crate::{
     bench::fmt_bench_samples,
     console::{ConsoleTestState, OutputLocation},
+    event::CapturedStreams,
     test_result::TestResult,
     time,
     types::NamePadding,
@@ -57,6 +58,10 @@ impl<T: Write> TerseFormatter<T> {
         self.write_short_result("a", term::color::YELLOW)
     }
 
+    pub fn write_flaky(&mut self) -> io::Result<()> {
+        self.write_short_result("f", term::color::YELLOW)
+    }
+
     pub fn write_bench(&mut self) -> io::Result<()> {
         self.write_pretty("bench", term::color::CYAN)
     }
@@ -156,6 +161,16 @@ impl<T: Write> TerseFormatter<T> {
         Ok(())
     }
 
+    pub fn write_flakes(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_plain("\nflaky:\n")?;
+        let mut flakes: Vec<_> = state.flakes.iter().map(|(f, _)| f.name.to_string()).collect();
+        flakes.sort();
+        for name in &flakes {
+            self.write_plain(&format!("    {}\n", name))?;
+        }
+        Ok(())
+    }
+
     fn write_test_name(&mut self, desc: &TestDesc) -> io::Result<()> {
         let name = desc.padded_name(self.max_name_len, desc.name.padding());
         self.write_plain(&format!("test {} ... ", name))?;
@@ -165,10 +180,15 @@ impl<T: Write> TerseFormatter<T> {
 }
 
 impl<T: Write> OutputFormatter for TerseFormatter<T> {
-    fn write_run_start(&mut self, test_count: usize) -> io::Result<()> {
+    fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
         self.total_test_count = test_count;
         let noun = if test_count != 1 { "tests" } else { "test" };
-        self.write_plain(&format!("\nrunning {} {}\n", test_count, noun))
+        let shuffle_seed_msg = if let Some(shuffle_seed) = shuffle_seed {
+            format!(" (shuffle seed: {})", shuffle_seed)
+        } else {
+            String::new()
+        };
+        self.write_plain(&format!("\nrunning {} {}{}\n", test_count, noun, shuffle_seed_msg))
     }
 
     fn write_test_start(&mut self, desc: &TestDesc) -> io::Result<()> {
@@ -189,15 +209,18 @@ impl<T: Write> OutputFormatter for TerseFormatter<T> {
         result: &TestResult,
         _: Option<&time::TestExecTime>,
         _: &[u8],
+        _: Option<&CapturedStreams>,
         _: &ConsoleTestState,
     ) -> io::Result<()> {
         match *result {
             TestResult::TrOk => self.write_ok(),
-            TestResult::TrFailed | TestResult::TrFailedMsg(_) | TestResult::TrTimedFail => {
-                self.write_failed()
-            }
+            TestResult::TrFailed
+            | TestResult::TrFailedMsg(_)
+            | TestResult::TrTimedFail
+            | TestResult::TrTimedOut => self.write_failed(),
             TestResult::TrIgnored => self.write_ignored(),
             TestResult::TrAllowedFail => self.write_allowed_fail(),
+            TestResult::TrFlaky(_) => self.write_flaky(),
             TestResult::TrBench(ref bs) => {
                 if self.is_multithreaded {
                     self.write_test_name(desc)?;
@@ -220,11 +243,23 @@ impl<T: Write> OutputFormatter for TerseFormatter<T> {
         if state.options.display_output {
             self.write_outputs(state)?;
         }
+        if !state.flakes.is_empty() {
+            self.write_flakes(state)?;
+        }
         let success = state.failed == 0;
         if !success {
             self.write_failures(state)?;
         }
 
+        if state.not_run > 0 {
+            self.write_plain(&format!(
+                "\nthe test run was aborted because a test timed out and could not be stopped; \
+                 {} {} not run\n",
+                state.not_run,
+                if state.not_run != 1 { "tests were" } else { "test was" }
+            ))?;
+        }
+
         self.write_plain("\ntest result: ")?;
 
         if success {
@@ -234,20 +269,29 @@ impl<T: Write> OutputFormatter for TerseFormatter<T> {
             self.write_pretty("FAILED", term::color::RED)?;
         }
 
+        let flaky =
+            if state.flaky > 0 { format!("; {} flaky", state.flaky) } else { String::new() };
         let s = if state.allowed_fail > 0 {
             format!(
-                ". {} passed; {} failed ({} allowed); {} ignored; {} measured; {} filtered out\n\n",
+                ". {} passed; {} failed ({} allowed){}; {} ignored; {} measured; \
+                 {} filtered out\n\n",
                 state.passed,
                 state.failed + state.allowed_fail,
                 state.allowed_fail,
+                flaky,
                 state.ignored,
                 state.measured,
                 state.filtered_out
             )
         } else {
             format!(
-                ". {} passed; {} failed; {} ignored; {} measured; {} filtered out\n\n",
-                state.passed, state.failed, state.ignored, state.measured, state.filtered_out
+                ". {} passed; {} failed{}; {} ignored; {} measured; {} filtered out\n\n",
+                state.passed,
+                state.failed,
+                flaky,
+                state.ignored,
+                state.measured,
+                state.filtered_out
             )
         };
 
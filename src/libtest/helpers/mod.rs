@@ -5,4 +5,6 @@ pub mod concurrency;
 pub mod exit_code;
 pub mod isatty;
 pub mod metrics;
+pub mod shard;
+pub mod shuffle;
 pub mod sink;
@@ -0,0 +1,23 @@
+//! Helper module for splitting the tests of a run into disjoint shards,
+//! e.g. to spread them over several machines.
+
+use crate::options::TestShard;
+use crate::types::TestName;
+
+/// Returns whether the test belongs to the given shard. This only depends on
+/// the name of the test, so every test belongs to exactly one shard, and
+/// adding or removing tests doesn't move the other ones between shards.
+pub fn is_in_shard(name: &TestName, shard: TestShard) -> bool {
+    (fnv1a(name.as_slice().as_bytes()) % shard.count as u64) as usize == shard.index
+}
+
+// FNV-1a is used rather than `DefaultHasher`, whose algorithm may change between
+// releases, since the shards must stay the same across toolchains and platforms.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &byte in bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
@@ -0,0 +1,71 @@
+//! Helper module for shuffling the order in which tests are executed,
+//! in a way that can be reproduced given the same seed.
+
+use super::shard::fnv1a;
+use crate::cli::TestOpts;
+use crate::types::TestDescAndFn;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Returns the seed to shuffle the tests with, if shuffling was requested.
+/// When no explicit seed was provided, a fresh one is derived from the current time.
+pub fn get_shuffle_seed(opts: &TestOpts) -> Option<u64> {
+    opts.shuffle_seed.or_else(|| {
+        if opts.shuffle {
+            Some(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .expect("Failed to get system time")
+                    .as_nanos() as u64,
+            )
+        } else {
+            None
+        }
+    })
+}
+
+/// Shuffles the tests in place. The resulting order only depends on the seed
+/// and on the names of the provided tests.
+pub fn shuffle_tests(shuffle_seed: u64, tests: &mut [TestDescAndFn]) {
+    let mut test_names = Vec::new();
+    for test in tests.iter() {
+        let name = test.desc.name.as_slice().as_bytes();
+        test_names.extend_from_slice(&(name.len() as u64).to_le_bytes());
+        test_names.extend_from_slice(name);
+    }
+    let test_names_hash = fnv1a(&test_names);
+    let mut rng = Rng::new(shuffle_seed, test_names_hash);
+    shuffle(&mut rng, tests);
+}
+
+// Fisher-Yates shuffle.
+fn shuffle<T>(rng: &mut Rng, slice: &mut [T]) {
+    for i in 0..slice.len() {
+        let idx = i + rng.rand_range(slice.len() - i);
+        slice.swap(i, idx);
+    }
+}
+
+/// A minimal pseudo-random generator (splitmix64). The exact sequence it
+/// produces is not important, but it must stay stable across platforms and
+/// runs so that a seed reported by a failing run can be replayed.
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64, extra: u64) -> Self {
+        Self { state: seed ^ extra }
+    }
+
+    fn rand_range(&mut self, len: usize) -> usize {
+        (self.rand_u64() % len as u64) as usize
+    }
+
+    fn rand_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
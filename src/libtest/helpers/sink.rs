@@ -6,17 +6,27 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-pub struct Sink(Arc<Mutex<Vec<u8>>>);
+pub struct Sink(Arc<Mutex<Vec<u8>>>, Option<Arc<Mutex<Vec<u8>>>>);
 
 impl Sink {
     pub fn new_boxed(data: &Arc<Mutex<Vec<u8>>>) -> Box<Self> {
-        Box::new(Self(data.clone()))
+        Box::new(Self(data.clone(), None))
+    }
+
+    /// Like `new_boxed`, but everything written is also copied to `stream`,
+    /// which keeps the output of a single stream apart from the others.
+    pub fn new_boxed_split(data: &Arc<Mutex<Vec<u8>>>, stream: &Arc<Mutex<Vec<u8>>>) -> Box<Self> {
+        Box::new(Self(data.clone(), Some(stream.clone())))
     }
 }
 
 impl Write for Sink {
     fn write(&mut self, data: &[u8]) -> io::Result<usize> {
-        Write::write(&mut *self.0.lock().unwrap(), data)
+        let written = Write::write(&mut *self.0.lock().unwrap(), data)?;
+        if let Some(ref stream) = self.1 {
+            stream.lock().unwrap().extend_from_slice(&data[..written]);
+        }
+        Ok(written)
     }
     fn flush(&mut self) -> io::Result<()> {
         Ok(())
@@ -33,7 +33,7 @@
 // Public reexports
 pub use self::bench::{black_box, Bencher};
 pub use self::console::run_tests_console;
-pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, ShouldPanic};
+pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, ShouldPanic, TestShard};
 pub use self::types::TestName::*;
 pub use self::types::*;
 pub use self::ColorConfig::*;
@@ -59,8 +59,8 @@ pub mod test {
 }
 
 use std::{
-    env, io,
-    io::prelude::Write,
+    cmp, env, io,
+    io::prelude::{Read, Write},
     panic::{self, catch_unwind, AssertUnwindSafe, PanicInfo},
     process::{self, Command, Termination},
     sync::mpsc::{channel, Sender},
@@ -69,6 +69,7 @@ use std::{
     time::{Duration, Instant},
 };
 
+mod baseline;
 pub mod bench;
 mod cli;
 mod console;
@@ -84,9 +85,11 @@ mod types;
 #[cfg(test)]
 mod tests;
 
-use event::{CompletedTest, TestEvent};
+use event::{CapturedStreams, CompletedTest, TestEvent};
 use helpers::concurrency::get_concurrency;
 use helpers::exit_code::get_exit_code;
+use helpers::shard::is_in_shard;
+use helpers::shuffle::{get_shuffle_seed, shuffle_tests};
 use helpers::sink::Sink;
 use options::{Concurrent, RunStrategy};
 use test_result::*;
@@ -134,6 +137,10 @@ pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Option<Opt
 /// This is the entry point for the main function generated by `rustc --test`
 /// when panic=unwind.
 pub fn test_main_static(tests: &[&TestDescAndFn]) {
+    // With `--isolate`, every test is run by a child process, which is a
+    // copy of this one.
+    run_secondary_test_if_requested(tests);
+
     let args = env::args().collect::<Vec<_>>();
     let owned_tests: Vec<_> = tests.iter().map(make_owned_test).collect();
     test_main(&args, owned_tests, None)
@@ -148,8 +155,16 @@ pub fn test_main_static(tests: &[&TestDescAndFn]) {
 /// This is the entry point for the main function generated by `rustc --test`
 /// when panic=abort.
 pub fn test_main_static_abort(tests: &[&TestDescAndFn]) {
-    // If we're being run in SpawnedSecondary mode, run the test here. run_test
-    // will then exit the process.
+    run_secondary_test_if_requested(tests);
+
+    let args = env::args().collect::<Vec<_>>();
+    let owned_tests: Vec<_> = tests.iter().map(make_owned_test).collect();
+    test_main(&args, owned_tests, Some(Options::new().panic_abort(true)))
+}
+
+/// If we're being run in SpawnedSecondary mode, run the test here.
+/// `run_test_in_spawned_subprocess` will then exit the process.
+fn run_secondary_test_if_requested(tests: &[&TestDescAndFn]) {
     if let Ok(name) = env::var(SECONDARY_TEST_INVOKER_VAR) {
         env::remove_var(SECONDARY_TEST_INVOKER_VAR);
         let test = tests
@@ -165,10 +180,6 @@ pub fn test_main_static_abort(tests: &[&TestDescAndFn]) {
         };
         run_test_in_spawned_subprocess(desc, Box::new(testfn));
     }
-
-    let args = env::args().collect::<Vec<_>>();
-    let owned_tests: Vec<_> = tests.iter().map(make_owned_test).collect();
-    test_main(&args, owned_tests, Some(Options::new().panic_abort(true)))
 }
 
 /// Clones static values for putting into a dynamic vector, which test_main()
@@ -227,13 +238,23 @@ where
         filtered_tests
     };
 
+    let shuffle_seed = get_shuffle_seed(opts);
+    let filtered_tests = match shuffle_seed {
+        Some(shuffle_seed) => {
+            let mut filtered_tests = filtered_tests;
+            shuffle_tests(shuffle_seed, &mut filtered_tests);
+            filtered_tests
+        }
+        None => filtered_tests,
+    };
+
     let filtered_out = tests_len - filtered_tests.len();
     let event = TestEvent::TeFilteredOut(filtered_out);
     notify_about_test_event(event)?;
 
     let filtered_descs = filtered_tests.iter().map(|t| t.desc.clone()).collect();
 
-    let event = TestEvent::TeFiltered(filtered_descs);
+    let event = TestEvent::TeFiltered(filtered_descs, shuffle_seed);
     notify_about_test_event(event)?;
 
     let (filtered_tests, filtered_benchs): (Vec<_>, _) =
@@ -249,13 +270,34 @@ where
     let mut pending = 0;
 
     let (tx, rx) = channel::<CompletedTest>();
-    let run_strategy = if opts.options.panic_abort && !opts.force_run_in_process {
+    let run_strategy = if (opts.options.panic_abort || opts.isolate) && !opts.force_run_in_process {
         RunStrategy::SpawnPrimary
     } else {
         RunStrategy::InProcess
     };
 
+    // A child process can only look up statically defined tests by their name,
+    // so with `--isolate` the dynamic ones (e.g. benchmarks run as tests) stay in-process.
+    let test_run_strategy = |testfn: &TestFn| match testfn {
+        DynTestFn(_) if opts.isolate && !opts.options.panic_abort => RunStrategy::InProcess,
+        _ => run_strategy,
+    };
+
     let mut running_tests: TestMap = HashMap::default();
+    // Tests running in-process can't be stopped once they exceed their timeout,
+    // so their deadlines are tracked here, and the run is aborted if one is missed.
+    let mut test_deadlines: TestMap = HashMap::default();
+    let mut hung_tests = Vec::new();
+
+    // Returns the deadline of a test that `run_test` itself can't enforce.
+    // Each attempt of a test that is rerun when it fails gets the full timeout.
+    let in_process_deadline = |test: &TestDescAndFn, strategy: RunStrategy| match strategy {
+        RunStrategy::InProcess => {
+            let attempts = get_test_retries(opts, test) as u32 + 1;
+            time::get_hard_test_timeout(&test.desc, opts.test_timeout).map(|t| t * attempts)
+        }
+        RunStrategy::SpawnPrimary => None,
+    };
 
     fn get_timed_out_tests(running_tests: &mut TestMap) -> Vec<TestDesc> {
         let now = Instant::now();
@@ -281,40 +323,80 @@ where
             let test = remaining.pop().unwrap();
             let event = TestEvent::TeWait(test.desc.clone());
             notify_about_test_event(event)?;
-            run_test(opts, !opts.run_tests, test, run_strategy, tx.clone(), Concurrent::No);
-            let completed_test = rx.recv().unwrap();
+            let strategy = test_run_strategy(&test.testfn);
+            let completed_test = match in_process_deadline(&test, strategy) {
+                None => {
+                    run_test(opts, !opts.run_tests, test, strategy, tx.clone(), Concurrent::No);
+                    rx.recv().unwrap()
+                }
+                Some(timeout) => {
+                    // The test has to run on its own thread, so that we can stop waiting for it.
+                    let desc = test.desc.clone();
+                    run_test(opts, !opts.run_tests, test, strategy, tx.clone(), Concurrent::Yes);
+                    match rx.recv_timeout(timeout) {
+                        Ok(completed_test) => completed_test,
+                        Err(_) => {
+                            hung_tests.push(desc.clone());
+                            CompletedTest::new(desc, TrTimedOut, None, Vec::new())
+                        }
+                    }
+                }
+            };
 
             let event = TestEvent::TeResult(completed_test);
             notify_about_test_event(event)?;
+
+            if !hung_tests.is_empty() {
+                break;
+            }
         }
     } else {
-        while pending > 0 || !remaining.is_empty() {
-            while pending < concurrency && !remaining.is_empty() {
+        while pending > 0 || (hung_tests.is_empty() && !remaining.is_empty()) {
+            while hung_tests.is_empty() && pending < concurrency && !remaining.is_empty() {
                 let test = remaining.pop().unwrap();
                 let timeout = time::get_default_test_timeout();
                 running_tests.insert(test.desc.clone(), timeout);
+                let strategy = test_run_strategy(&test.testfn);
+                if let Some(hard_timeout) = in_process_deadline(&test, strategy) {
+                    test_deadlines.insert(test.desc.clone(), Instant::now() + hard_timeout);
+                }
 
                 let event = TestEvent::TeWait(test.desc.clone());
                 notify_about_test_event(event)?; //here no pad
-                run_test(opts, !opts.run_tests, test, run_strategy, tx.clone(), Concurrent::Yes);
+                run_test(opts, !opts.run_tests, test, strategy, tx.clone(), Concurrent::Yes);
                 pending += 1;
             }
 
             let mut res;
             loop {
-                if let Some(timeout) = calc_timeout(&running_tests) {
+                let timeout = match (calc_timeout(&running_tests), calc_timeout(&test_deadlines)) {
+                    (Some(warn), Some(deadline)) => Some(cmp::min(warn, deadline)),
+                    (warn, deadline) => warn.or(deadline),
+                };
+                if let Some(timeout) = timeout {
                     res = rx.recv_timeout(timeout);
                     for test in get_timed_out_tests(&mut running_tests) {
                         let event = TestEvent::TeTimeout(test);
                         notify_about_test_event(event)?;
                     }
 
+                    for test in get_timed_out_tests(&mut test_deadlines) {
+                        // The test keeps running on its thread, but we stop waiting for it.
+                        running_tests.remove(&test);
+                        hung_tests.push(test.clone());
+                        let completed_test = CompletedTest::new(test, TrTimedOut, None, Vec::new());
+                        let event = TestEvent::TeResult(completed_test);
+                        notify_about_test_event(event)?;
+                        pending -= 1;
+                    }
+
                     match res {
-                        Err(RecvTimeoutError::Timeout) => {
+                        Err(RecvTimeoutError::Timeout) if pending > 0 => {
                             // Result is not yet ready, continue waiting.
                         }
                         _ => {
-                            // We've got a result, stop the loop.
+                            // We've got a result, or there's nothing left to wait for,
+                            // stop the loop.
                             break;
                         }
                     }
@@ -324,8 +406,18 @@ where
                 }
             }
 
-            let completed_test = res.unwrap();
+            let completed_test = match res {
+                Ok(completed_test) => completed_test,
+                // All the tests we were waiting for have timed out.
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(e) => panic!("failed to receive a test result: {:?}", e),
+            };
+            if hung_tests.contains(&completed_test.desc) {
+                // The test finished right after timing out, and was already reported.
+                continue;
+            }
             running_tests.remove(&completed_test.desc);
+            test_deadlines.remove(&completed_test.desc);
 
             let event = TestEvent::TeResult(completed_test);
             notify_about_test_event(event)?;
@@ -333,12 +425,21 @@ where
         }
     }
 
+    if !hung_tests.is_empty() {
+        // Tests that are still running can't be stopped, so the rest of the
+        // run is skipped and the caller is expected to exit the process.
+        let event = TestEvent::TeAborted(remaining.len() + filtered_benchs.len());
+        notify_about_test_event(event)?;
+        return Ok(());
+    }
+
     if opts.bench_benchmarks {
         // All benchmarks run at the end, in serial.
         for b in filtered_benchs {
             let event = TestEvent::TeWait(b.desc.clone());
             notify_about_test_event(event)?;
-            run_test(opts, false, b, run_strategy, tx.clone(), Concurrent::No);
+            let strategy = test_run_strategy(&b.testfn);
+            run_test(opts, false, b, strategy, tx.clone(), Concurrent::No);
             let completed_test = rx.recv().unwrap();
 
             let event = TestEvent::TeResult(completed_test);
@@ -384,6 +485,11 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
         RunIgnored::No => {}
     }
 
+    // Only keep the tests of the requested shard
+    if let Some(shard) = opts.shard {
+        filtered.retain(|test| is_in_shard(&test.desc.name, shard));
+    }
+
     // Sort the tests alphabetically
     filtered.sort_by(|t1, t2| t1.desc.name.as_slice().cmp(t2.desc.name.as_slice()));
 
@@ -409,6 +515,15 @@ pub fn convert_benchmarks_to_tests(tests: Vec<TestDescAndFn>) -> Vec<TestDescAnd
         .collect()
 }
 
+/// Returns how many times a test is rerun when it fails. Only tests defined as
+/// plain functions can be rerun, since the closures of dynamic tests are consumed.
+fn get_test_retries(opts: &TestOpts, test: &TestDescAndFn) -> usize {
+    match test.testfn {
+        StaticTestFn(_) => test.desc.retries.unwrap_or(opts.retries),
+        _ => 0,
+    }
+}
+
 pub fn run_test(
     opts: &TestOpts,
     force_ignore: bool,
@@ -417,6 +532,7 @@ pub fn run_test(
     monitor_ch: Sender<CompletedTest>,
     concurrency: Concurrent,
 ) {
+    let retries = get_test_retries(opts, &test);
     let TestDescAndFn { desc, testfn } = test;
 
     // Emscripten can catch panics but other wasm targets cannot
@@ -434,34 +550,57 @@ pub fn run_test(
         pub strategy: RunStrategy,
         pub nocapture: bool,
         pub concurrency: Concurrent,
+        pub report_time: bool,
         pub time: Option<time::TestTimeOptions>,
+        pub timeout: Option<Duration>,
+        pub retries: usize,
     }
 
     fn run_test_inner(
         desc: TestDesc,
         monitor_ch: Sender<CompletedTest>,
         testfn: Box<dyn FnOnce() + Send>,
+        rerun: Option<fn()>,
         opts: TestRunOpts,
     ) {
         let concurrency = opts.concurrency;
         let name = desc.name.clone();
 
-        let runtest = move || match opts.strategy {
-            RunStrategy::InProcess => run_test_in_process(
-                desc,
-                opts.nocapture,
-                opts.time.is_some(),
-                testfn,
-                monitor_ch,
-                opts.time,
-            ),
-            RunStrategy::SpawnPrimary => spawn_test_subprocess(
-                desc,
-                opts.nocapture,
-                opts.time.is_some(),
-                monitor_ch,
-                opts.time,
-            ),
+        let runtest = move || {
+            let run_attempt = |testfn: Box<dyn FnOnce() + Send>| match opts.strategy {
+                RunStrategy::InProcess => run_test_in_process(
+                    desc.clone(),
+                    opts.nocapture,
+                    opts.report_time,
+                    testfn,
+                    opts.time,
+                ),
+                RunStrategy::SpawnPrimary => spawn_test_subprocess(
+                    desc.clone(),
+                    opts.nocapture,
+                    opts.report_time,
+                    opts.time,
+                    opts.timeout,
+                ),
+            };
+
+            let mut completed_test = run_attempt(testfn);
+            if let Some(f) = rerun {
+                let mut failures = 0;
+                while failures < opts.retries && is_retryable(&completed_test.result) {
+                    failures += 1;
+                    let retried = run_attempt(Box::new(move || __rust_begin_short_backtrace(f)));
+                    if retried.result == TrOk {
+                        // The output of the failed attempt is kept, since it is what
+                        // explains the flakiness.
+                        completed_test.result = TrFlaky(failures);
+                        completed_test.exec_time = retried.exec_time;
+                    } else {
+                        completed_test = retried;
+                    }
+                }
+            }
+            monitor_ch.send(completed_test).unwrap();
         };
 
         // If the platform is single-threaded we're just going to run
@@ -476,8 +615,18 @@ pub fn run_test(
         }
     }
 
-    let test_run_opts =
-        TestRunOpts { strategy, nocapture: opts.nocapture, concurrency, time: opts.time_options };
+    let test_run_opts = TestRunOpts {
+        strategy,
+        nocapture: opts.nocapture,
+        concurrency,
+        // The JSON and JUnit formats record the execution time of every test.
+        report_time: opts.time_options.is_some()
+            || opts.format == OutputFormat::Json
+            || opts.format == OutputFormat::Junit,
+        time: opts.time_options,
+        timeout: time::get_hard_test_timeout(&desc, opts.test_timeout),
+        retries,
+    };
 
     match testfn {
         DynBenchFn(bencher) => {
@@ -499,6 +648,7 @@ pub fn run_test(
                 desc,
                 monitor_ch,
                 Box::new(move || __rust_begin_short_backtrace(f)),
+                None,
                 test_run_opts,
             );
         }
@@ -506,11 +656,20 @@ pub fn run_test(
             desc,
             monitor_ch,
             Box::new(move || __rust_begin_short_backtrace(f)),
+            Some(f),
             test_run_opts,
         ),
     }
 }
 
+/// Returns whether a test with this result is rerun, if it has retries left.
+fn is_retryable(result: &TestResult) -> bool {
+    match *result {
+        TrFailed | TrFailedMsg(_) | TrTimedFail | TrTimedOut => true,
+        _ => false,
+    }
+}
+
 /// Fixed frame used to clean the backtrace with `RUST_BACKTRACE=1`.
 #[inline(never)]
 fn __rust_begin_short_backtrace<F: FnOnce()>(f: F) {
@@ -522,16 +681,17 @@ fn run_test_in_process(
     nocapture: bool,
     report_time: bool,
     testfn: Box<dyn FnOnce() + Send>,
-    monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
-) {
-    // Buffer for capturing standard I/O
+) -> CompletedTest {
+    // Buffer for capturing standard I/O, and copies of each stream
     let data = Arc::new(Mutex::new(Vec::new()));
+    let stdout_data = Arc::new(Mutex::new(Vec::new()));
+    let stderr_data = Arc::new(Mutex::new(Vec::new()));
 
     let oldio = if !nocapture {
         Some((
-            io::set_print(Some(Sink::new_boxed(&data))),
-            io::set_panic(Some(Sink::new_boxed(&data))),
+            io::set_print(Some(Sink::new_boxed_split(&data, &stdout_data))),
+            io::set_panic(Some(Sink::new_boxed_split(&data, &stderr_data))),
         ))
     } else {
         None
@@ -554,18 +714,23 @@ fn run_test_in_process(
         Err(e) => calc_result(&desc, Err(e.as_ref()), &time_opts, &exec_time),
     };
     let stdout = data.lock().unwrap().to_vec();
-    let message = CompletedTest::new(desc, test_result, exec_time, stdout);
-    monitor_ch.send(message).unwrap();
+    let mut message = CompletedTest::new(desc, test_result, exec_time, stdout);
+    if !nocapture {
+        let stdout = stdout_data.lock().unwrap().to_vec();
+        let stderr = stderr_data.lock().unwrap().to_vec();
+        message = message.with_streams(CapturedStreams { stdout, stderr });
+    }
+    message
 }
 
 fn spawn_test_subprocess(
     desc: TestDesc,
     nocapture: bool,
     report_time: bool,
-    monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
-) {
-    let (result, test_output, exec_time) = (|| {
+    timeout: Option<Duration>,
+) -> CompletedTest {
+    let (result, test_output, streams, exec_time) = (|| {
         let args = env::args().collect::<Vec<_>>();
         let current_exe = &args[0];
 
@@ -574,14 +739,21 @@ fn spawn_test_subprocess(
         if nocapture {
             command.stdout(process::Stdio::inherit());
             command.stderr(process::Stdio::inherit());
+        } else {
+            command.stdout(process::Stdio::piped());
+            command.stderr(process::Stdio::piped());
         }
 
         let start = report_time.then(Instant::now);
-        let output = match command.output() {
+        let output = match timeout {
+            Some(timeout) => output_with_timeout(&mut command, timeout),
+            None => command.output().map(|out| (Some(out.status), out.stdout, out.stderr)),
+        };
+        let (status, stdout, stderr) = match output {
             Ok(out) => out,
             Err(e) => {
                 let err = format!("Failed to spawn {} as child for test: {:?}", args[0], e);
-                return (TrFailed, err.into_bytes(), None);
+                return (TrFailed, err.into_bytes(), None, None);
             }
         };
         let exec_time = start.map(|start| {
@@ -589,10 +761,15 @@ fn spawn_test_subprocess(
             TestExecTime(duration)
         });
 
-        let std::process::Output { stdout, stderr, status } = output;
-        let mut test_output = stdout;
+        let mut test_output = stdout.clone();
         formatters::write_stderr_delimiter(&mut test_output, &desc.name);
         test_output.extend_from_slice(&stderr);
+        let mut streams = CapturedStreams { stdout, stderr };
+
+        let status = match status {
+            Some(status) => status,
+            None => return (TrTimedOut, test_output, Some(streams), exec_time),
+        };
 
         let result = match (|| -> Result<TestResult, String> {
             let exit_code = get_exit_code(status)?;
@@ -601,15 +778,67 @@ fn spawn_test_subprocess(
             Ok(r) => r,
             Err(e) => {
                 write!(&mut test_output, "Unexpected error: {}", e).unwrap();
+                write!(&mut streams.stderr, "Unexpected error: {}", e).unwrap();
                 TrFailed
             }
         };
 
-        (result, test_output, exec_time)
+        (result, test_output, Some(streams), exec_time)
     })();
 
-    let message = CompletedTest::new(desc, result, exec_time, test_output);
-    monitor_ch.send(message).unwrap();
+    let mut message = CompletedTest::new(desc, result, exec_time, test_output);
+    if let (false, Some(streams)) = (nocapture, streams) {
+        message = message.with_streams(streams);
+    }
+    message
+}
+
+/// Runs the command like `Command::output` does, but kills the child process
+/// if it doesn't exit within `timeout`, in which case no exit status is returned.
+fn output_with_timeout(
+    command: &mut Command,
+    timeout: Duration,
+) -> io::Result<(Option<process::ExitStatus>, Vec<u8>, Vec<u8>)> {
+    fn read_pipe<R: Read + Send + 'static>(
+        pipe: Option<R>,
+    ) -> Option<thread::JoinHandle<io::Result<Vec<u8>>>> {
+        pipe.map(|mut pipe| {
+            thread::spawn(move || {
+                let mut buf = Vec::new();
+                pipe.read_to_end(&mut buf).map(|_| buf)
+            })
+        })
+    }
+
+    fn join_pipe(handle: Option<thread::JoinHandle<io::Result<Vec<u8>>>>) -> io::Result<Vec<u8>> {
+        match handle {
+            Some(handle) => handle.join().unwrap(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    let deadline = Instant::now() + timeout;
+    let mut child = command.spawn()?;
+    // The pipes are drained on separate threads, so that the child never blocks on a full pipe.
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            // The child may have exited in the meantime, in which case killing it fails.
+            let _ = child.kill();
+            child.wait()?;
+            break None;
+        }
+        thread::sleep(cmp::min(deadline - now, Duration::from_millis(10)));
+    };
+
+    Ok((status, join_pipe(stdout)?, join_pipe(stderr)?))
 }
 
 fn run_test_in_spawned_subprocess(desc: TestDesc, testfn: Box<dyn FnOnce() + Send>) -> ! {
@@ -640,5 +869,5 @@ fn run_test_in_spawned_subprocess(desc: TestDesc, testfn: Box<dyn FnOnce() + Sen
     panic::set_hook(Box::new(move |info| record_result2(Some(&info))));
     testfn();
     record_result(None);
-    unreachable!("panic=abort callback should have exited the process")
+    unreachable!("recording the result of the test should have exited the process")
 }
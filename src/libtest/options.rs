@@ -39,6 +39,8 @@ pub enum OutputFormat {
     Terse,
     /// JSON output
     Json,
+    /// JUnit XML output
+    Junit,
 }
 
 /// Whether ignored test should be run or not
@@ -50,6 +52,14 @@ pub enum RunIgnored {
     Only,
 }
 
+/// One of `count` disjoint subsets the tests of a run are split into.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TestShard {
+    /// Index of the shard to run, starting at 0.
+    pub index: usize,
+    pub count: usize,
+}
+
 #[derive(Clone, Copy)]
 pub enum RunStrategy {
     /// Runs the test in the current process, and sends the result back over the
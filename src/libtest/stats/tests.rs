@@ -571,6 +571,23 @@ fn test_sum_f64_between_ints_that_sum_to_0() {
     assert_eq!([1e30f64, 1.2f64, -1e30f64].sum(), 1.2);
 }
 
+#[test]
+fn test_mann_whitney_u() {
+    let a: Vec<f64> = (1..=10).map(|x| x as f64).collect();
+    let b: Vec<f64> = (11..=20).map(|x| x as f64).collect();
+    assert_approx_eq!(mann_whitney_u(&a, &b), 0.000182672);
+    assert_approx_eq!(mann_whitney_u(&b, &a), 0.000182672);
+    assert_approx_eq!(mann_whitney_u(&a, &a), 1.0);
+}
+
+#[test]
+fn test_mann_whitney_u_ties() {
+    let a = [1.0, 2.0, 2.0, 3.0, 5.0];
+    let b = [2.0, 4.0, 4.0, 6.0, 7.0];
+    assert_approx_eq!(mann_whitney_u(&a, &b), 0.137563894);
+    assert_eq!(mann_whitney_u(&[3.0; 5], &[3.0; 5]), 1.0);
+}
+
 #[bench]
 pub fn sum_three_items(b: &mut Bencher) {
     b.iter(|| {
@@ -22,6 +22,9 @@ pub enum TestResult {
     TrAllowedFail,
     TrBench(BenchSamples),
     TrTimedFail,
+    TrTimedOut,
+    /// The test failed, but passed when it was rerun. Holds the number of failed attempts.
+    TrFlaky(usize),
 }
 
 unsafe impl Send for TestResult {}
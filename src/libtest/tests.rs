@@ -3,7 +3,7 @@ use super::*;
 use crate::{
     bench::Bencher,
     console::OutputLocation,
-    formatters::PrettyFormatter,
+    formatters::{JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter},
     options::OutputFormat,
     test::{
         filter_tests,
@@ -38,6 +38,7 @@ impl TestOpts {
             filter: None,
             filter_exact: false,
             force_run_in_process: false,
+            isolate: false,
             exclude_should_panic: false,
             run_ignored: RunIgnored::No,
             run_tests: false,
@@ -49,6 +50,13 @@ impl TestOpts {
             test_threads: None,
             skip: vec![],
             time_options: None,
+            test_timeout: None,
+            shuffle: false,
+            shuffle_seed: None,
+            save_baseline: None,
+            baseline: None,
+            shard: None,
+            retries: 0,
             options: Options::new(),
         }
     }
@@ -62,6 +70,8 @@ fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
                 ignore: true,
                 should_panic: ShouldPanic::No,
                 allow_fail: false,
+                timeout: None,
+                retries: None,
                 test_type: TestType::Unknown,
             },
             testfn: DynTestFn(Box::new(move || {})),
@@ -72,6 +82,8 @@ fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
                 ignore: false,
                 should_panic: ShouldPanic::No,
                 allow_fail: false,
+                timeout: None,
+                retries: None,
                 test_type: TestType::Unknown,
             },
             testfn: DynTestFn(Box::new(move || {})),
@@ -90,6 +102,8 @@ pub fn do_not_run_ignored_tests() {
             ignore: true,
             should_panic: ShouldPanic::No,
             allow_fail: false,
+            timeout: None,
+            retries: None,
             test_type: TestType::Unknown,
         },
         testfn: DynTestFn(Box::new(f)),
@@ -109,6 +123,8 @@ pub fn ignored_tests_result_in_ignored() {
             ignore: true,
             should_panic: ShouldPanic::No,
             allow_fail: false,
+            timeout: None,
+            retries: None,
             test_type: TestType::Unknown,
         },
         testfn: DynTestFn(Box::new(f)),
@@ -132,6 +148,8 @@ fn test_should_panic() {
             ignore: false,
             should_panic: ShouldPanic::Yes,
             allow_fail: false,
+            timeout: None,
+            retries: None,
             test_type: TestType::Unknown,
         },
         testfn: DynTestFn(Box::new(f)),
@@ -155,6 +173,8 @@ fn test_should_panic_good_message() {
             ignore: false,
             should_panic: ShouldPanic::YesWithMessage("error message"),
             allow_fail: false,
+            timeout: None,
+            retries: None,
             test_type: TestType::Unknown,
         },
         testfn: DynTestFn(Box::new(f)),
@@ -183,6 +203,8 @@ fn test_should_panic_bad_message() {
             ignore: false,
             should_panic: ShouldPanic::YesWithMessage(expected),
             allow_fail: false,
+            timeout: None,
+            retries: None,
             test_type: TestType::Unknown,
         },
         testfn: DynTestFn(Box::new(f)),
@@ -214,6 +236,8 @@ fn test_should_panic_non_string_message_type() {
             ignore: false,
             should_panic: ShouldPanic::YesWithMessage(expected),
             allow_fail: false,
+            timeout: None,
+            retries: None,
             test_type: TestType::Unknown,
         },
         testfn: DynTestFn(Box::new(f)),
@@ -235,6 +259,8 @@ fn test_should_panic_but_succeeds() {
             ignore: false,
             should_panic: ShouldPanic::Yes,
             allow_fail: false,
+            timeout: None,
+            retries: None,
             test_type: TestType::Unknown,
         },
         testfn: DynTestFn(Box::new(f)),
@@ -253,6 +279,8 @@ fn report_time_test_template(report_time: bool) -> Option<TestExecTime> {
             ignore: false,
             should_panic: ShouldPanic::No,
             allow_fail: false,
+            timeout: None,
+            retries: None,
             test_type: TestType::Unknown,
         },
         testfn: DynTestFn(Box::new(f)),
@@ -286,6 +314,8 @@ fn time_test_failure_template(test_type: TestType) -> TestResult {
             ignore: false,
             should_panic: ShouldPanic::No,
             allow_fail: false,
+            timeout: None,
+            retries: None,
             test_type,
         },
         testfn: DynTestFn(Box::new(f)),
@@ -323,6 +353,8 @@ fn typed_test_desc(test_type: TestType) -> TestDesc {
         ignore: false,
         should_panic: ShouldPanic::No,
         allow_fail: false,
+        timeout: None,
+        retries: None,
         test_type,
     }
 }
@@ -439,6 +471,8 @@ pub fn exclude_should_panic_option() {
             ignore: false,
             should_panic: ShouldPanic::Yes,
             allow_fail: false,
+            timeout: None,
+            retries: None,
             test_type: TestType::Unknown,
         },
         testfn: DynTestFn(Box::new(move || {})),
@@ -461,6 +495,8 @@ pub fn exact_filter_match() {
                     ignore: false,
                     should_panic: ShouldPanic::No,
                     allow_fail: false,
+                    timeout: None,
+                    retries: None,
                     test_type: TestType::Unknown,
                 },
                 testfn: DynTestFn(Box::new(move || {})),
@@ -508,6 +544,44 @@ pub fn exact_filter_match() {
     assert_eq!(exact.len(), 1);
 }
 
+#[test]
+pub fn shard_filter() {
+    fn tests() -> Vec<TestDescAndFn> {
+        (0..100)
+            .map(|i| TestDescAndFn {
+                desc: TestDesc {
+                    name: DynTestName(format!("test{}", i)),
+                    ignore: false,
+                    should_panic: ShouldPanic::No,
+                    allow_fail: false,
+                    timeout: None,
+                    retries: None,
+                    test_type: TestType::Unknown,
+                },
+                testfn: DynTestFn(Box::new(move || {})),
+            })
+            .collect()
+    }
+
+    let shard = |index| {
+        let opts = TestOpts { shard: Some(TestShard { index, count: 3 }), ..TestOpts::new() };
+        filter_tests(&opts, tests())
+            .into_iter()
+            .map(|test| test.desc.name.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let shards = vec![shard(0), shard(1), shard(2)];
+    assert!(shards.iter().all(|shard| !shard.is_empty()));
+    assert_eq!(shards, vec![shard(0), shard(1), shard(2)]);
+
+    let mut all: Vec<String> = shards.concat();
+    all.sort();
+    let mut expected: Vec<String> = tests().iter().map(|test| test.desc.name.to_string()).collect();
+    expected.sort();
+    assert_eq!(all, expected);
+}
+
 #[test]
 pub fn sort_tests() {
     let mut opts = TestOpts::new();
@@ -536,6 +610,8 @@ pub fn sort_tests() {
                     ignore: false,
                     should_panic: ShouldPanic::No,
                     allow_fail: false,
+                    timeout: None,
+                    retries: None,
                     test_type: TestType::Unknown,
                 },
                 testfn: DynTestFn(Box::new(testfn)),
@@ -613,6 +689,8 @@ pub fn test_bench_no_iter() {
         ignore: false,
         should_panic: ShouldPanic::No,
         allow_fail: false,
+        timeout: None,
+        retries: None,
         test_type: TestType::Unknown,
     };
 
@@ -633,6 +711,8 @@ pub fn test_bench_iter() {
         ignore: false,
         should_panic: ShouldPanic::No,
         allow_fail: false,
+        timeout: None,
+        retries: None,
         test_type: TestType::Unknown,
     };
 
@@ -640,6 +720,77 @@ pub fn test_bench_iter() {
     rx.recv().unwrap();
 }
 
+fn bench_samples(samples: &[f64]) -> bench::BenchSamples {
+    bench::BenchSamples {
+        ns_iter_summ: stats::Summary::new(samples),
+        samples: samples.to_vec(),
+        mb_s: 0,
+        baseline: None,
+    }
+}
+
+#[test]
+fn bench_baseline_round_trip() {
+    let mut baseline = baseline::Baseline::default();
+    baseline.insert("a::b", &bench_samples(&[1.5, 2.0, 1.0 / 3.0]));
+    baseline.insert("tab\tand\\backslash", &bench_samples(&[4.0]));
+
+    let serialized = baseline.serialize();
+    assert_eq!(baseline::Baseline::parse(&serialized), Ok(baseline));
+
+    assert!(baseline::Baseline::parse("").is_err());
+    let malformed = format!("{}not a benchmark\n", serialized);
+    assert!(baseline::Baseline::parse(&malformed).is_err());
+}
+
+#[test]
+fn bench_baseline_comparison() {
+    let old: Vec<f64> = (0..50).map(|i| 1000.0 + i as f64).collect();
+    let mut baseline = baseline::Baseline::default();
+    baseline.insert("bench", &bench_samples(&old));
+
+    assert_eq!(baseline.compare("other", &bench_samples(&old)), None);
+
+    let same = baseline.compare("bench", &bench_samples(&old)).unwrap();
+    assert_eq!(same.change_pct, 0.0);
+    assert!(!same.is_significant());
+
+    let slower: Vec<f64> = old.iter().map(|x| x * 1.1).collect();
+    let mut bs = bench_samples(&slower);
+    bs.baseline = baseline.compare("bench", &bs);
+    let comparison = bs.baseline.as_ref().unwrap();
+    assert!(comparison.is_significant());
+    assert_eq!(comparison.verdict(), "regressed");
+    assert!((comparison.change_pct - 10.0).abs() < 1e-9);
+    assert!(bench::fmt_bench_samples(&bs).ends_with("(+10.00%, p = 0.000: regressed)"));
+}
+
+#[test]
+fn parse_baseline_flags() {
+    let args = vec!["progname".to_string(), "--baseline".to_string(), "main".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--save-baseline".to_string(),
+        "new".to_string(),
+        "--baseline".to_string(),
+        "main".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.save_baseline.as_deref(), Some("new"));
+    assert_eq!(opts.baseline.as_deref(), Some("main"));
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--baseline".to_string(),
+        "../main".to_string(),
+    ];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
 #[test]
 fn should_sort_failures_before_printing_them() {
     let test_a = TestDesc {
@@ -647,6 +798,8 @@ fn should_sort_failures_before_printing_them() {
         ignore: false,
         should_panic: ShouldPanic::No,
         allow_fail: false,
+        timeout: None,
+        retries: None,
         test_type: TestType::Unknown,
     };
 
@@ -655,6 +808,8 @@ fn should_sort_failures_before_printing_them() {
         ignore: false,
         should_panic: ShouldPanic::No,
         allow_fail: false,
+        timeout: None,
+        retries: None,
         test_type: TestType::Unknown,
     };
 
@@ -665,15 +820,18 @@ fn should_sort_failures_before_printing_them() {
         total: 0,
         passed: 0,
         failed: 0,
+        flaky: 0,
         ignored: 0,
         allowed_fail: 0,
         filtered_out: 0,
         measured: 0,
+        not_run: 0,
         metrics: MetricMap::new(),
         failures: vec![(test_b, Vec::new()), (test_a, Vec::new())],
         options: Options::new(),
         not_failures: Vec::new(),
         time_failures: Vec::new(),
+        flakes: Vec::new(),
     };
 
     out.write_failures(&st).unwrap();
@@ -686,3 +844,472 @@ fn should_sort_failures_before_printing_them() {
     let bpos = s.find("b").unwrap();
     assert!(apos < bpos);
 }
+
+#[test]
+fn junit_output_escapes_and_reports_failures() {
+    let test_ok = TestDesc {
+        name: StaticTestName("module::passing"),
+        ignore: false,
+        should_panic: ShouldPanic::No,
+        allow_fail: false,
+        timeout: None,
+        retries: None,
+        test_type: TestType::Unknown,
+    };
+
+    let test_failed = TestDesc {
+        name: StaticTestName("failing"),
+        ignore: false,
+        should_panic: ShouldPanic::No,
+        allow_fail: false,
+        timeout: None,
+        retries: None,
+        test_type: TestType::Unknown,
+    };
+
+    let mut out = JunitFormatter::new(OutputLocation::Raw(Vec::new()));
+
+    let st = console::ConsoleTestState {
+        log_out: None,
+        total: 3,
+        passed: 1,
+        failed: 1,
+        flaky: 0,
+        ignored: 0,
+        allowed_fail: 0,
+        filtered_out: 1,
+        measured: 0,
+        not_run: 1,
+        metrics: MetricMap::new(),
+        failures: Vec::new(),
+        options: Options::new(),
+        not_failures: Vec::new(),
+        time_failures: Vec::new(),
+        flakes: Vec::new(),
+    };
+
+    out.write_run_start(3, None).unwrap();
+    let exec_time = test_exec_time(1500);
+    out.write_result(&test_ok, &TrOk, Some(&exec_time), b"hidden", None, &st).unwrap();
+    out.write_result(
+        &test_failed,
+        &TestResult::TrFailedMsg("expected <a> & \"b\"".to_string()),
+        None,
+        b"line 1\nline 2",
+        None,
+        &st,
+    )
+    .unwrap();
+    assert!(!out.write_run_finish(&st).unwrap());
+
+    let s = match out.output_location() {
+        &OutputLocation::Raw(ref m) => String::from_utf8_lossy(&m[..]).into_owned(),
+        &OutputLocation::Pretty(_) => unreachable!(),
+    };
+
+    assert!(s.starts_with("<?xml"));
+    // The tests which were filtered out or not run have no `testcase` and aren't counted.
+    assert!(s.contains(r#"failures="1" tests="2" skipped="0""#));
+    assert!(s.contains(r#"<testcase classname="module" name="passing" time="1.500">"#));
+    assert!(!s.contains("hidden"));
+    assert!(s.contains(r#"<testcase classname="" name="failing" time="0.000">"#));
+    assert!(
+        s.contains(r#"<failure type="assert" message="expected &lt;a&gt; &amp; &quot;b&quot;"/>"#)
+    );
+    assert!(s.contains("<system-out>line 1&#10;line 2</system-out>"));
+}
+
+#[test]
+fn json_output_reports_streams_separately() {
+    let test_failed = TestDesc {
+        name: StaticTestName("module::\"quoted\""),
+        ignore: false,
+        should_panic: ShouldPanic::No,
+        allow_fail: false,
+        timeout: None,
+        retries: None,
+        test_type: TestType::Unknown,
+    };
+
+    let mut out = JsonFormatter::new(OutputLocation::Raw(Vec::new()));
+    let st = console::ConsoleTestState::new(&TestOpts::new()).unwrap();
+    let streams = event::CapturedStreams { stdout: b"out\n".to_vec(), stderr: b"err\n".to_vec() };
+
+    out.write_run_start(1, None).unwrap();
+    let exec_time = test_exec_time(1500);
+    out.write_result(&test_failed, &TrFailed, Some(&exec_time), b"out\nerr\n", Some(&streams), &st)
+        .unwrap();
+    out.write_result(&test_failed, &TrFailed, None, b"out\nerr\n", None, &st).unwrap();
+
+    let s = match out.output_location() {
+        &OutputLocation::Raw(ref m) => String::from_utf8_lossy(&m[..]).into_owned(),
+        &OutputLocation::Pretty(_) => unreachable!(),
+    };
+    let lines: Vec<&str> = s.lines().collect();
+
+    assert_eq!(
+        lines[0],
+        r#"{ "type": "suite", "event": "started", "schema_version": 1, "test_count": 1 }"#
+    );
+    assert_eq!(
+        lines[1],
+        concat!(
+            r#"{ "type": "test", "name": "module::\"quoted\"", "event": "failed", "#,
+            r#""exec_time": 1.5, "stdout": "out\n", "stderr": "err\n" }"#
+        )
+    );
+    assert_eq!(
+        lines[2],
+        concat!(
+            r#"{ "type": "test", "name": "module::\"quoted\"", "event": "failed", "#,
+            r#""stdout": "out\nerr\n" }"#
+        )
+    );
+}
+
+#[test]
+fn parse_shuffle_flags() {
+    let args = vec!["progname".to_string(), "--shuffle".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+
+    let args =
+        vec!["progname".to_string(), "-Zunstable-options".to_string(), "--shuffle".to_string()];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert!(opts.shuffle);
+    assert_eq!(opts.shuffle_seed, None);
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--shuffle-seed".to_string(),
+        "42".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.shuffle_seed, Some(42));
+}
+
+#[test]
+fn parse_shard_flags() {
+    let args = |extra: &[&str]| -> Vec<String> {
+        let mut args = vec!["progname".to_string(), "-Zunstable-options".to_string()];
+        args.extend(extra.iter().map(|arg| arg.to_string()));
+        args
+    };
+
+    let opts = parse_opts(&args(&["--shard-index", "1", "--shard-count", "4"])).unwrap().unwrap();
+    assert_eq!(opts.shard, Some(TestShard { index: 1, count: 4 }));
+
+    assert!(parse_opts(&args(&["--shard-index", "1"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--shard-index", "4", "--shard-count", "4"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--shard-index", "0", "--shard-count", "0"])).unwrap().is_err());
+
+    let args = vec![
+        "progname".to_string(),
+        "--shard-index".to_string(),
+        "0".to_string(),
+        "--shard-count".to_string(),
+        "2".to_string(),
+    ];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
+#[test]
+fn parse_isolate_flag() {
+    let args = vec!["progname".to_string(), "--isolate".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+
+    let args =
+        vec!["progname".to_string(), "-Zunstable-options".to_string(), "--isolate".to_string()];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert!(opts.isolate);
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--isolate".to_string(),
+        "--force-run-in-process".to_string(),
+    ];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
+#[test]
+fn isolate_runs_dynamic_tests_in_process() {
+    let mut opts = TestOpts::new();
+    opts.isolate = true;
+    opts.run_tests = true;
+
+    let tests = vec![TestDescAndFn {
+        desc: TestDesc {
+            name: StaticTestName("dynamic"),
+            ignore: false,
+            should_panic: ShouldPanic::No,
+            allow_fail: false,
+            timeout: None,
+            retries: None,
+            test_type: TestType::Unknown,
+        },
+        testfn: DynTestFn(Box::new(move || {})),
+    }];
+
+    let mut results = Vec::new();
+    run_tests(&opts, tests, |event| {
+        if let TestEvent::TeResult(completed_test) = event {
+            results.push(completed_test.result);
+        }
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(results, vec![TrOk]);
+}
+
+#[test]
+fn parse_retries_flag() {
+    let args = vec!["progname".to_string(), "--retries".to_string(), "2".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--retries".to_string(),
+        "2".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.retries, 2);
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--retries".to_string(),
+        "many".to_string(),
+    ];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
+#[test]
+fn retries_report_flaky_tests() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+    fn fails_twice() {
+        assert!(ATTEMPTS.fetch_add(1, Ordering::SeqCst) >= 2);
+    }
+
+    fn always_fails() {
+        panic!();
+    }
+
+    let desc = |name, retries| TestDesc {
+        name: StaticTestName(name),
+        ignore: false,
+        should_panic: ShouldPanic::No,
+        allow_fail: false,
+        timeout: None,
+        retries,
+        test_type: TestType::Unknown,
+    };
+
+    let mut opts = TestOpts::new();
+    opts.run_tests = true;
+    opts.retries = 2;
+    opts.test_threads = Some(1);
+
+    let tests = vec![
+        TestDescAndFn { desc: desc("flaky", None), testfn: StaticTestFn(fails_twice) },
+        TestDescAndFn { desc: desc("failing", Some(1)), testfn: StaticTestFn(always_fails) },
+        TestDescAndFn { desc: desc("dynamic", None), testfn: DynTestFn(Box::new(always_fails)) },
+    ];
+
+    let mut results = Vec::new();
+    run_tests(&opts, tests, |event| {
+        if let TestEvent::TeResult(completed_test) = event {
+            results.push((completed_test.desc.name.to_string(), completed_test.result));
+        }
+        Ok(())
+    })
+    .unwrap();
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+
+    assert_eq!(
+        results,
+        vec![
+            ("dynamic".to_string(), TrFailed),
+            ("failing".to_string(), TrFailed),
+            ("flaky".to_string(), TrFlaky(2)),
+        ]
+    );
+    assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+pub fn shuffle_tests_with_seed() {
+    fn tests() -> Vec<TestDescAndFn> {
+        (0..20)
+            .map(|i| TestDescAndFn {
+                desc: TestDesc {
+                    name: DynTestName(format!("test{}", i)),
+                    ignore: false,
+                    should_panic: ShouldPanic::No,
+                    allow_fail: false,
+                    timeout: None,
+                    retries: None,
+                    test_type: TestType::Unknown,
+                },
+                testfn: DynTestFn(Box::new(move || {})),
+            })
+            .collect()
+    }
+
+    fn names(tests: &[TestDescAndFn]) -> Vec<String> {
+        tests.iter().map(|test| test.desc.name.to_string()).collect()
+    }
+
+    let opts = TestOpts { shuffle_seed: Some(1), ..TestOpts::new() };
+    let seed = helpers::shuffle::get_shuffle_seed(&opts).unwrap();
+    assert_eq!(seed, 1);
+
+    let mut first = tests();
+    helpers::shuffle::shuffle_tests(seed, &mut first);
+    let mut second = tests();
+    helpers::shuffle::shuffle_tests(seed, &mut second);
+    let mut other = tests();
+    helpers::shuffle::shuffle_tests(seed + 1, &mut other);
+
+    // The same seed always produces the same order, on every platform.
+    assert_eq!(names(&first), names(&second));
+    assert_eq!(names(&first)[..5], ["test7", "test5", "test14", "test15", "test19"]);
+    assert_ne!(names(&first), names(&tests()));
+    assert_ne!(names(&first), names(&other));
+
+    // Shuffling is a permutation of the original tests.
+    let mut sorted = names(&first);
+    sorted.sort();
+    let mut expected = names(&tests());
+    expected.sort();
+    assert_eq!(sorted, expected);
+}
+
+#[test]
+fn parse_test_timeout_flag() {
+    let args = vec!["progname".to_string(), "--test-timeout".to_string(), "10".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--test-timeout".to_string(),
+        "10".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.test_timeout, Some(Duration::from_secs(10)));
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--test-timeout".to_string(),
+        "0".to_string(),
+    ];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
+fn hung_test_template(test_threads: usize) -> Vec<TestEvent> {
+    fn hang() {
+        // Nobody ever sends on this channel.
+        let (_tx, rx) = channel::<()>();
+        rx.recv().unwrap();
+    }
+
+    let tests = vec![
+        TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("a"),
+                ignore: false,
+                should_panic: ShouldPanic::No,
+                allow_fail: false,
+                timeout: None,
+                retries: None,
+                test_type: TestType::Unknown,
+            },
+            testfn: DynTestFn(Box::new(move || {})),
+        },
+        TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("b"),
+                ignore: false,
+                should_panic: ShouldPanic::No,
+                allow_fail: false,
+                timeout: Some(1),
+                retries: None,
+                test_type: TestType::Unknown,
+            },
+            testfn: DynTestFn(Box::new(hang)),
+        },
+        TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("c"),
+                ignore: false,
+                should_panic: ShouldPanic::No,
+                allow_fail: false,
+                timeout: None,
+                retries: None,
+                test_type: TestType::Unknown,
+            },
+            testfn: DynTestFn(Box::new(move || {})),
+        },
+    ];
+
+    let opts = TestOpts {
+        run_tests: true,
+        test_threads: Some(test_threads),
+        test_timeout: Some(Duration::from_secs(1000)),
+        ..TestOpts::new()
+    };
+    let mut events = Vec::new();
+    run_tests(&opts, tests, |event| {
+        events.push(event);
+        Ok(())
+    })
+    .unwrap();
+    events
+}
+
+#[test]
+fn in_process_test_timeout_aborts_run() {
+    let events = hung_test_template(1);
+
+    let results: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            TestEvent::TeResult(completed) => {
+                Some((completed.desc.name.to_string(), completed.result.clone()))
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(results, vec![("a".to_string(), TrOk), ("b".to_string(), TrTimedOut)]);
+
+    match events.last() {
+        Some(TestEvent::TeAborted(not_run)) => assert_eq!(*not_run, 1),
+        event => panic!("expected the run to be aborted, got {:?}", event),
+    }
+}
+
+#[test]
+fn in_process_test_timeout_aborts_concurrent_run() {
+    let events = hung_test_template(2);
+
+    let timed_out: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            TestEvent::TeResult(completed) if completed.result == TrTimedOut => {
+                Some(completed.desc.name.to_string())
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(timed_out, vec!["b".to_string()]);
+    assert!(events.iter().any(|event| match event {
+        TestEvent::TeAborted(_) => true,
+        _ => false,
+    }));
+}
@@ -60,6 +60,12 @@ pub fn get_default_test_timeout() -> Instant {
     Instant::now() + Duration::from_secs(TEST_WARN_TIMEOUT_S)
 }
 
+/// Returns the duration after which the test is considered hung and gets stopped:
+/// the timeout set on the test itself if there is one, the timeout of the run otherwise.
+pub fn get_hard_test_timeout(desc: &TestDesc, run_timeout: Option<Duration>) -> Option<Duration> {
+    desc.timeout.map(Duration::from_secs).or(run_timeout)
+}
+
 /// The meassured execution time of a unit test.
 #[derive(Debug, Clone, PartialEq)]
 pub struct TestExecTime(pub Duration);
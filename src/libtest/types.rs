@@ -120,6 +120,10 @@ pub struct TestDesc {
     pub ignore: bool,
     pub should_panic: options::ShouldPanic,
     pub allow_fail: bool,
+    /// Overrides the `--test-timeout` of the run for this test, in seconds.
+    pub timeout: Option<u64>,
+    /// Overrides the `--retries` of the run for this test.
+    pub retries: Option<usize>,
     pub test_type: TestType,
 }
 
@@ -0,0 +1,8 @@
+mod other;
+
+fn main() {
+    let x = 1;
+    if x == 1 {
+        other::print(x);
+    }
+}
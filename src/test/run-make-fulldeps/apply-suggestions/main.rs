@@ -0,0 +1,8 @@
+mod other;
+
+fn main() {
+    let mut x = 1;
+    if (x == 1) {
+        other::print(x);
+    }
+}
@@ -0,0 +1,7 @@
+pub fn print(x: u32) {
+    let mut y = x;
+    while (y > 0) {
+        println!("{}", x);
+        y -= 1;
+    }
+}
@@ -0,0 +1,31 @@
+pub fn added() {}
+
+pub fn changed(x: u64) -> u32 {
+    x as u32
+}
+
+pub fn bounded<T: Clone>(x: T) -> T {
+    x
+}
+
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+pub enum Shape {
+    Circle,
+    Square,
+    Triangle,
+}
+
+pub trait Draw {
+    fn draw(&self);
+
+    fn name(&self) -> String {
+        String::new()
+    }
+}
+
+pub struct Unit;
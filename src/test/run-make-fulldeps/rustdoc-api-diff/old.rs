@@ -0,0 +1,26 @@
+pub fn removed() {}
+
+pub fn changed(x: u32) -> u32 {
+    x
+}
+
+pub fn bounded<T>(x: T) -> T {
+    x
+}
+
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+pub enum Shape {
+    Circle,
+    Square,
+}
+
+pub trait Draw {
+    fn draw(&self);
+}
+
+#[derive(Clone)]
+pub struct Unit;
@@ -0,0 +1,60 @@
+#!/usr/bin/env python
+
+# Checks the structure of the JSON documentation of `foo.rs`.
+
+import json
+import sys
+
+with open(sys.argv[1]) as f:
+    krate = json.load(f)
+
+assert krate["format_version"] == 1
+assert not krate["includes_private"]
+
+index = krate["index"]
+paths = krate["paths"]
+
+
+def item_by_path(path):
+    for (id, summary) in paths.items():
+        if summary["path"] == path:
+            return index[id]
+    raise AssertionError("no item with path {}".format("::".join(path)))
+
+
+root = index[krate["root"]]
+assert root["name"] == "foo"
+assert root["kind"] == "module"
+assert root["inner"]["is_crate"]
+
+inner = item_by_path(["foo", "inner"])
+assert inner["docs"] == "Some documentation."
+assert inner["id"] in root["inner"]["items"]
+
+bar = item_by_path(["foo", "inner", "Bar"])
+assert bar["kind"] == "struct"
+assert bar["inner"]["generics"]["params"][0]["name"] == "T"
+# The private field is stripped from the documentation.
+assert bar["inner"]["fields_stripped"]
+assert [index[f]["name"] for f in bar["inner"]["fields"]] == ["field"]
+assert bar["span"]["filename"].endswith("foo.rs")
+
+impls = [index[i] for i in bar["inner"]["impls"]]
+assert len(impls) == 2
+inherent = [i for i in impls if i["inner"]["trait"] is None]
+assert len(inherent) == 1
+methods = [index[m] for m in inherent[0]["inner"]["items"]]
+assert [m["name"] for m in methods] == ["get"]
+assert methods[0]["inner"]["has_body"]
+
+baz = item_by_path(["foo", "Baz"])
+assert baz["kind"] == "trait"
+trait_impls = [index[i] for i in baz["inner"]["implementors"]]
+assert [i["inner"]["trait"]["inner"]["name"] for i in trait_impls] == ["Baz"]
+tymethod = index[baz["inner"]["items"][0]]
+assert tymethod["kind"] == "method"
+assert not tymethod["inner"]["has_body"]
+
+alias = item_by_path(["foo", "Alias"])
+assert alias["kind"] == "typedef"
+assert alias["deprecation"]["since"] == "1.0.0"
@@ -0,0 +1,32 @@
+#![crate_name = "foo"]
+
+/// Some documentation.
+pub mod inner {
+    /// A struct with a documented field.
+    pub struct Bar<T> {
+        /// The field.
+        pub field: T,
+        hidden: u8,
+    }
+
+    impl<T: Clone> Bar<T> {
+        /// Returns a clone of the field.
+        pub fn get(&self) -> T {
+            self.field.clone()
+        }
+    }
+}
+
+/// A trait.
+pub trait Baz {
+    fn baz(&self) -> u32;
+}
+
+impl<T> Baz for inner::Bar<T> {
+    fn baz(&self) -> u32 {
+        self.hidden as u32
+    }
+}
+
+#[deprecated(since = "1.0.0", note = "use `inner::Bar` instead")]
+pub type Alias = inner::Bar<String>;
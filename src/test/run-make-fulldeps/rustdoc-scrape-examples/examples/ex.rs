@@ -0,0 +1,9 @@
+use foobar::{Count, Counter};
+
+fn main() {
+    let mut counter = Counter(0);
+    foobar::ok();
+    counter.incr();
+    counter.count();
+    unsafe { foobar::abs(-1) };
+}
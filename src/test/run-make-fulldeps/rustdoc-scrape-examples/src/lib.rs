@@ -0,0 +1,37 @@
+// @has foobar/fn.ok.html '//*[@class="scraped-examples"]//*[@class="scraped-example-title"]' \
+//      'examples/ex.rs'
+// @has - '//*[@class="scraped-examples"]//*[@class="line-highlighted"]' '5'
+pub fn ok() {}
+
+// @!has foobar/fn.unused.html '//*[@class="scraped-examples"]'
+pub fn unused() {}
+
+pub struct Counter(pub u32);
+
+impl Counter {
+    // @has foobar/struct.Counter.html \
+    //      '//*[@class="scraped-examples"]//*[@class="line-highlighted"]' '6'
+    pub fn incr(&mut self) {
+        self.0 += 1;
+    }
+}
+
+// Calls to functions without a body are found too.
+
+pub trait Count {
+    // @has foobar/trait.Count.html \
+    //      '//*[@class="scraped-examples"]//*[@class="line-highlighted"]' '7'
+    fn count(&self) -> u32;
+}
+
+impl Count for Counter {
+    fn count(&self) -> u32 {
+        self.0
+    }
+}
+
+extern "C" {
+    // @has foobar/fn.abs.html \
+    //      '//*[@class="scraped-examples"]//*[@class="line-highlighted"]' '8'
+    pub fn abs(x: i32) -> i32;
+}
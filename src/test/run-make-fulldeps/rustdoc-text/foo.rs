@@ -0,0 +1,39 @@
+#![crate_name = "foo"]
+
+//! A crate with a struct and a trait.
+
+/// Some documentation.
+pub mod inner {
+    /// A struct with a documented field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use foo::inner::Bar;
+    /// let bar = Bar { field: 1 };
+    /// ```
+    pub struct Bar<T> {
+        /// The field.
+        pub field: T,
+        hidden: u8,
+    }
+
+    impl<T: Clone> Bar<T> {
+        /// Returns a clone of the field.
+        pub fn get(&self) -> T {
+            self.field.clone()
+        }
+    }
+}
+
+/// A trait.
+pub trait Baz {
+    /// Returns a number.
+    fn baz(&self) -> u32;
+}
+
+impl<T> Baz for inner::Bar<T> {
+    fn baz(&self) -> u32 {
+        0
+    }
+}
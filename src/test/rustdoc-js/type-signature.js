@@ -0,0 +1,32 @@
+// exact-check
+
+const QUERY = [
+    'Vec<T>, usize -> Option<T>',
+    '&Bag<T>, usize -> Option<&T>',
+    '(A, B) -> (B, A)',
+    'Vec<u8>, usize -> Option<char>',
+];
+
+const EXPECTED = [
+    {
+        'in_args': [
+            { 'path': 'type_signature', 'name': 'nth' },
+            { 'path': 'type_signature', 'name': 'nth_byte' },
+        ],
+    },
+    {
+        'in_args': [
+            { 'path': 'type_signature::Bag', 'name': 'get' },
+        ],
+    },
+    {
+        'returned': [
+            { 'path': 'type_signature', 'name': 'swap' },
+        ],
+    },
+    {
+        'in_args': [
+            { 'path': 'type_signature', 'name': 'nth_char' },
+        ],
+    },
+];
@@ -0,0 +1,29 @@
+pub struct Bag<T> {
+    items: Vec<T>,
+}
+
+impl<T> Bag<T> {
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+}
+
+pub fn nth<T>(items: Vec<T>, n: usize) -> Option<T> {
+    items.into_iter().nth(n)
+}
+
+pub fn nth_byte(bytes: Vec<u8>, n: usize) -> Option<u8> {
+    bytes.get(n).copied()
+}
+
+pub fn nth_char(bytes: Vec<u8>, n: usize) -> Option<char> {
+    bytes.get(n).map(|&b| b as char)
+}
+
+pub fn swap<A, B>(pair: (A, B)) -> (B, A) {
+    (pair.1, pair.0)
+}
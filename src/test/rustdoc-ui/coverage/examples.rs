@@ -0,0 +1,36 @@
+// compile-flags:-Z unstable-options --show-coverage --coverage-details
+// compile-flags:--coverage-threshold 50 --coverage-threshold examples=50
+// check-pass
+
+//! Items with code examples are counted separately from documented items.
+//!
+//! ```
+//! let x = 1;
+//! ```
+
+/// A documented struct with an example.
+///
+/// ```
+/// let s = examples::WithExample;
+/// ```
+pub struct WithExample;
+
+/// A documented struct without an example.
+pub struct WithoutExample;
+
+/// Constants aren't expected to have examples.
+pub const CONST: u32 = 0;
+
+pub mod inner {
+    /// A function with a text block, which isn't a code example.
+    ///
+    /// ```text
+    /// not rust
+    /// ```
+    pub fn text_only() {}
+
+    /// ```
+    /// examples::inner::with_example();
+    /// ```
+    pub fn with_example() {}
+}
@@ -0,0 +1,6 @@
+// compile-flags:-Z unstable-options --show-coverage
+// compile-flags:--coverage-threshold 75 --coverage-threshold examples=10
+
+//! The crate root is documented, but the function isn't, and nothing has code examples.
+
+pub fn undocumented() {}
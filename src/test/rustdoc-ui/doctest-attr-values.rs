@@ -0,0 +1,25 @@
+// check-pass
+// compile-flags:--test --test-args --test-threads=1
+// normalize-stdout-test: "src/test/rustdoc-ui" -> "$$DIR"
+
+// Tests the attributes of code blocks that take a value.
+
+/// ```should_panic = "index out of bounds"
+/// let v: Vec<u8> = Vec::new();
+/// v[0];
+/// ```
+pub struct ShouldPanic;
+
+/// ```env(DOCTEST_GREETING="hello, world")
+/// assert_eq!(env!("DOCTEST_GREETING"), "hello, world");
+/// assert_eq!(std::env::var("DOCTEST_GREETING").unwrap(), "hello, world");
+/// ```
+pub struct Env;
+
+/// ```cfg(feature = "extra")
+/// #[cfg(feature = "extra")]
+/// fn extra() {}
+///
+/// extra();
+/// ```
+pub struct Cfg;
@@ -0,0 +1,13 @@
+// FIXME: if/when the output of the test harness can be tested on its own, this test should be
+// adapted to use that, and that normalize line can go away
+
+// compile-flags:--test
+// rustc-env:RUST_BACKTRACE=0
+// normalize-stdout-test: "src/test/rustdoc-ui" -> "$$DIR"
+// normalize-stdout-test: "\.rs:\d+:\d+" -> ".rs:LL:COL"
+// failure-status: 101
+
+/// ```should_panic = "index out of bounds"
+/// panic!("oh no");
+/// ```
+pub struct Foo;
@@ -0,0 +1,36 @@
+// compile-flags: -Z unstable-options --check-intra-doc-links
+
+pub struct S;
+
+impl S {
+    pub fn method() {}
+}
+
+pub trait T {}
+
+pub const C: i32 = 0;
+
+pub fn f() {}
+
+#[macro_export]
+macro_rules! m {
+    () => {};
+}
+
+/// These links are fine: [`fn@f`], [`f()`], [`method@S::method`], [`S::method()`], [`value@C`],
+/// [`type@S`], [`prim@u8`], [`m!`] and [`macro@m`].
+///
+/// [`struct@T`] is a trait. //~ERROR incompatible link kind for `T`
+///
+/// [`fn@C`] is a constant. //~ERROR incompatible link kind for `C`
+///
+/// [const@f] is a function. //~ERROR incompatible link kind for `f`
+///
+/// [`const@S::method`] is a method. //~ERROR incompatible link kind for `S::method`
+///
+/// [`derive@m`] is a macro. //~ERROR incompatible link kind for `m`
+///
+/// [`struct@u8`] is a primitive type. //~ERROR incompatible link kind for `u8`
+///
+/// [`trait@S#anchor`] keeps its anchor. //~ERROR incompatible link kind for `S`
+pub struct Docs;
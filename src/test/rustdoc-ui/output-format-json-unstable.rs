@@ -0,0 +1,4 @@
+// compile-flags:--output-format json
+
+/// Foo
+pub struct Xo;
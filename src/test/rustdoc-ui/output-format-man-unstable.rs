@@ -0,0 +1,4 @@
+// compile-flags:--output-format man
+
+/// Foo
+pub struct Xo;
@@ -0,0 +1,6 @@
+#![crate_name = "dep"]
+
+#[macro_export]
+macro_rules! dep_macro {
+    () => {};
+}
@@ -0,0 +1,33 @@
+#![feature(doc_cfg)]
+#![doc(auto_cfg)]
+#![doc(cfg_hide(test))]
+
+// @has doc_auto_cfg/fn.unix_only.html \
+//  '//*[@id="main"]/*[@class="stability"]/*[@class="stab portability"]' \
+//  'This is supported on Unix only.'
+#[cfg(any(unix, doc))]
+pub fn unix_only() {}
+
+// @has doc_auto_cfg/fn.not_test.html
+// @count - '//*[@class="stab portability"]' 0
+#[cfg(not(test))]
+pub fn not_test() {}
+
+// @has doc_auto_cfg/struct.Duplicated.html \
+//  '//*[@id="main"]/*[@class="stability"]/*[@class="stab portability"]' \
+//  'This is supported on Unix only.'
+#[cfg(any(unix, doc))]
+#[doc(cfg(unix))]
+pub struct Duplicated;
+
+// @has doc_auto_cfg/unix_mod/index.html \
+//  '//*[@id="main"]/*[@class="stability"]/*[@class="stab portability"]' \
+//  'This is supported on Unix only.'
+#[cfg(any(unix, doc))]
+pub mod unix_mod {
+    // @has doc_auto_cfg/unix_mod/fn.with_feature.html \
+    //  '//*[@id="main"]/*[@class="stability"]/*[@class="stab portability"]' \
+    //  'This is supported on Unix and feature="serde" only.'
+    #[cfg(any(feature = "serde", doc))]
+    pub fn with_feature() {}
+}
@@ -0,0 +1,12 @@
+// aux-build:intra-link-extern-macro.rs
+// build-aux-docs
+
+// Macros of other crates can be linked to by their path, and not only by their name.
+
+#![deny(intra_doc_link_resolution_failure)]
+
+// @has intra_link_extern_macro/index.html
+// @has - '//a/@href' '../dep/macro.dep_macro.html'
+//! [`dep::dep_macro!`]
+
+extern crate dep;
@@ -0,0 +1,42 @@
+// aux-build:link-to-definition-dep.rs
+// build-aux-docs
+// compile-flags: -Z unstable-options --generate-link-to-definition
+// ignore-tidy-linelength
+
+#![crate_name = "foo"]
+
+extern crate link_to_definition_dep as dep;
+
+// The file of a private module is rendered when it is linked to.
+// @has 'src/foo/link-to-definition/private.rs.html'
+// @has - '//a[@href="../../../src/foo/link-to-definition/private.rs.html#3"]' 'x'
+// @has - '//a[@href="../../../link_to_definition_dep/struct.Dep.html"]' 'Dep'
+// @has 'src/foo/link-to-definition.rs.html'
+// @has - '//a[@href="../../src/foo/link-to-definition/private.rs.html#1"]' 'private'
+#[path = "link-to-definition/private.rs"]
+mod private;
+
+pub struct Bar {
+    pub x: u32,
+}
+
+impl Bar {
+    pub fn new() -> Bar {
+        Bar { x: 0 }
+    }
+
+    pub fn get(&self) -> u32 {
+        self.x
+    }
+}
+
+// @has - '//a[@href="../../link_to_definition_dep/struct.Dep.html"]' 'Dep'
+// @has - '//a[@href="../../src/foo/link-to-definition.rs.html#19"]' 'Bar'
+// @has - '//a[@href="../../src/foo/link-to-definition.rs.html#24"]' 'new'
+// @has - '//a[@href="../../src/foo/link-to-definition.rs.html#28"]' 'get'
+// @has - '//a[@href="../../src/foo/link-to-definition.rs.html#40"]' 'bar'
+// @has - '//a[@href="../../src/foo/link-to-definition/private.rs.html#3"]' 'helper'
+pub fn foo(d: dep::Dep) -> u32 {
+    let bar = Bar::new();
+    private::helper(bar.get(), d)
+}
@@ -0,0 +1,5 @@
+use dep::Dep;
+
+pub fn helper(x: u32, _: Dep) -> u32 {
+    x
+}
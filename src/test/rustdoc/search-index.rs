@@ -2,7 +2,9 @@
 
 use std::ops::Deref;
 
-// @has search-index.js Foo
+// @has search-index.js rustdoc_test
+// @!has - Foo
+// @has search-index/rustdoc_test.js Foo
 pub use private::Foo;
 
 mod private {
@@ -20,7 +22,7 @@ mod private {
 pub struct Bar;
 
 impl Deref for Bar {
-    // @!has search-index.js Target
+    // @!has search-index/rustdoc_test.js Target
     type Target = Bar;
     fn deref(&self) -> &Bar { self }
 }
@@ -0,0 +1,70 @@
+// Tests the errors of `#[derive(SessionDiagnostic)]`.
+
+// ignore-stage1
+
+#![feature(rustc_private)]
+#![crate_type = "lib"]
+
+extern crate rustc_errors;
+extern crate rustc_macros;
+extern crate rustc_session;
+extern crate rustc_span;
+
+use rustc_errors::Applicability;
+use rustc_macros::SessionDiagnostic;
+use rustc_span::Span;
+
+#[derive(SessionDiagnostic)]
+#[code = "E0124"]
+#[note = "`{name}` is a name"]
+struct Correct {
+    name: String,
+    #[message = "`{name}` is {{wrong}}"]
+    #[label = "this is `{name:?}`"]
+    span: Span,
+    #[suggestion(message = "use `{name}`", code = "{name}", applicability = "MaybeIncorrect")]
+    suggestion: Option<Span>,
+    #[suggestion(message = "remove it", code = "")]
+    removal: (Span, Applicability),
+    #[help = "declared here"]
+    help: Option<(Span, Applicability)>,
+}
+
+#[derive(SessionDiagnostic)]
+enum NotAStruct {} //~ ERROR `#[derive(SessionDiagnostic)]` can only be used on structs
+
+#[derive(SessionDiagnostic)]
+#[code = "E12"] //~ ERROR error codes are written `E` and four digits
+#[message = "message"]
+struct InvalidCode {}
+
+#[derive(SessionDiagnostic)]
+struct NoMessage { //~ ERROR a diagnostic needs a `#[message = "..."]`
+    #[label = "label"]
+    span: Span,
+}
+
+#[derive(SessionDiagnostic)]
+struct UnknownField {
+    #[message = "`{name}` is unknown"] //~ ERROR `{name}` doesn't refer to a field of the struct
+    span: Span,
+}
+
+#[derive(SessionDiagnostic)]
+#[message = "message"]
+struct NotASpan {
+    #[label = "label"] //~ ERROR diagnostic attributes can only be used on fields of type `Span`
+    name: String,
+}
+
+#[derive(SessionDiagnostic)]
+#[message = "message"]
+struct InvalidApplicability {
+    #[suggestion(message = "m", code = "c", applicability = "Sometimes")] //~ ERROR expected one of
+    span: Span,
+}
+
+#[derive(SessionDiagnostic)]
+#[message = "message"]
+#[label = "label"] //~ ERROR this attribute goes on a field
+struct LabelOnStruct {}
@@ -0,0 +1,6 @@
+// check that #[test_retries] is feature-gated
+
+#[test_retries(2)] //~ ERROR the `#[test_retries]` attribute is an experimental feature
+fn flaky() {}
+
+fn main() {}
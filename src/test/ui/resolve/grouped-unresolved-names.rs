@@ -0,0 +1,12 @@
+// Checks that the errors about the uses of a name that was already found missing are grouped
+// with the first error, unless `-Z verbose-diagnostics` is given, which compiletest passes.
+
+// revisions: grouped verbose
+//[grouped] compile-flags: -Z verbose-diagnostics=no
+
+fn main() {
+    let a: Missing = 1; //~ ERROR cannot find type `Missing` in this scope
+    let b: Missing = 2; //[verbose]~ ERROR cannot find type `Missing` in this scope
+    let c: Missing = 3; //[verbose]~ ERROR cannot find type `Missing` in this scope
+    let d: Other = 4; //~ ERROR cannot find type `Other` in this scope
+}
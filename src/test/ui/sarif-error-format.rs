@@ -0,0 +1,8 @@
+// compile-flags: --error-format=sarif -Z unstable-options --remap-path-prefix={{src-base}}=remapped
+// check-pass
+
+#![warn(unused_variables)]
+
+fn main() {
+    let x = 1;
+}
@@ -0,0 +1,11 @@
+// compile-flags: --test
+#![feature(test_retries)]
+
+#[test]
+#[test_retries(2)]
+fn test1() {}
+
+#[test]
+#[test_retries(twice)]
+//~^ ERROR: argument must be a number of retries
+fn test2() {}
@@ -0,0 +1,16 @@
+// compile-flags: --test
+#![feature(test_timeout)]
+
+#[test]
+#[test_timeout(60)]
+fn test1() {}
+
+#[test]
+#[test_timeout(sixty)]
+//~^ ERROR: argument must be a number of seconds
+fn test2() {}
+
+#[test]
+#[test_timeout(0)]
+//~^ ERROR: argument for `test_timeout` must not be 0
+fn test3() {}
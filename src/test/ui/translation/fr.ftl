@@ -0,0 +1,3 @@
+# A partial French catalog: the label of the first use of the field isn't translated.
+typeck-field-multiply-specified = champ `{$ident}` spécifié plusieurs fois
+typeck-field-multiply-specified-label = utilisé plusieurs fois
@@ -0,0 +1,4 @@
+// compile-flags: -Z translate-lang=xx-XX
+// normalize-stderr-test: "expected one at `.*`" -> "expected one at `$$PATH`"
+
+fn main() {}
@@ -0,0 +1,16 @@
+// Check that the diagnostics are translated with the catalog given to `-Z translate-catalog`, and
+// that the messages it doesn't have are left in English.
+//
+// compile-flags: -Z translate-lang=fr -Z translate-catalog={{src-base}}/translation/fr.ftl
+
+struct Foo {
+    x: i32
+}
+
+fn main() {
+    let x = Foo {
+        x: 0,
+        x: 0,
+        //~^ ERROR champ `x` spécifié plusieurs fois
+    };
+}
@@ -445,7 +445,15 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         list: false,
         options: test::Options::new(),
         time_options: None,
+        test_timeout: None,
         force_run_in_process: false,
+        isolate: false,
+        shuffle: false,
+        shuffle_seed: None,
+        save_baseline: None,
+        baseline: None,
+        shard: None,
+        retries: 0,
     }
 }
 
@@ -602,6 +610,8 @@ fn make_test(config: &Config, testpaths: &TestPaths, inputs: &Stamp) -> Vec<test
                     ignore,
                     should_panic,
                     allow_fail: false,
+                    timeout: None,
+                    retries: None,
                     test_type: test::TestType::Unknown,
                 },
                 testfn: make_test_closure(config, testpaths, revision),
@@ -1894,6 +1894,7 @@ impl<'test> TestCx<'test> {
                 }
                 rustc.arg("-Zui-testing");
                 rustc.arg("-Zdeduplicate-diagnostics=no");
+                rustc.arg("-Zverbose-diagnostics");
             }
             Ui => {
                 if !self.props.compile_flags.iter().any(|s| s.starts_with("--error-format")) {
@@ -1901,6 +1902,7 @@ impl<'test> TestCx<'test> {
                 }
                 rustc.arg("-Zui-testing");
                 rustc.arg("-Zdeduplicate-diagnostics=no");
+                rustc.arg("-Zverbose-diagnostics");
             }
             MirOpt => {
                 rustc.args(&[
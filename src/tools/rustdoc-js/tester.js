@@ -218,13 +218,12 @@ function lookForEntry(entry, data) {
     return null;
 }
 
-function loadMainJsAndIndex(mainJs, searchIndex, storageJs, crate) {
-    if (searchIndex[searchIndex.length - 1].length === 0) {
-        searchIndex.pop();
-    }
-    searchIndex.pop();
-    var fullSearchIndex = searchIndex.join("\n") + '\nexports.rawSearchIndex = searchIndex;';
-    searchIndex = loadContent(fullSearchIndex);
+function loadMainJsAndIndex(mainJs, searchIndexShards, storageJs, crate) {
+    // Each shard calls `searchIndexShardLoaded` with the search index of its crate.
+    var searchIndex = loadContent(
+        'var rawSearchIndex = {};\n' +
+        'function searchIndexShardLoaded(crate, shard) { rawSearchIndex[crate] = shard; }\n' +
+        searchIndexShards.join("\n") + '\nexports.rawSearchIndex = rawSearchIndex;');
     var finalJS = "";
 
     var arraysToLoad = ["itemTypes"];
@@ -340,10 +339,12 @@ function runChecks(testFile, loaded, index) {
 function load_files(doc_folder, resource_suffix, crate) {
     var mainJs = readFile(path.join(doc_folder, "main" + resource_suffix + ".js"));
     var storageJs = readFile(path.join(doc_folder, "storage" + resource_suffix + ".js"));
-    var searchIndex = readFile(
-        path.join(doc_folder, "search-index" + resource_suffix + ".js")).split("\n");
+    var shardsFolder = path.join(doc_folder, "search-index");
+    var searchIndexShards = fs.readdirSync(shardsFolder)
+        .filter(file => file.endsWith(resource_suffix + ".js"))
+        .map(file => readFile(path.join(shardsFolder, file)));
 
-    return loadMainJsAndIndex(mainJs, searchIndex, storageJs, crate);
+    return loadMainJsAndIndex(mainJs, searchIndexShards, storageJs, crate);
 }
 
 function showHelp() {
@@ -1,10 +1,11 @@
 //! Checks that all error codes have at least one test to prevent having error
-//! codes that are silently not thrown by the compiler anymore.
+//! codes that are silently not thrown by the compiler anymore, and that the
+//! error codes of the `#[derive(SessionDiagnostic)]` diagnostics are registered.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
 use std::fs::read_to_string;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // A few of those error codes can't be tested but all the others can and *should* be tested!
 const WHITELIST: &[&str] = &[
@@ -80,6 +81,7 @@ macro_rules! some_or_continue {
 fn extract_error_codes(
     f: &str,
     error_codes: &mut HashMap<String, bool>,
+    registered: &mut HashSet<String>,
     path: &Path,
     errors: &mut Vec<String>,
 ) {
@@ -90,6 +92,7 @@ fn extract_error_codes(
         if !reached_no_explanation && s.starts_with('E') && s.contains("include_str!(\"") {
             if let Some(err_code) = s.splitn(2, ':').next() {
                 let err_code = err_code.to_owned();
+                registered.insert(err_code.clone());
                 if !error_codes.contains_key(&err_code) {
                     error_codes.insert(err_code.clone(), false);
                 }
@@ -125,6 +128,7 @@ fn extract_error_codes(
         } else if reached_no_explanation && s.starts_with('E') {
             if let Some(err_code) = s.splitn(2, ',').next() {
                 let err_code = err_code.to_owned();
+                registered.insert(err_code.clone());
                 if !error_codes.contains_key(&err_code) {
                     // this check should *never* fail!
                     error_codes.insert(err_code, false);
@@ -150,18 +154,57 @@ fn extract_error_codes_from_tests(f: &str, error_codes: &mut HashMap<String, boo
     }
 }
 
+/// Extracts the error codes given to diagnostics with `#[code = "E0000"]`.
+fn extract_error_codes_from_diagnostics(
+    f: &str,
+    path: &Path,
+    diagnostic_codes: &mut Vec<(String, PathBuf)>,
+) {
+    for line in f.lines() {
+        let s = line.trim();
+        if s.starts_with("#[code = \"E") {
+            if let Some(err_code) = s.splitn(3, '"').nth(1) {
+                diagnostic_codes.push((err_code.to_owned(), path.to_owned()));
+            }
+        }
+    }
+}
+
 pub fn check(path: &Path, bad: &mut bool) {
     let mut errors = Vec::new();
     println!("Checking which error codes lack tests...");
     let mut error_codes: HashMap<String, bool> = HashMap::new();
+    let mut registered = HashSet::new();
+    let mut diagnostic_codes = Vec::new();
+    let test_dir = path.join("test");
     super::walk(path, &mut |path| super::filter_dirs(path), &mut |entry, contents| {
         let file_name = entry.file_name();
         if file_name == "error_codes.rs" {
-            extract_error_codes(contents, &mut error_codes, entry.path(), &mut errors);
+            extract_error_codes(
+                contents,
+                &mut error_codes,
+                &mut registered,
+                entry.path(),
+                &mut errors,
+            );
         } else if entry.path().extension() == Some(OsStr::new("stderr")) {
             extract_error_codes_from_tests(contents, &mut error_codes);
+        } else if entry.path().extension() == Some(OsStr::new("rs"))
+            && !entry.path().starts_with(&test_dir)
+        {
+            // Tests may use invalid error codes on purpose.
+            extract_error_codes_from_diagnostics(contents, entry.path(), &mut diagnostic_codes);
         }
     });
+    for (err_code, path) in &diagnostic_codes {
+        if !registered.contains(err_code) {
+            errors.push(format!(
+                "`{}` uses error code {}, which isn't registered in `rustc_error_codes`",
+                path.display(),
+                err_code,
+            ));
+        }
+    }
     if errors.is_empty() {
         println!("Found {} error codes", error_codes.len());
 